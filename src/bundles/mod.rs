@@ -56,6 +56,7 @@ impl SystemBundle {
         boundary: SystemBoundary,
         meshes: &mut ResMut<Assets<Mesh>>,
         zoom: f32,
+        scaling_factor: f32,
         nesting_level: u16,
         name: &str,
         description: &str,
@@ -63,7 +64,7 @@ impl SystemBundle {
         let zoomed_radius = radius * zoom;
 
         let (simplified_mesh, path) = get_system_geometry_from_radius(zoomed_radius);
-        let scale = NestingLevel::compute_scale(nesting_level, zoom);
+        let scale = NestingLevel::compute_scale(nesting_level, zoom, scaling_factor);
         let time_unit = "Second".to_string();
         Self {
             system: System {
@@ -72,9 +73,11 @@ impl SystemBundle {
                 membership: 1.0,
                 equivalence: "".to_string(),
                 transformation: "".to_string(),
+                transformation_rule: None,
                 history: "".to_string(),
                 boundary,
                 time_unit,
+                attributes: vec![],
             },
             name: Name::new(name.to_string()),
             description: ElementDescription::new(description),
@@ -1,9 +1,6 @@
 use crate::components::*;
 use crate::constants::{BUTTON_WIDTH_HALF, BUTTON_Z};
-use crate::systems::{
-    on_create_button_click, on_external_entity_create_button_click, on_flow_terminal_button_click,
-    on_subsystem_button_click,
-};
+use crate::systems::on_create_button_pointer_click;
 use bevy::prelude::*;
 use bevy_mod_picking::prelude::*;
 
@@ -45,16 +42,6 @@ pub fn spawn_create_button(
 
     let button_width = BUTTON_WIDTH_HALF * 2.0;
 
-    let on_click_handler = match create_button.ty {
-        CreateButtonType::Subsystem => On::<Pointer<Click>>::run(on_subsystem_button_click),
-        CreateButtonType::FlowTerminalStart | CreateButtonType::FlowTerminalEnd => {
-            On::<Pointer<Click>>::run(on_flow_terminal_button_click)
-        }
-        CreateButtonType::Source | CreateButtonType::Sink => {
-            On::<Pointer<Click>>::run(on_external_entity_create_button_click)
-        }
-        _ => On::<Pointer<Click>>::run(on_create_button_click),
-    };
     let button_entity = commands
         .spawn((
             create_button,
@@ -69,7 +56,7 @@ pub fn spawn_create_button(
                 ..default()
             },
             PickableBundle::default(),
-            on_click_handler,
+            On::<Pointer<Click>>::run(on_create_button_pointer_click),
             InitialPosition::new(position),
             Name::new(name),
         ))
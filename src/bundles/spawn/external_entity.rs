@@ -4,10 +4,11 @@ use crate::constants::{
     EXTERNAL_ENTITY_WIDTH_HALF, EXTERNAL_ENTITY_Z,
 };
 use crate::events::ExternalEntityDrag;
-use crate::plugins::label::{add_name_label, Alignment, CopyPositionArgs};
+use crate::plugins::label::{add_name_label, Alignment, BackgroundArgs, CopyPositionArgs};
 use crate::plugins::lyon_selection::HighlightBundles;
 use crate::plugins::mouse_interaction::DragPosition;
 use crate::plugins::mouse_interaction::PickSelection;
+use crate::plugins::theme::{label_background, Themes};
 use crate::resources::{FixedSystemElementGeometriesByNestingLevel, StrokeTessellator};
 use crate::utils::ui_transform_from_button;
 use bevy::math::{vec2, vec3};
@@ -26,6 +27,7 @@ pub fn spawn_external_entity(
     transform: &Transform,
     fixed_system_element_geometries: &mut ResMut<FixedSystemElementGeometriesByNestingLevel>,
     zoom: f32,
+    scaling_factor: f32,
     is_selected: bool,
     meshes: &mut ResMut<Assets<Mesh>>,
     tess: &mut ResMut<StrokeTessellator>,
@@ -56,6 +58,7 @@ pub fn spawn_external_entity(
         initial_position,
         nesting_level,
         zoom,
+        scaling_factor,
         fixed_system_element_geometries,
         meshes,
         tess,
@@ -97,19 +100,21 @@ pub fn spawn_external_entity_only(
     initial_position: InitialPosition,
     nesting_level: u16,
     zoom: f32,
+    scaling_factor: f32,
     fixed_system_element_geometries: &mut ResMut<FixedSystemElementGeometriesByNestingLevel>,
     meshes: &mut ResMut<Assets<Mesh>>,
     tess: &mut ResMut<StrokeTessellator>,
 ) -> Entity {
     let color = substance_type.flow_color();
 
-    let scale = NestingLevel::compute_scale(nesting_level, zoom);
+    let scale = NestingLevel::compute_scale(nesting_level, zoom, scaling_factor);
 
     commands
         .spawn((
             ExternalEntity {
                 equivalence: "".to_string(),
                 model: "".to_string(),
+                attributes: vec![],
             },
             SpatialBundle {
                 transform,
@@ -131,7 +136,7 @@ pub fn spawn_external_entity_only(
             ElementDescription::new(description),
             initial_position,
             fixed_system_element_geometries
-                .get_or_create(nesting_level, zoom, meshes, tess)
+                .get_or_create(nesting_level, zoom, scaling_factor, meshes, tess)
                 .external_entity,
             NestingLevel::new(nesting_level),
             On::<DragPosition>::send_event::<ExternalEntityDrag>(),
@@ -145,13 +150,19 @@ pub fn auto_spawn_external_entity_label(
     external_entity_query: Query<(Entity, &NestingLevel), Added<ExternalEntity>>,
     name_query: Query<&Name>,
     asset_server: Res<AssetServer>,
+    themes: Res<Themes>,
 ) {
+    let theme = themes.current();
+
     for (external_entity, nesting_level) in external_entity_query.iter() {
         add_name_label(
             &mut commands,
             external_entity,
             vec2(70.0, 100.0),
-            None,
+            Some(BackgroundArgs {
+                color: label_background(&theme, **nesting_level),
+                ..default()
+            }),
             Some(CopyPositionArgs {
                 offset: vec3(1.0, 0.0, 0.0),
                 horizontal_alignment: Alignment::Auto,
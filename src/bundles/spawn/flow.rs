@@ -3,8 +3,10 @@ use crate::components::*;
 use crate::constants::*;
 use crate::data_model::Transform2d;
 use crate::plugins::label::{add_name_label, BackgroundArgs};
+use crate::plugins::layout::FlowRoute;
 use crate::plugins::lyon_selection::HighlightBundles;
 use crate::plugins::mouse_interaction::PickSelection;
+use crate::plugins::theme::{label_background, Themes};
 use crate::resources::{
     FixedSystemElementGeometriesByNestingLevel, FocusedSystem, StrokeTessellator,
 };
@@ -36,6 +38,8 @@ macro_rules! spawn_flow {
             stroke_tess: &mut ResMut<StrokeTessellator>,
             meshes: &mut ResMut<Assets<Mesh>>,
             zoom: f32,
+            scaling_factor: f32,
+            flow_length: f32,
             is_selected: bool,
             substance_type: SubstanceType,
             usability: InteractionUsability,
@@ -55,12 +59,13 @@ macro_rules! spawn_flow {
                     .get(subsystem.parent_system)
                     .expect("Parent system has to exist");
 
-                SUBSYSTEM_SCALING_FACTOR * system.radius / MAIN_SYSTEM_RADIUS
+                scaling_factor * system.radius / MAIN_SYSTEM_RADIUS
             } else {
                 1.0
             };
 
-            let flow_curve = FlowCurve::$curve_method(zoom, initial_position, direction, scale);
+            let flow_curve =
+                FlowCurve::$curve_method(zoom, initial_position, direction, scale, flow_length);
 
             spawn_interaction(
                 commands,
@@ -76,6 +81,9 @@ macro_rules! spawn_flow {
                     substance_type,
                     substance_sub_type: "".to_string(),
                     amount,
+                    amount_origin: AmountOrigin::Entered,
+                    formula: None,
+                    uncertainty: None,
                     unit: unit.to_string(),
                     usability,
                     parameters: vec![],
@@ -86,7 +94,7 @@ macro_rules! spawn_flow {
                 },
                 is_selected,
                 nesting_level,
-                NestingLevel::compute_scale(nesting_level, zoom),
+                NestingLevel::compute_scale(nesting_level, zoom, scaling_factor),
             )
         }
     };
@@ -146,7 +154,7 @@ pub fn spawn_interaction_only(
     stroke_tess: &mut ResMut<StrokeTessellator>,
     meshes: &mut ResMut<Assets<Mesh>>,
 ) -> Entity {
-    let curve_path = create_path_from_flow_curve(&flow_curve, scale);
+    let curve_path = create_path_from_flow_curve(&flow_curve, &[], scale);
 
     let mut head_path_builder = PathBuilder::new();
 
@@ -156,7 +164,7 @@ pub fn spawn_interaction_only(
     head_path_builder.close();
     let head_path = head_path_builder.build();
 
-    let aabb = create_aabb_from_flow_curve(&flow_curve);
+    let aabb = create_aabb_from_flow_curve(&flow_curve, &[]);
 
     let color = flow.substance_type.flow_color();
 
@@ -164,6 +172,7 @@ pub fn spawn_interaction_only(
         .spawn((
             flow,
             flow_curve,
+            FlowRoute::default(),
             SimplifiedMesh {
                 mesh: tessellate_simplified_mesh(&curve_path, meshes, stroke_tess),
             },
@@ -222,6 +231,8 @@ macro_rules! spawn_complete_flow {
                 FixedSystemElementGeometriesByNestingLevel,
             >,
             zoom: f32,
+            scaling_factor: f32,
+            flow_length: f32,
             interface_angle: f32,
             system_radius: f32,
             substance_type: SubstanceType,
@@ -254,6 +265,8 @@ macro_rules! spawn_complete_flow {
                 &mut stroke_tess,
                 &mut meshes,
                 zoom,
+                scaling_factor,
+                flow_length,
                 false,
                 substance_type,
                 usability,
@@ -273,6 +286,7 @@ macro_rules! spawn_complete_flow {
                 *focused_system,
                 fixed_system_element_geometries,
                 zoom,
+                scaling_factor,
                 false,
                 meshes,
                 stroke_tess,
@@ -287,7 +301,7 @@ macro_rules! spawn_complete_flow {
                 .with_rotation(Quat::from_rotation_z(t2d.rotation))
             } else {
                 let right = transform.right();
-                transform.translation += right * FLOW_LENGTH;
+                transform.translation += right * flow_length;
                 transform
             };
 
@@ -302,6 +316,7 @@ macro_rules! spawn_complete_flow {
                 &transform,
                 fixed_system_element_geometries,
                 zoom,
+                scaling_factor,
                 false,
                 meshes,
                 stroke_tess,
@@ -323,13 +338,12 @@ pub fn auto_spawn_flow_label(
     flow_query: Query<(Entity, &NestingLevel), Added<Flow>>,
     name_query: Query<&Name>,
     asset_server: Res<AssetServer>,
+    themes: Res<Themes>,
 ) {
+    let theme = themes.current();
+
     for (flow_entity, nesting_level) in flow_query.iter() {
-        let color = if **nesting_level == 0 {
-            CLEAR_COLOR
-        } else {
-            Color::WHITE
-        };
+        let color = label_background(&theme, **nesting_level);
 
         add_name_label(
             &mut commands,
@@ -3,10 +3,11 @@ use crate::constants::{
     INTERFACE_HEIGHT_HALF, INTERFACE_LINE_WIDTH, INTERFACE_SELECTED_LINE_WIDTH, INTERFACE_Z,
 };
 use crate::events::InterfaceDrag;
-use crate::plugins::label::{add_name_label, Alignment, CopyPositionArgs};
+use crate::plugins::label::{add_name_label, Alignment, BackgroundArgs, CopyPositionArgs};
 use crate::plugins::lyon_selection::HighlightBundles;
 use crate::plugins::mouse_interaction::DragPosition;
 use crate::plugins::mouse_interaction::PickSelection;
+use crate::plugins::theme::{label_background, Themes};
 use crate::resources::{FixedSystemElementGeometriesByNestingLevel, StrokeTessellator};
 use crate::utils::ui_transform_from_button;
 use bevy::math::{vec2, vec3};
@@ -24,6 +25,7 @@ pub fn spawn_interface(
     focused_system: Entity,
     fixed_system_element_geometries: &mut ResMut<FixedSystemElementGeometriesByNestingLevel>,
     zoom: f32,
+    scaling_factor: f32,
     is_selected: bool,
     meshes: &mut ResMut<Assets<Mesh>>,
     tess: &mut ResMut<StrokeTessellator>,
@@ -42,6 +44,7 @@ pub fn spawn_interface(
         nesting_level,
         focused_system,
         zoom,
+        scaling_factor,
         is_selected,
         name,
         description,
@@ -76,6 +79,7 @@ pub fn spawn_interface_only(
     nesting_level: u16,
     parent_system: Entity,
     zoom: f32,
+    scaling_factor: f32,
     is_selected: bool,
     name: &str,
     description: &str,
@@ -85,7 +89,7 @@ pub fn spawn_interface_only(
     meshes: &mut ResMut<Assets<Mesh>>,
     fixed_system_element_geometries: &mut ResMut<FixedSystemElementGeometriesByNestingLevel>,
 ) -> Entity {
-    let scale = NestingLevel::compute_scale(nesting_level, zoom);
+    let scale = NestingLevel::compute_scale(nesting_level, zoom, scaling_factor);
 
     let interface_entity = commands
         .spawn((
@@ -106,7 +110,7 @@ pub fn spawn_interface_only(
             ElementDescription::new(description),
             initial_position,
             fixed_system_element_geometries
-                .get_or_create(nesting_level, zoom, meshes, tess)
+                .get_or_create(nesting_level, zoom, scaling_factor, meshes, tess)
                 .interface
                 .clone(),
             NestingLevel::new(nesting_level),
@@ -123,13 +127,19 @@ pub fn auto_spawn_interface_label(
     interface_query: Query<(Entity, &NestingLevel), Added<Interface>>,
     name_query: Query<&Name>,
     asset_server: Res<AssetServer>,
+    themes: Res<Themes>,
 ) {
+    let theme = themes.current();
+
     for (interface, nesting_level) in interface_query.iter() {
         add_name_label(
             &mut commands,
             interface,
             vec2(50.0, 45.0),
-            None,
+            Some(BackgroundArgs {
+                color: label_background(&theme, **nesting_level),
+                ..default()
+            }),
             Some(CopyPositionArgs {
                 offset: vec3(INTERFACE_HEIGHT_HALF * 1.7, -INTERFACE_HEIGHT_HALF, 0.0),
                 horizontal_alignment: Alignment::AutoStartEnd,
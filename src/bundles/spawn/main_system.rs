@@ -13,6 +13,7 @@ pub fn spawn_main_system(
     complexity: Complexity,
     boundary: SystemBoundary,
     zoom: f32,
+    scaling_factor: f32,
     name: &str,
     description: &str,
     meshes: &mut ResMut<Assets<Mesh>>,
@@ -28,6 +29,7 @@ pub fn spawn_main_system(
                 boundary,
                 meshes,
                 zoom,
+                scaling_factor,
                 0,
                 name,
                 description,
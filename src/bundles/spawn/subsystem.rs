@@ -31,6 +31,7 @@ pub fn spawn_interface_subsystem(
     focused_system: &Res<FocusedSystem>,
     meshes: &mut ResMut<Assets<Mesh>>,
     zoom: f32,
+    scaling_factor: f32,
     name: &str,
     description: &str,
 ) -> Entity {
@@ -82,6 +83,7 @@ pub fn spawn_interface_subsystem(
         nesting_level_query,
         meshes,
         zoom,
+        scaling_factor,
         name,
         description,
         angle,
@@ -130,6 +132,7 @@ fn spawn_subsystem_common(
     nesting_level_query: &Query<&NestingLevel>,
     meshes: &mut ResMut<Assets<Mesh>>,
     zoom: f32,
+    scaling_factor: f32,
     name: &str,
     description: &str,
     angle: f32,
@@ -168,6 +171,7 @@ fn spawn_subsystem_common(
                     Default::default(),
                     meshes,
                     zoom,
+                    scaling_factor,
                     nesting_level,
                     name,
                     description,
@@ -192,6 +196,7 @@ pub fn spawn_subsystem(
     meshes: &mut ResMut<Assets<Mesh>>,
     tess: &mut ResMut<StrokeTessellator>,
     zoom: f32,
+    scaling_factor: f32,
     name: &str,
     description: &str,
     position: Vec2,
@@ -202,6 +207,7 @@ pub fn spawn_subsystem(
         nesting_level_query,
         meshes,
         zoom,
+        scaling_factor,
         name,
         description,
         0.0,
@@ -235,6 +241,7 @@ pub fn spawn_subsystem(
             subsystem_entity,
             fixed_system_element_geometries,
             zoom,
+            scaling_factor,
             true,
             meshes,
             tess,
@@ -269,6 +276,7 @@ pub fn spawn_subsystem(
             subsystem_entity,
             fixed_system_element_geometries,
             zoom,
+            scaling_factor,
             true,
             meshes,
             tess,
@@ -1,10 +1,12 @@
 //! All app components referenced across modules.
 mod connections;
+mod parameter;
 mod system_elements;
 mod ui;
 mod zoom;
 
 pub use connections::*;
+pub use parameter::*;
 pub use system_elements::*;
 pub use ui::*;
 pub use zoom::*;
@@ -0,0 +1,363 @@
+//! Typed parameters of flows and their validation.
+use bevy::utils::HashSet;
+use regex::Regex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Represents a user-defined parameter stored in a Flow.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    /// Files from before parameters were typed store plain strings. These are migrated on load.
+    #[serde(deserialize_with = "deserialize_parameter_value")]
+    pub value: ParameterValue,
+}
+
+/// The typed value of a [`Parameter`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ParameterValue {
+    Text {
+        value: String,
+    },
+    Number {
+        value: Decimal,
+        unit: String,
+    },
+    Bool {
+        value: bool,
+    },
+    Enum {
+        value: String,
+        options: Vec<String>,
+    },
+    /// ISO 8601 date (YYYY-MM-DD).
+    Date {
+        value: String,
+    },
+    /// Name of another element of the model.
+    Reference {
+        value: String,
+    },
+}
+
+impl Default for ParameterValue {
+    fn default() -> Self {
+        ParameterValue::Text {
+            value: String::new(),
+        }
+    }
+}
+
+/// The type of a [`ParameterValue`] without its data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParameterKind {
+    Text,
+    Number,
+    Bool,
+    Enum,
+    Date,
+    Reference,
+}
+
+impl ParameterKind {
+    pub const ALL: [ParameterKind; 6] = [
+        ParameterKind::Text,
+        ParameterKind::Number,
+        ParameterKind::Bool,
+        ParameterKind::Enum,
+        ParameterKind::Date,
+        ParameterKind::Reference,
+    ];
+
+    pub fn default_value(&self) -> ParameterValue {
+        match self {
+            ParameterKind::Text => ParameterValue::default(),
+            ParameterKind::Number => ParameterValue::Number {
+                value: Decimal::ZERO,
+                unit: String::new(),
+            },
+            ParameterKind::Bool => ParameterValue::Bool { value: false },
+            ParameterKind::Enum => ParameterValue::Enum {
+                value: String::new(),
+                options: vec![],
+            },
+            ParameterKind::Date => ParameterValue::Date {
+                value: String::new(),
+            },
+            ParameterKind::Reference => ParameterValue::Reference {
+                value: String::new(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ParameterKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl ParameterValue {
+    pub fn kind(&self) -> ParameterKind {
+        match self {
+            ParameterValue::Text { .. } => ParameterKind::Text,
+            ParameterValue::Number { .. } => ParameterKind::Number,
+            ParameterValue::Bool { .. } => ParameterKind::Bool,
+            ParameterValue::Enum { .. } => ParameterKind::Enum,
+            ParameterValue::Date { .. } => ParameterKind::Date,
+            ParameterValue::Reference { .. } => ParameterKind::Reference,
+        }
+    }
+
+    /// Converts the value to a different kind and keeps as much of the data as possible.
+    pub fn convert_to(&self, kind: ParameterKind) -> ParameterValue {
+        if self.kind() == kind {
+            return self.clone();
+        }
+
+        match (ParameterValue::from_legacy(&self.to_string()), kind) {
+            (value, _) if value.kind() == kind => value,
+            (_, ParameterKind::Text) => ParameterValue::Text {
+                value: self.to_string(),
+            },
+            (_, ParameterKind::Enum) => ParameterValue::Enum {
+                value: self.to_string(),
+                options: vec![self.to_string()],
+            },
+            (_, ParameterKind::Reference) => ParameterValue::Reference {
+                value: self.to_string(),
+            },
+            _ => kind.default_value(),
+        }
+    }
+
+    /// Infers the type of a plain string value from files saved before parameters were typed.
+    /// The migration is lossless: a value is only converted if it's displayed exactly as it was
+    /// written, otherwise it stays a text. For example "007", "10kg" and " true" stay texts.
+    pub fn from_legacy(original: &str) -> ParameterValue {
+        let inferred = Self::infer(original);
+        if inferred.to_string() == original {
+            inferred
+        } else {
+            ParameterValue::Text {
+                value: original.to_string(),
+            }
+        }
+    }
+
+    fn infer(value: &str) -> ParameterValue {
+        if value == "true" || value == "false" {
+            return ParameterValue::Bool {
+                value: value == "true",
+            };
+        }
+
+        if is_valid_date(value) {
+            return ParameterValue::Date {
+                value: value.to_string(),
+            };
+        }
+
+        let (number, unit) = value.split_once(' ').unwrap_or((value, ""));
+
+        // A unit is a single word like "kg", "m/s" or "°C". This keeps "12 Main Street" a text.
+        let is_unit =
+            !unit.starts_with(|c: char| c.is_ascii_digit()) && !unit.contains(char::is_whitespace);
+
+        match Decimal::from_str(number) {
+            Ok(decimal) if is_unit => ParameterValue::Number {
+                value: decimal,
+                unit: unit.to_string(),
+            },
+            _ => ParameterValue::Text {
+                value: value.to_string(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ParameterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterValue::Number { value, unit } if unit.is_empty() => write!(f, "{}", value),
+            ParameterValue::Number { value, unit } => write!(f, "{} {}", value, unit),
+            ParameterValue::Bool { value } => write!(f, "{}", value),
+            ParameterValue::Text { value }
+            | ParameterValue::Enum { value, .. }
+            | ParameterValue::Date { value }
+            | ParameterValue::Reference { value } => write!(f, "{}", value),
+        }
+    }
+}
+
+fn deserialize_parameter_value<'de, D>(deserializer: D) -> Result<ParameterValue, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredValue {
+        Legacy(String),
+        Typed(ParameterValue),
+    }
+
+    Ok(match StoredValue::deserialize(deserializer)? {
+        StoredValue::Legacy(value) => ParameterValue::from_legacy(&value),
+        StoredValue::Typed(value) => value,
+    })
+}
+
+/// Checks that the string is a valid calendar date in the format YYYY-MM-DD.
+pub fn is_valid_date(value: &str) -> bool {
+    static DATE_REGEX: OnceLock<Regex> = OnceLock::new();
+    let regex =
+        DATE_REGEX.get_or_init(|| Regex::new(r"^(\d{4})-(\d{2})-(\d{2})$").expect("Valid regex"));
+
+    let Some(captures) = regex.captures(value) else {
+        return false;
+    };
+
+    let year: u32 = captures[1].parse().unwrap_or_default();
+    let month: u32 = captures[2].parse().unwrap_or_default();
+    let day: u32 = captures[3].parse().unwrap_or_default();
+
+    let is_leap_year =
+        (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year => 29,
+        2 => 28,
+        _ => return false,
+    };
+
+    (1..=days_in_month).contains(&day)
+}
+
+/// Validates a list of parameters against the parameters of a schema.
+/// `element_names` are the names of all elements that a reference parameter can point to.
+pub fn validate_parameters(
+    parameters: &[Parameter],
+    schema: &[Parameter],
+    element_names: &HashSet<String>,
+) -> Vec<String> {
+    let mut errors = vec![];
+    let mut seen_names = HashSet::new();
+
+    for parameter in parameters {
+        let name = parameter.name.trim();
+
+        if name.is_empty() {
+            errors.push("A parameter has no name".to_string());
+        } else if !seen_names.insert(name) {
+            errors.push(format!("\"{}\" is defined more than once", name));
+        }
+
+        match &parameter.value {
+            ParameterValue::Enum { value, options } if !options.contains(value) => {
+                errors.push(format!("\"{}\" is not an option of \"{}\"", value, name));
+            }
+            ParameterValue::Date { value } if !is_valid_date(value) => {
+                errors.push(format!(
+                    "\"{}\" needs a date in the format YYYY-MM-DD",
+                    name
+                ));
+            }
+            ParameterValue::Reference { value } if !element_names.contains(value) => {
+                errors.push(format!("\"{}\" references an unknown element", name));
+            }
+            _ => {}
+        }
+
+        if let Some(expected) = schema.iter().find(|p| p.name.trim() == name) {
+            if expected.value.kind() != parameter.value.kind() {
+                errors.push(format!(
+                    "\"{}\" should be a {} but is a {}",
+                    name,
+                    expected.value.kind(),
+                    parameter.value.kind()
+                ));
+            }
+        }
+    }
+
+    for expected in schema {
+        if !parameters
+            .iter()
+            .any(|p| p.name.trim() == expected.name.trim())
+        {
+            errors.push(format!("\"{}\" is missing", expected.name.trim()));
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn legacy_values_are_typed() {
+        assert_eq!(
+            ParameterValue::from_legacy("true"),
+            ParameterValue::Bool { value: true }
+        );
+        assert_eq!(
+            ParameterValue::from_legacy("2024-02-29"),
+            ParameterValue::Date {
+                value: "2024-02-29".to_string()
+            }
+        );
+        assert_eq!(
+            ParameterValue::from_legacy("-1.5"),
+            ParameterValue::Number {
+                value: dec!(-1.5),
+                unit: String::new()
+            }
+        );
+        assert_eq!(
+            ParameterValue::from_legacy("10 kg/s"),
+            ParameterValue::Number {
+                value: dec!(10),
+                unit: "kg/s".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn legacy_values_that_would_change_stay_text() {
+        for original in [
+            "10kg",
+            "12 Main Street",
+            "007",
+            " 5",
+            "5 ",
+            "10  kg",
+            "True",
+            "2023-02-29",
+            "1 2",
+            "",
+        ] {
+            assert_eq!(
+                ParameterValue::from_legacy(original),
+                ParameterValue::Text {
+                    value: original.to_string()
+                },
+                "{original:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn legacy_migration_is_lossless() {
+        for original in ["42", "3.14 m", "false", "1999-12-31", "hello world", "10kg"] {
+            assert_eq!(ParameterValue::from_legacy(original).to_string(), original);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 //! This file contains all the Bevy components and data structures related to System Elements and their associated helper methods.
+use super::Parameter;
 use crate::data_model::Complexity;
 use bevy::prelude::*;
 use rust_decimal::Decimal;
@@ -38,9 +39,58 @@ pub struct System {
     pub membership: f32,
     pub equivalence: String,
     pub transformation: String,
+    /// Structured counterpart of `transformation` that is used to propagate amounts.
+    #[reflect(ignore)]
+    pub transformation_rule: Option<TransformationRule>,
     pub history: String,
     pub boundary: SystemBoundary,
     pub time_unit: String,
+    /// Values of the user-defined system attributes.
+    #[reflect(ignore)]
+    pub attributes: Vec<Parameter>,
+}
+
+/// Describes how a system transforms its inflows into its outflows, for example
+/// `1 kg material + 3 kWh energy → 0.9 kg product + 0.1 kg waste`.
+/// The amounts are ratios. Scaling all inputs by a factor scales all outputs by the same factor.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TransformationRule {
+    pub inputs: Vec<RuleTerm>,
+    pub outputs: Vec<RuleTerm>,
+}
+
+/// One side of a [`TransformationRule`] consists of these terms.
+/// `substance` is matched against the name, substance sub type, substance type or usability of the
+/// interactions of the system (in that order and case insensitive).
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RuleTerm {
+    pub amount: Decimal,
+    pub unit: String,
+    pub substance: String,
+}
+
+impl std::fmt::Display for RuleTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.unit.is_empty() {
+            write!(f, "{} {}", self.amount, self.substance)
+        } else {
+            write!(f, "{} {} {}", self.amount, self.unit, self.substance)
+        }
+    }
+}
+
+impl std::fmt::Display for TransformationRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |terms: &[RuleTerm]| {
+            terms
+                .iter()
+                .map(|term| term.to_string())
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
+
+        write!(f, "{} → {}", join(&self.inputs), join(&self.outputs))
+    }
 }
 
 /// Attached to entities with a SystemElement::System component to hold modeling data related to the system's boundary.
@@ -64,7 +114,13 @@ pub struct SystemEnvironment {
 #[derive(Clone, Debug, Component, Reflect, PartialEq, Eq, Default)]
 #[reflect(Component)]
 pub struct Interface {
+    /// Name of the protocol in the protocol registry.
     pub protocol: String,
+    /// Version of the referenced protocol.
+    pub protocol_version: String,
+    /// Values of the user-defined interface attributes.
+    #[reflect(ignore)]
+    pub attributes: Vec<Parameter>,
 }
 
 /// Represents whether an interface imports into or exports flow out of a system. It's to determine control flow in different systems
@@ -83,16 +139,46 @@ pub struct Flow {
     pub substance_sub_type: String,
     #[reflect(ignore)]
     pub amount: Decimal,
+    pub amount_origin: AmountOrigin,
+    /// Expression that computes the amount, e.g. `0.3 * inflow("Fuel")`. Without the leading `=`.
+    #[reflect(ignore)]
+    pub formula: Option<String>,
+    /// Range or distribution around `amount` that is sampled by the Monte Carlo analysis.
+    #[reflect(ignore)]
+    pub uncertainty: Option<AmountUncertainty>,
     pub unit: String,
     pub usability: InteractionUsability,
+    #[reflect(ignore)]
     pub parameters: Vec<Parameter>,
 }
 
-/// Represents a user-defined parameter stored in a Flow.
-#[derive(Clone, Debug, Reflect, PartialEq, Eq, Default, Serialize, Deserialize)]
-pub struct Parameter {
-    pub name: String,
-    pub value: String,
+/// Whether the amount of a flow was entered by the user or computed from transformation rules.
+#[derive(Copy, Clone, Debug, Reflect, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum AmountOrigin {
+    #[default]
+    Entered,
+    Computed,
+}
+
+/// Uncertainty of a flow amount. The amount of the flow is the nominal value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AmountUncertainty {
+    /// Triangular distribution between min and max that peaks at the nominal amount.
+    Range { min: Decimal, max: Decimal },
+    /// Every value between min and max is equally likely.
+    Uniform { min: Decimal, max: Decimal },
+    /// Normal distribution around the nominal amount. Negative samples are clamped to zero.
+    Normal { std_dev: Decimal },
+}
+
+impl AmountUncertainty {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AmountUncertainty::Range { .. } => "Min / Max",
+            AmountUncertainty::Uniform { .. } => "Uniform",
+            AmountUncertainty::Normal { .. } => "Normal",
+        }
+    }
 }
 
 /// Corresponds to the System Language Interaction types.
@@ -109,6 +195,9 @@ pub enum InteractionType {
 pub struct ExternalEntity {
     pub equivalence: String,
     pub model: String,
+    /// Values of the user-defined external entity attributes.
+    #[reflect(ignore)]
+    pub attributes: Vec<Parameter>,
 }
 
 /// Attached to entities with a System component that are nested inside of a parent system.
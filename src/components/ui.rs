@@ -1,5 +1,4 @@
 use crate::components::{InitialPosition, InterfaceType, SubstanceType};
-use crate::constants::FLOW_LENGTH;
 use bevy::prelude::*;
 
 #[derive(Copy, Clone, Debug, Component, Reflect, PartialEq)]
@@ -74,11 +73,12 @@ impl FlowCurve {
         initial_position: InitialPosition,
         direction: Vec2,
         scale: f32,
+        length: f32,
     ) -> Self {
         let zoomed_pos = *initial_position * zoom;
 
         Self {
-            start: zoomed_pos + direction * FLOW_LENGTH * scale * zoom,
+            start: zoomed_pos + direction * length * scale * zoom,
             start_direction: -direction,
             end: zoomed_pos,
             end_direction: direction,
@@ -90,13 +90,14 @@ impl FlowCurve {
         initial_position: InitialPosition,
         direction: Vec2,
         scale: f32,
+        length: f32,
     ) -> Self {
         let zoomed_pos = *initial_position * zoom;
 
         Self {
             start: zoomed_pos,
             start_direction: direction,
-            end: zoomed_pos + direction * FLOW_LENGTH * scale * zoom,
+            end: zoomed_pos + direction * length * scale * zoom,
             end_direction: -direction,
         }
     }
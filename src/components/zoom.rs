@@ -1,6 +1,5 @@
 //! Holds components used to control the geometry of the diagram.
 //! See design/Geometry_High-Level_Overview.pdf for an overview of how these components work together.
-use crate::constants::SUBSYSTEM_SCALING_FACTOR;
 use bevy::prelude::*;
 use num_traits::Pow;
 
@@ -34,9 +33,11 @@ impl NestingLevel {
         }
     }
 
+    /// `scaling_factor` is the size of a subsystem relative to its parent, see
+    /// `Preferences::subsystem_scaling_factor`.
     #[inline(always)]
-    pub fn compute_scale(nesting_level: u16, zoom: f32) -> f32 {
-        (SUBSYSTEM_SCALING_FACTOR.pow(nesting_level) * zoom).min(1.0)
+    pub fn compute_scale(nesting_level: u16, zoom: f32, scaling_factor: f32) -> f32 {
+        (scaling_factor.pow(nesting_level) * zoom).min(1.0)
     }
 }
 
@@ -6,6 +6,14 @@ use bevy::prelude::{Color, ColorMaterial};
 pub const SCALE_VISIBILITY_THRESHOLD: f32 = 0.2;
 /// Minimum scale of a label before the visibility is switched to hidden.
 pub const LABEL_SCALE_VISIBILITY_THRESHOLD: f32 = 0.5;
+/// Default lower bound of the zoom.
+pub const MIN_ZOOM: f32 = 0.05;
+/// Default upper bound of the zoom.
+pub const MAX_ZOOM: f32 = 20.0;
+/// Zoom speed of the mouse wheel in zoom factor per pixel scrolled.
+pub const WHEEL_ZOOM_SPEED: f32 = 0.002;
+/// Number of pixels a mouse wheel line is equivalent to.
+pub const WHEEL_LINE_HEIGHT: f32 = 20.0;
 // Default z-index of a label.
 pub const LABEL_Z: f32 = 150.0;
 
@@ -67,7 +75,6 @@ pub const BUTTON_WIDTH_HALF: f32 = 16.0;
 /// Local z coordinate of a create-button.
 pub const BUTTON_Z: f32 = 200.0;
 
-
 /// Default half width of an external entity.
 pub const EXTERNAL_ENTITY_WIDTH_HALF: f32 = 20.0;
 /// Default half height of an external entity.
@@ -86,5 +93,5 @@ pub const EXTERNAL_ENTITY_Z: f32 = 1.0;
 pub const WHITE_COLOR_MATERIAL_HANDLE: Handle<ColorMaterial> =
     Handle::weak_from_u128(0xE4E775785EA288EEA9FB9EC95377D078);
 
-/// The default background color of the canvas. It's also used as the background color for flow labels in the environment.
+/// The background color of the canvas until the active theme is applied.
 pub const CLEAR_COLOR: Color = Color::ANTIQUE_WHITE;
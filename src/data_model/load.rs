@@ -5,9 +5,16 @@ use crate::bundles::{
 use crate::constants::{EXTERNAL_ENTITY_Z, INTERFACE_Z, SUBSYSTEM_Z};
 use crate::data_model::*;
 use crate::events::SubsystemDrag;
+use crate::plugins::annotations::spawn_annotation;
 use crate::plugins::file_dialog::ImportFileEvent;
+use crate::plugins::layout::FlowRoute;
 use crate::plugins::mouse_interaction::DragPosition;
+use crate::plugins::navigation::CameraBookmarks;
+use crate::plugins::preferences::Preferences;
+use crate::plugins::scenarios::{ElementValues, Scenarios};
+use crate::plugins::theme::Themes;
 use crate::resources::*;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use bevy_eventlistener::event_listener::On;
@@ -41,18 +48,80 @@ impl Context {
     }
 }
 
+/// Model-wide resources that are replaced by the loaded ones.
+#[derive(SystemParam)]
+pub struct ModelResourcesMut<'w> {
+    parameter_schemas: ResMut<'w, ParameterSchemas>,
+    attribute_schemas: ResMut<'w, AttributeSchemas>,
+    protocols: ResMut<'w, Protocols>,
+    substance_catalog: ResMut<'w, SubstanceCatalog>,
+    scenarios: ResMut<'w, Scenarios>,
+    camera_bookmarks: ResMut<'w, CameraBookmarks>,
+}
+
+/// Registers the protocols that the interfaces of a model from before the protocol registry
+/// reference, so they aren't reported as unknown. The substance type is taken from the first
+/// interaction at an interface with the protocol.
+fn legacy_protocols(world_model: &WorldModel) -> Vec<Protocol> {
+    let mut protocols = Vec::<Protocol>::new();
+
+    for interface in world_model
+        .systems
+        .iter()
+        .flat_map(|system| &system.boundary.interfaces)
+    {
+        let name = interface.protocol.trim();
+        let version = interface.protocol_version.trim();
+        if name.is_empty()
+            || protocols
+                .iter()
+                .any(|p| p.name == name && p.version == version)
+        {
+            continue;
+        }
+
+        let substance_type = world_model
+            .interactions
+            .iter()
+            .find(|interaction| {
+                interaction.source_interface.as_ref() == Some(&interface.info.id)
+                    || interaction.sink_interface.as_ref() == Some(&interface.info.id)
+            })
+            .map(|interaction| interaction.substance.ty)
+            .unwrap_or_default();
+
+        protocols.push(Protocol {
+            name: name.to_string(),
+            version: version.to_string(),
+            substance_type,
+            specification: String::new(),
+        });
+    }
+
+    protocols.sort_by_key(|protocol| protocol.label());
+    protocols
+}
+
 pub fn load_world(
     mut commands: Commands,
     mut load_file_event_reader: EventReader<ImportFileEvent>,
     existing_elements_query: Query<Entity, With<SystemElement>>,
+    existing_annotations_query: Query<Entity, With<crate::plugins::annotations::Annotation>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut stroke_tess: ResMut<StrokeTessellator>,
     mut fixed_system_element_geometries: ResMut<FixedSystemElementGeometriesByNestingLevel>,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
+    themes: Res<Themes>,
+    asset_server: Res<AssetServer>,
+    mut model_resources: ModelResourcesMut,
 ) {
     for event in load_file_event_reader.read() {
         // clear the scene first
-        for entity in &existing_elements_query {
+        for entity in existing_elements_query
+            .iter()
+            .chain(&existing_annotations_query)
+        {
             commands.entity(entity).despawn_recursive();
         }
 
@@ -61,6 +130,14 @@ pub fn load_world(
 
         let mut ctx = Context::new();
 
+        model_resources.parameter_schemas.0 = world_model.parameter_schemas.clone();
+        *model_resources.attribute_schemas = world_model.attribute_schemas.clone();
+        model_resources.protocols.0 = match &world_model.protocols {
+            Some(protocols) => protocols.clone(),
+            None => legacy_protocols(&world_model),
+        };
+        model_resources.substance_catalog.0 = world_model.substance_catalog.clone();
+
         // start by mapping all external entities to the substance type
         for interaction in &world_model.interactions {
             if matches!(interaction.sink.ty, IdType::Sink) {
@@ -81,6 +158,7 @@ pub fn load_world(
             &mut ctx,
             &world_model,
             **zoom,
+            preferences.subsystem_scaling_factor,
             &mut meshes,
             &mut stroke_tess,
             &mut fixed_system_element_geometries,
@@ -94,6 +172,7 @@ pub fn load_world(
             &world_model.environment,
             None,
             **zoom,
+            preferences.subsystem_scaling_factor,
             &mut meshes,
             &mut stroke_tess,
             &mut fixed_system_element_geometries,
@@ -104,9 +183,69 @@ pub fn load_world(
             &mut ctx,
             &world_model,
             **zoom,
+            preferences.subsystem_scaling_factor,
             &mut meshes,
             &mut stroke_tess,
         );
+
+        *model_resources.scenarios = Scenarios::new(
+            world_model
+                .scenarios
+                .iter()
+                .map(|scenario| crate::plugins::scenarios::Scenario {
+                    name: scenario.name.clone(),
+                    overrides: scenario
+                        .overrides
+                        .iter()
+                        .filter_map(|o| {
+                            let values = ElementValues {
+                                amount: o.amount,
+                                parameters: o.parameters.clone(),
+                                attributes: o.attributes.clone(),
+                                removed_parameters: o.removed_parameters.clone(),
+                                removed_attributes: o.removed_attributes.clone(),
+                            };
+                            Some((*ctx.id_to_entity.get(&o.element)?, values))
+                        })
+                        .collect(),
+                })
+                .collect(),
+        );
+
+        model_resources.camera_bookmarks.0 = world_model
+            .camera_bookmarks
+            .iter()
+            .map(|bookmark| crate::plugins::navigation::CameraBookmark {
+                name: bookmark.name.clone(),
+                center: bookmark.center,
+                zoom: bookmark.zoom,
+                focus: bookmark
+                    .focus
+                    .as_ref()
+                    .and_then(|id| ctx.id_to_entity.get(id).copied()),
+            })
+            .collect();
+
+        let theme = themes.current();
+        for annotation in &world_model.annotations {
+            spawn_annotation(
+                &mut commands,
+                crate::plugins::annotations::Annotation {
+                    kind: annotation.kind.into(),
+                    text: annotation.text.clone(),
+                    anchor: annotation
+                        .anchor
+                        .as_ref()
+                        .and_then(|id| ctx.id_to_entity.get(id).copied()),
+                    position: annotation.position,
+                    extent: annotation.extent,
+                },
+                annotation.level,
+                false,
+                &theme,
+                &asset_server,
+            );
+        }
     }
 }
 
@@ -115,6 +254,7 @@ fn spawn_interactions(
     ctx: &mut Context,
     world_model: &WorldModel,
     zoom: f32,
+    scaling_factor: f32,
     meshes: &mut ResMut<Assets<Mesh>>,
     stroke_tess: &mut ResMut<StrokeTessellator>,
 ) {
@@ -127,6 +267,9 @@ fn spawn_interactions(
                 substance_type: interaction.substance.ty,
                 substance_sub_type: interaction.substance.sub_type.clone(),
                 amount: interaction.amount,
+                amount_origin: interaction.amount_origin,
+                formula: interaction.formula.clone(),
+                uncertainty: interaction.uncertainty,
                 unit: interaction.unit.clone(),
                 usability: interaction.usability,
                 parameters: interaction.parameters.clone(),
@@ -136,7 +279,7 @@ fn spawn_interactions(
             &interaction.info.description,
             false,
             nesting_level,
-            NestingLevel::compute_scale(nesting_level, zoom),
+            NestingLevel::compute_scale(nesting_level, zoom, scaling_factor),
             stroke_tess,
             meshes,
         );
@@ -146,6 +289,12 @@ fn spawn_interactions(
 
         let mut interaction_commands = commands.entity(interaction_entity);
 
+        interaction_commands.insert(FlowRoute {
+            routed: interaction.routed,
+            waypoints: interaction.waypoints.iter().map(|w| *w * zoom).collect(),
+            ..default()
+        });
+
         let mut system_id = Id {
             ty: IdType::System,
             indices: vec![],
@@ -209,6 +358,7 @@ fn spawn_systems_interfaces_and_external_entities(
     ctx: &mut Context,
     world_model: &WorldModel,
     zoom: f32,
+    scaling_factor: f32,
     meshes: &mut ResMut<Assets<Mesh>>,
     stroke_tess: &mut ResMut<StrokeTessellator>,
     fixed_system_element_geometries: &mut ResMut<FixedSystemElementGeometriesByNestingLevel>,
@@ -234,6 +384,7 @@ fn spawn_systems_interfaces_and_external_entities(
                     description: system.boundary.info.description.clone(),
                 },
                 zoom,
+                scaling_factor,
                 &system.info.name,
                 &system.info.description,
                 meshes,
@@ -253,9 +404,14 @@ fn spawn_systems_interfaces_and_external_entities(
                 angle,
                 meshes,
                 zoom,
+                scaling_factor,
             )
         };
 
+        commands
+            .entity(system_entity)
+            .insert(system_component_from_model(system));
+
         for interface in &system.boundary.interfaces {
             let interface_entity = spawn_loaded_interface(
                 ctx,
@@ -265,6 +421,7 @@ fn spawn_systems_interfaces_and_external_entities(
                 nesting_level,
                 system_entity,
                 zoom,
+                scaling_factor,
                 meshes,
                 stroke_tess,
                 fixed_system_element_geometries,
@@ -283,6 +440,7 @@ fn spawn_systems_interfaces_and_external_entities(
             system,
             Some(system_entity),
             zoom,
+            scaling_factor,
             meshes,
             stroke_tess,
             fixed_system_element_geometries,
@@ -290,12 +448,34 @@ fn spawn_systems_interfaces_and_external_entities(
     }
 }
 
+/// The bundle constructors only set the geometry related fields. This restores the modeling data.
+fn system_component_from_model(system: &System) -> crate::components::System {
+    crate::components::System {
+        radius: system.radius,
+        complexity: system.complexity,
+        membership: system.member_autonomy,
+        equivalence: system.equivalence.clone(),
+        transformation: system.transformation.clone(),
+        transformation_rule: system.transformation_rule.clone(),
+        history: system.history.clone(),
+        boundary: SystemBoundary {
+            porosity: system.boundary.porosity,
+            perceptive_fuzziness: system.boundary.perceptive_fuzziness,
+            name: system.boundary.info.name.clone(),
+            description: system.boundary.info.description.clone(),
+        },
+        time_unit: system.time_constant.clone(),
+        attributes: system.attributes.clone(),
+    }
+}
+
 fn spawn_external_entities<S: HasSourcesAndSinks + HasInfo>(
     commands: &mut Commands,
     ctx: &mut Context,
     sources_and_sinks: &S,
     parent_entity: Option<Entity>,
     zoom: f32,
+    scaling_factor: f32,
     meshes: &mut ResMut<Assets<Mesh>>,
     stroke_tess: &mut ResMut<StrokeTessellator>,
     fixed_system_element_geometries: &mut ResMut<FixedSystemElementGeometriesByNestingLevel>,
@@ -320,11 +500,20 @@ fn spawn_external_entities<S: HasSourcesAndSinks + HasInfo>(
             initial_position,
             (sources_and_sinks.info().level + 1) as u16,
             zoom,
+            scaling_factor,
             fixed_system_element_geometries,
             meshes,
             stroke_tess,
         );
 
+        commands
+            .entity(external_entity)
+            .insert(crate::components::ExternalEntity {
+                equivalence: ext_entity.equivalence.clone(),
+                model: ext_entity.model.clone(),
+                attributes: ext_entity.attributes.clone(),
+            });
+
         ctx.id_to_entity
             .insert(ext_entity.info.id.clone(), external_entity);
 
@@ -407,6 +596,7 @@ fn spawn_loaded_interface(
     nesting_level: u16,
     system_entity: Entity,
     zoom: f32,
+    scaling_factor: f32,
     meshes: &mut ResMut<Assets<Mesh>>,
     stroke_tess: &mut ResMut<StrokeTessellator>,
     fixed_system_element_geometries: &mut ResMut<FixedSystemElementGeometriesByNestingLevel>,
@@ -423,6 +613,7 @@ fn spawn_loaded_interface(
         nesting_level,
         system_entity,
         zoom,
+        scaling_factor,
         false,
         &interface.info.name,
         &interface.info.description,
@@ -433,6 +624,14 @@ fn spawn_loaded_interface(
         fixed_system_element_geometries,
     );
 
+    commands
+        .entity(interface_entity)
+        .insert(crate::components::Interface {
+            protocol: interface.protocol.clone(),
+            protocol_version: interface.protocol_version.clone(),
+            attributes: interface.attributes.clone(),
+        });
+
     ctx.entity_to_interface_interactions.insert(
         interface_entity,
         (
@@ -453,6 +652,7 @@ fn spawn_loaded_subsystem(
     angle: f32,
     meshes: &mut ResMut<Assets<Mesh>>,
     zoom: f32,
+    scaling_factor: f32,
 ) -> Entity {
     let interface_subsystem = if let Some(parent_interface_id) = &system.boundary.parent_interface {
         parent_interface_id.indices == system.info.id.indices
@@ -486,6 +686,7 @@ fn spawn_loaded_subsystem(
                 },
                 meshes,
                 zoom,
+                scaling_factor,
                 nesting_level,
                 &system.info.name,
                 &system.info.description,
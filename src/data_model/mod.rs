@@ -2,6 +2,7 @@ pub mod load;
 pub mod save;
 
 use crate::components::*;
+use crate::resources::{AttributeSchemas, ParameterSchema, Protocol, SubstanceDefinition};
 use bevy::prelude::*;
 use rust_decimal::Decimal;
 use serde::de::{Error, Visitor};
@@ -22,6 +23,28 @@ pub struct WorldModel {
     pub systems: Vec<System>,
     /// All interactions at all nesting levels.
     pub interactions: Vec<Interaction>,
+    /// Parameters that flows of a substance are expected to have.
+    #[serde(default)]
+    pub parameter_schemas: Vec<ParameterSchema>,
+    /// Attributes that systems, interfaces & external entities are expected to have.
+    #[serde(default)]
+    pub attribute_schemas: AttributeSchemas,
+    /// Registry of the protocols that interfaces can reference. `None` in files from before the
+    /// registry existed. Their protocols are registered from the interfaces on load.
+    #[serde(default)]
+    pub protocols: Option<Vec<Protocol>>,
+    /// Hierarchy of substances below the three base substance types.
+    #[serde(default)]
+    pub substance_catalog: Vec<SubstanceDefinition>,
+    /// Named variants of the model. Only their differences to the base model are stored.
+    #[serde(default)]
+    pub scenarios: Vec<Scenario>,
+    /// Named camera positions.
+    #[serde(default)]
+    pub camera_bookmarks: Vec<CameraBookmark>,
+    /// Notes, arrows and group boxes on the canvas.
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
 }
 
 /// Unique identifier for any kind of object.
@@ -152,8 +175,13 @@ pub struct System {
     pub equivalence: String,
     pub history: String,
     pub transformation: String,
+    #[serde(default)]
+    pub transformation_rule: Option<TransformationRule>,
     pub member_autonomy: f32,
     pub time_constant: String,
+    /// Values of the user-defined system attributes.
+    #[serde(default)]
+    pub attributes: Vec<Parameter>,
 }
 
 /// Boundary of a system.
@@ -174,6 +202,9 @@ pub struct Boundary {
 pub struct Interface {
     pub info: Info,
     pub protocol: String,
+    /// Version of the protocol. Empty if the protocol isn't versioned.
+    #[serde(default)]
+    pub protocol_version: String,
     #[serde(rename = "type")]
     pub ty: InterfaceType,
     /// Ids of targets that are connected through interactions from this interface. Can be either a
@@ -184,6 +215,9 @@ pub struct Interface {
     pub receives_from: Vec<Id>,
     /// Rotation in radians.
     pub angle: Option<f32>,
+    /// Values of the user-defined interface attributes.
+    #[serde(default)]
+    pub attributes: Vec<Parameter>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy)]
@@ -224,6 +258,9 @@ pub struct ExternalEntity {
     pub transform: Option<Transform2d>,
     pub equivalence: String,
     pub model: String,
+    /// Values of the user-defined external entity attributes.
+    #[serde(default)]
+    pub attributes: Vec<Parameter>,
 }
 
 #[derive(Serialize, Deserialize, Copy, Clone)]
@@ -252,9 +289,113 @@ pub struct Interaction {
     /// ends at.
     pub sink_interface: Option<Id>,
     pub amount: Decimal,
+    /// Whether the amount was entered by the user or computed by the amount propagation.
+    #[serde(default)]
+    pub amount_origin: AmountOrigin,
+    /// Expression that computes the amount. The amount holds the last computed value.
+    #[serde(default)]
+    pub formula: Option<String>,
+    /// Range or distribution of the amount. The amount is the nominal value.
+    #[serde(default)]
+    pub uncertainty: Option<AmountUncertainty>,
     pub unit: String,
     /// List of additional parameters
     pub parameters: Vec<Parameter>,
+    /// Whether the interaction is routed around systems and external entities.
+    #[serde(default)]
+    pub routed: bool,
+    /// Points that a routed interaction passes through. This is in pixels if zoom is at 100%.
+    #[serde(default)]
+    pub waypoints: Vec<Vec2>,
+}
+
+/// Named variant of the model that overrides values of some elements.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Scenario {
+    pub name: String,
+    pub overrides: Vec<ElementOverride>,
+}
+
+/// Values of one element that differ from the base model in a scenario.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ElementOverride {
+    pub element: Id,
+    /// Amount of an interaction.
+    #[serde(default)]
+    pub amount: Option<Decimal>,
+    /// Parameters of an interaction. Replaces the parameters with the same name.
+    #[serde(default)]
+    pub parameters: Vec<Parameter>,
+    /// Attributes of a system, interface or external entity. Replaces the attributes with the
+    /// same name.
+    #[serde(default)]
+    pub attributes: Vec<Parameter>,
+    /// Names of the parameters of an interaction that the scenario removes.
+    #[serde(default)]
+    pub removed_parameters: Vec<String>,
+    /// Names of the attributes that the scenario removes.
+    #[serde(default)]
+    pub removed_attributes: Vec<String>,
+}
+
+/// Named camera position.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CameraBookmark {
+    pub name: String,
+    /// Center of the view. This is in pixels if zoom is at 100%.
+    pub center: Vec2,
+    pub zoom: f32,
+    /// The system that is focused when the bookmark is recalled.
+    #[serde(default)]
+    pub focus: Option<Id>,
+}
+
+/// Note, arrow or group box on the canvas.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Annotation {
+    pub kind: AnnotationKind,
+    pub text: String,
+    /// Nesting level of the elements the annotation is scaled like.
+    pub level: u16,
+    /// The element the annotation moves with. Arrows point at it.
+    #[serde(default)]
+    pub anchor: Option<Id>,
+    /// Center of notes and group boxes and tail of arrows. Relative to the anchor if there is one.
+    /// This is in pixels if zoom is at 100%.
+    pub position: Vec2,
+    /// Size of notes and group boxes. For arrows without an anchor this is the offset from the
+    /// tail to the head. This is in pixels if zoom is at 100%.
+    pub extent: Vec2,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub enum AnnotationKind {
+    /// Sticky note with free text.
+    Note,
+    /// Arrow that points at its anchor with an optional caption at its tail.
+    Arrow,
+    /// Translucent rectangle with a title that groups the elements below it.
+    GroupBox,
+}
+
+impl From<crate::plugins::annotations::AnnotationKind> for AnnotationKind {
+    fn from(kind: crate::plugins::annotations::AnnotationKind) -> Self {
+        match kind {
+            crate::plugins::annotations::AnnotationKind::Note => Self::Note,
+            crate::plugins::annotations::AnnotationKind::Arrow => Self::Arrow,
+            crate::plugins::annotations::AnnotationKind::GroupBox => Self::GroupBox,
+        }
+    }
+}
+
+impl From<AnnotationKind> for crate::plugins::annotations::AnnotationKind {
+    fn from(kind: AnnotationKind) -> Self {
+        match kind {
+            AnnotationKind::Note => Self::Note,
+            AnnotationKind::Arrow => Self::Arrow,
+            AnnotationKind::GroupBox => Self::GroupBox,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
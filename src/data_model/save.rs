@@ -1,8 +1,14 @@
 use crate::components::*;
 use crate::data_model::Interaction;
 use crate::data_model::*;
+use crate::plugins::annotations::Annotation as AnnotationComponent;
 use crate::plugins::file_dialog::ExportFileEvent;
+use crate::plugins::layout::FlowRoute;
+use crate::plugins::navigation::CameraBookmarks;
+use crate::plugins::scenarios::Scenarios;
+use crate::resources::{AttributeSchemas, ParameterSchemas, Protocols, SubstanceCatalog, Zoom};
 use bevy::core::Name;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 
@@ -56,6 +62,17 @@ impl Context {
     }
 }
 
+/// Model-wide resources that are saved along with the elements.
+#[derive(SystemParam)]
+pub struct ModelResources<'w> {
+    parameter_schemas: Res<'w, ParameterSchemas>,
+    attribute_schemas: Res<'w, AttributeSchemas>,
+    protocols: Res<'w, Protocols>,
+    substance_catalog: Res<'w, SubstanceCatalog>,
+    scenarios: Res<'w, Scenarios>,
+    camera_bookmarks: Res<'w, CameraBookmarks>,
+}
+
 pub fn save_world(
     mut save_file_event_reader: EventReader<ExportFileEvent>,
     name_and_description_query: Query<(&Name, &ElementDescription)>,
@@ -76,6 +93,10 @@ pub fn save_world(
     )>,
     interface_query: Query<(&crate::components::Interface, &Transform)>,
     external_entity_query: Query<&crate::components::ExternalEntity>,
+    flow_route_query: Query<&FlowRoute>,
+    annotation_query: Query<(&AnnotationComponent, &NestingLevel)>,
+    zoom: Res<Zoom>,
+    model_resources: ModelResources,
 ) {
     for event in save_file_event_reader.read() {
         let (system_entity, system_component, environment) = main_system_info_query
@@ -171,6 +192,11 @@ pub fn save_world(
             let interaction = ctx.interaction_mut_by_entity(flow_entity);
             interaction.source_interface = source_interface;
             interaction.sink_interface = sink_interface;
+
+            if let Ok(route) = flow_route_query.get(flow_entity) {
+                interaction.routed = route.routed;
+                interaction.waypoints = route.waypoints.iter().map(|w| *w / **zoom).collect();
+            }
         }
 
         let model = WorldModel {
@@ -178,6 +204,16 @@ pub fn save_world(
             systems: entity_to_system.into_values().collect(),
             interactions: ctx.interactions,
             environment,
+            parameter_schemas: model_resources.parameter_schemas.0.clone(),
+            attribute_schemas: model_resources.attribute_schemas.clone(),
+            protocols: Some(model_resources.protocols.0.clone()),
+            substance_catalog: model_resources.substance_catalog.0.clone(),
+            scenarios: build_scenarios(&model_resources.scenarios, &ctx.entity_to_id),
+            camera_bookmarks: build_camera_bookmarks(
+                &model_resources.camera_bookmarks,
+                &ctx.entity_to_id,
+            ),
+            annotations: build_annotations(&annotation_query, &ctx.entity_to_id),
         };
 
         let save_file = &**event;
@@ -186,6 +222,77 @@ pub fn save_world(
     }
 }
 
+/// Map the anchors of the annotations from bevy entities to data model ids.
+fn build_annotations(
+    annotation_query: &Query<(&AnnotationComponent, &NestingLevel)>,
+    entity_to_id: &HashMap<Entity, Id>,
+) -> Vec<Annotation> {
+    annotation_query
+        .iter()
+        .map(|(annotation, nesting_level)| Annotation {
+            kind: annotation.kind.into(),
+            text: annotation.text.clone(),
+            level: **nesting_level,
+            anchor: annotation
+                .anchor
+                .and_then(|entity| entity_to_id.get(&entity).cloned()),
+            position: annotation.position,
+            extent: annotation.extent,
+        })
+        .collect()
+}
+
+/// Map the focused systems of the camera bookmarks from bevy entities to data model ids.
+fn build_camera_bookmarks(
+    camera_bookmarks: &CameraBookmarks,
+    entity_to_id: &HashMap<Entity, Id>,
+) -> Vec<CameraBookmark> {
+    camera_bookmarks
+        .0
+        .iter()
+        .map(|bookmark| CameraBookmark {
+            name: bookmark.name.clone(),
+            center: bookmark.center,
+            zoom: bookmark.zoom,
+            focus: bookmark
+                .focus
+                .and_then(|entity| entity_to_id.get(&entity).cloned()),
+        })
+        .collect()
+}
+
+/// Map the overrides of all scenarios from bevy entities to data model ids.
+fn build_scenarios(scenarios: &Scenarios, entity_to_id: &HashMap<Entity, Id>) -> Vec<Scenario> {
+    scenarios
+        .scenarios
+        .iter()
+        .map(|scenario| {
+            let mut overrides = scenario
+                .overrides
+                .iter()
+                .filter_map(|(entity, values)| {
+                    Some(ElementOverride {
+                        element: entity_to_id.get(entity)?.clone(),
+                        amount: values.amount,
+                        parameters: values.parameters.clone(),
+                        attributes: values.attributes.clone(),
+                        removed_parameters: values.removed_parameters.clone(),
+                        removed_attributes: values.removed_attributes.clone(),
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            // Keep the file stable between saves.
+            overrides.sort_by_key(|o| format!("{:?}", o.element));
+
+            Scenario {
+                name: scenario.name.clone(),
+                overrides,
+            }
+        })
+        .collect()
+}
+
 /// Iterate through all subsystems of the given system entity and build them. Then build all
 /// the interactions between them and sources/sinks contained in the parent system.
 /// Then do it recursively for each subsystem again.
@@ -534,10 +641,12 @@ fn build_interface<C: Connection>(
                 &name_and_description_query,
             ),
             protocol: interface.protocol.clone(),
+            protocol_version: interface.protocol_version.clone(),
             ty, // TODO : hybrid
             exports_to: vec![],
             receives_from: vec![],
             angle: Some(interface_transform.right().truncate().to_angle()),
+            attributes: interface.attributes.clone(),
         });
 
     system.boundary.interfaces.len() - 1
@@ -576,8 +685,13 @@ fn build_interaction<P: HasInfo>(
         source_interface: None,
         sink_interface: None,
         amount: flow.amount,
+        amount_origin: flow.amount_origin,
+        formula: flow.formula.clone(),
+        uncertainty: flow.uncertainty,
         unit: flow.unit.clone(),
         parameters: flow.parameters.clone(),
+        routed: false,
+        waypoints: vec![],
     };
 
     ctx.interactions.push(interaction);
@@ -616,6 +730,7 @@ fn build_external_entity<P: HasInfo>(
         transform: transform2d_from_entity(entity, &transform_query),
         equivalence: external_entity_component.equivalence.clone(),
         model: external_entity_component.model.clone(),
+        attributes: external_entity_component.attributes.clone(),
     }
 }
 
@@ -659,8 +774,10 @@ fn build_system(
         equivalence: system.equivalence.clone(),
         history: system.history.clone(),
         transformation: system.transformation.clone(),
+        transformation_rule: system.transformation_rule.clone(),
         member_autonomy: 1.0,
         time_constant: system.time_unit.clone(),
+        attributes: system.attributes.clone(),
     };
 
     ctx.entity_to_id
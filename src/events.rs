@@ -34,6 +34,12 @@ macro_rules! impl_drag_event {
 impl_drag_event!(ExternalEntityDrag);
 impl_drag_event!(InterfaceDrag);
 impl_drag_event!(SubsystemDrag);
+impl_drag_event!(AnnotationDrag);
+
+/// Fires when a create button is clicked on the canvas or run from the command palette. The
+/// handlers of the button's type spawn the element.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CreateButtonClick(pub Entity);
 
 /// Fires when an entity is removed from the world. Used in system control flow.
 #[derive(Event, Debug, Clone, Copy)]
@@ -15,12 +15,28 @@ use crate::constants::WHITE_COLOR_MATERIAL_HANDLE;
 use crate::data_model::load::load_world;
 use crate::data_model::save::save_world;
 use crate::events::*;
+use crate::plugins::analysis::AnalysisPlugin;
+use crate::plugins::annotations::AnnotationsPlugin;
+use crate::plugins::attributes::AttributesPlugin;
+use crate::plugins::command_palette::CommandPalettePlugin;
+use crate::plugins::descriptions::DescriptionsPlugin;
 use crate::plugins::file_dialog::{FileDialogPlugin, FileState};
+use crate::plugins::formula::FormulaPlugin;
+use crate::plugins::keybindings::{
+    key_action_just_pressed, KeyAction, KeybindingsPlugin, COMMAND_KEYS,
+};
 use crate::plugins::label::{copy_position, LabelPlugin};
+use crate::plugins::layout::LayoutPlugin;
 use crate::plugins::lyon_selection::LyonSelectionPlugin;
 use crate::plugins::mouse_interaction::{
     disable_selection, enable_selection, MouseInteractionPlugin,
 };
+use crate::plugins::navigation::{breadcrumbs_egui, NavigationPlugin};
+use crate::plugins::outline::OutlinePlugin;
+use crate::plugins::preferences::{Preferences, PreferencesPlugin};
+use crate::plugins::protocols::ProtocolsPlugin;
+use crate::plugins::scenarios::ScenariosPlugin;
+use crate::plugins::theme::ThemePlugin;
 use crate::resources::*;
 use crate::states::*;
 use crate::systems::*;
@@ -81,24 +97,47 @@ fn main() {
         MouseInteractionPlugin,
         LabelPlugin,
         FileDialogPlugin,
+        KeybindingsPlugin,
+    ))
+    .add_plugins((
+        AnalysisPlugin,
+        AttributesPlugin,
+        FormulaPlugin,
+        ScenariosPlugin,
+        ProtocolsPlugin,
+        ThemePlugin,
+        NavigationPlugin,
+        OutlinePlugin,
+        LayoutPlugin,
+        CommandPalettePlugin,
+        PreferencesPlugin,
+        AnnotationsPlugin,
+        DescriptionsPlugin,
     ))
     .insert_resource(DebugPickingMode::Disabled)
     .insert_resource(StrokeTessellator::new())
     .init_resource::<Zoom>()
     .init_resource::<FixedSystemElementGeometriesByNestingLevel>()
+    .init_resource::<ParameterSchemas>()
+    .init_resource::<ParameterSchemaEditor>()
+    .init_resource::<AttributeSchemas>()
+    .init_resource::<AttributeSchemaEditor>()
+    .init_resource::<Protocols>()
+    .init_resource::<ProtocolEditor>()
+    .init_resource::<SubstanceCatalog>()
+    .init_resource::<SubstanceCatalogEditor>()
     .add_event::<ExternalEntityDrag>()
     .add_event::<InterfaceDrag>()
     .add_event::<SubsystemDrag>()
+    .add_event::<CreateButtonClick>()
     .add_event::<RemoveEvent>()
     .init_state::<AppState>()
     .add_systems(Startup, (window_setup, setup));
     #[cfg(feature = "init_complete_system")]
     app.add_systems(Startup, init_complete_system.after(setup));
 
-    let wheel_zoom_condition = input_pressed(KeyCode::ControlLeft)
-        .or_else(input_pressed(KeyCode::ControlRight).or_else(
-            input_pressed(KeyCode::SuperLeft).or_else(input_pressed(KeyCode::SuperRight)),
-        ));
+    let wheel_zoom_condition =
+        input_pressed(COMMAND_KEYS[0]).or_else(input_pressed(COMMAND_KEYS[1]));
 
     app.add_systems(
         PreUpdate,
@@ -107,7 +146,6 @@ fn main() {
                 .after(bevy_egui::systems::process_input_system)
                 .before(bevy_egui::EguiSet::BeginFrame),
             control_zoom_from_keyboard,
-            // TODO : for some reason this doesn't always work: control_zoom_from_mouse_wheel.run_if(wheel_zoom_condition.clone()),
         )
             .in_set(AllSet),
     );
@@ -116,7 +154,12 @@ fn main() {
         Update,
         (
             (
-                egui_selected_context.after(bevy_egui::EguiSet::InitContexts),
+                egui_menu_bar.after(bevy_egui::EguiSet::InitContexts),
+                egui_selected_context.after(breadcrumbs_egui),
+                parameter_schemas_egui.after(egui_menu_bar),
+                attribute_schemas_egui.after(egui_menu_bar),
+                protocols_egui.after(egui_menu_bar),
+                substance_catalog_egui.after(egui_menu_bar),
                 change_focused_system,
                 draw_flow_curve,
                 update_initial_position_from_transform,
@@ -127,12 +170,18 @@ fn main() {
             )
                 .in_set(FlowTerminalSelectingSet),
             (drag_external_entity, drag_interface, drag_subsystem),
+            (
+                on_create_button_click,
+                on_external_entity_create_button_click,
+                on_flow_terminal_button_click,
+                on_subsystem_button_click,
+            ),
             (
                 pan_camera_with_mouse.run_if(input_pressed(MouseButton::Right)),
-                pan_camera_with_mouse_wheel.run_if(not(wheel_zoom_condition)),
-                reset_camera_position.run_if(
-                    input_pressed(KeyCode::SuperLeft).and_then(input_just_pressed(KeyCode::KeyR)),
-                ),
+                pan_camera_with_mouse_wheel.run_if(not(wheel_zoom_condition.clone())),
+                control_zoom_from_mouse_wheel.run_if(wheel_zoom_condition),
+                control_zoom_from_pinch,
+                reset_camera_position.run_if(key_action_just_pressed(KeyAction::ResetCamera)),
             )
                 .in_set(CameraControlSet),
             (load_world, save_world),
@@ -161,6 +210,7 @@ fn main() {
                 spawn_selected_interface,
                 spawn_selected_external_entity,
                 update_selected_flow_curve,
+                apply_parameter_schemas_to_new_flows,
                 despawn_selected_helper,
                 remove_selected_elements.run_if(
                     in_state(AppState::Normal).and_then(
@@ -177,7 +227,7 @@ fn main() {
                 update_interface_color_from_flow::<FlowEndInterfaceConnection>,
                 update_interface_subsystem_color,
                 update_system_color_from_subsystem,
-                apply_zoom_to_system_radii, // this is not in ZoomSet on purpose
+                apply_zoom_to_system_radii.after(CameraControlSet), // this is not in ZoomSet on purpose
             ),
         )
             .in_set(AllSet),
@@ -220,6 +270,7 @@ fn main() {
             )
                 .in_set(GeometryUpdateSet),
             apply_zoom_to_added_label.after(AutoSpawnLabelSet),
+            apply_zoom_to_added_strokes,
         )
             .in_set(AllSet),
     )
@@ -237,7 +288,8 @@ fn main() {
         Update,
         (
             RemovalCleanupSet.after(remove_selected_elements),
-            ZoomSet.run_if(resource_changed::<Zoom>),
+            ZoomSet.run_if(resource_changed::<Zoom>.or_else(resource_changed::<Preferences>)),
+            CameraControlSet.before(ZoomSet),
             FlowTerminalSelectingSet.run_if(in_state(AppState::FlowTerminalSelection)),
             AllSet.run_if(in_state(FileState::Inactive)),
         ),
@@ -0,0 +1,409 @@
+//! Resilience analysis that hypothetically removes systems or interactions from the model
+//! and reports which sinks and products are affected.
+use super::{InteractionGraph, SystemHeatmap};
+use crate::components::*;
+use crate::plugins::mouse_interaction::PickSelection;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_egui::{egui, EguiContexts};
+
+/// Outcome of hypothetically removing a system (including all of its subsystems) or an interaction.
+#[derive(Clone, Debug)]
+pub struct DisruptionReport {
+    pub removed: Entity,
+    /// Sinks that don't receive anything from any source anymore.
+    pub lost_sinks: Vec<Entity>,
+    /// Product interactions that are not produced anymore.
+    pub lost_products: Vec<Entity>,
+}
+
+/// A subsystem together with the number of product interactions that depend on it.
+#[derive(Copy, Clone, Debug)]
+pub struct FailureRank {
+    pub system: Entity,
+    pub dependent_products: usize,
+}
+
+/// A disruption interaction together with all the nodes that are downstream of it.
+#[derive(Clone, Debug)]
+pub struct DisruptionExposure {
+    pub flow: Entity,
+    pub exposed: Vec<Entity>,
+}
+
+/// State of the resilience analysis window.
+#[derive(Resource, Default)]
+pub struct DisruptionAnalysis {
+    pub open: bool,
+    pub report: Option<DisruptionReport>,
+    pub ranking: Vec<FailureRank>,
+    pub exposures: Vec<DisruptionExposure>,
+    pub show_heatmap: bool,
+}
+
+/// Removes the given nodes and flows from the graph and compares the supplied sinks and produced
+/// products to the intact graph.
+pub fn analyse_removal(
+    graph: &InteractionGraph,
+    removed: Entity,
+    removed_nodes: &HashSet<Entity>,
+    removed_flows: &HashSet<Entity>,
+) -> DisruptionReport {
+    let no_entities = HashSet::new();
+    let intact_supplied = graph.supplied_nodes(&no_entities, &no_entities);
+    let intact_products = graph.produced_products(&intact_supplied, &no_entities);
+
+    let supplied = graph.supplied_nodes(removed_nodes, removed_flows);
+    let products = graph.produced_products(&supplied, removed_flows);
+
+    DisruptionReport {
+        removed,
+        lost_sinks: graph
+            .sinks
+            .iter()
+            .filter(|sink| intact_supplied.contains(*sink) && !supplied.contains(*sink))
+            .copied()
+            .collect(),
+        lost_products: intact_products.difference(&products).copied().collect(),
+    }
+}
+
+/// Ranks all the given systems by the number of products that stop being produced when the system
+/// fails. Systems at the top of the list are single points of failure.
+pub fn rank_single_points_of_failure(
+    graph: &InteractionGraph,
+    systems: &[Entity],
+    children_by_system: &HashMap<Entity, Vec<Entity>>,
+) -> Vec<FailureRank> {
+    let no_flows = HashSet::new();
+
+    let mut ranking = systems
+        .iter()
+        .map(|system| FailureRank {
+            system: *system,
+            dependent_products: analyse_removal(
+                graph,
+                *system,
+                &system_with_descendants(*system, children_by_system),
+                &no_flows,
+            )
+            .lost_products
+            .len(),
+        })
+        .collect::<Vec<_>>();
+
+    ranking.sort_by_key(|rank| std::cmp::Reverse(rank.dependent_products));
+    ranking
+}
+
+/// Lists every disruption interaction with all the systems and sinks it can affect.
+pub fn disruption_exposures(graph: &InteractionGraph) -> Vec<DisruptionExposure> {
+    graph
+        .edges
+        .iter()
+        .filter(|edge| matches!(edge.usability, InteractionUsability::Disruption))
+        .map(|edge| {
+            let mut exposed = graph.downstream_nodes(edge.to);
+            exposed.insert(edge.to);
+
+            DisruptionExposure {
+                flow: edge.flow,
+                exposed: exposed.into_iter().collect(),
+            }
+        })
+        .collect()
+}
+
+/// Returns the system itself and all of it's subsystems at all nesting levels.
+pub fn system_with_descendants(
+    system: Entity,
+    children_by_system: &HashMap<Entity, Vec<Entity>>,
+) -> HashSet<Entity> {
+    let mut systems = HashSet::new();
+    let mut stack = vec![system];
+
+    while let Some(system) = stack.pop() {
+        if systems.insert(system) {
+            stack.extend(children_by_system.get(&system).into_iter().flatten());
+        }
+    }
+
+    systems
+}
+
+/// Shows the resilience analysis window and runs the analyses on user request.
+pub fn disruption_analysis_egui(
+    mut egui_contexts: EguiContexts,
+    mut analysis: ResMut<DisruptionAnalysis>,
+    mut heatmap: ResMut<SystemHeatmap>,
+    flow_query: Query<(
+        Entity,
+        &Flow,
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>,
+    interface_subsystem_query: Query<&InterfaceSubsystemConnection>,
+    subsystem_query: Query<(Entity, &Subsystem)>,
+    selected_query: Query<(Entity, &PickSelection, &SystemElement)>,
+    name_query: Query<&Name>,
+) {
+    if !analysis.open {
+        return;
+    }
+
+    let name_of = |entity: Entity| {
+        name_query
+            .get(entity)
+            .map(|name| name.to_string())
+            .unwrap_or_else(|_| "<removed>".to_string())
+    };
+
+    let selected = selected_query
+        .iter()
+        .filter(|(_, selection, _)| selection.is_selected)
+        .map(|(entity, _, element)| (entity, *element))
+        .collect::<Vec<_>>();
+
+    let mut open = analysis.open;
+    let mut run_removal = false;
+    let mut run_ranking = false;
+    let mut show_heatmap = analysis.show_heatmap;
+
+    egui::Window::new("Resilience Analysis")
+        .open(&mut open)
+        .default_width(320.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            let can_remove = matches!(
+                selected.as_slice(),
+                [(_, SystemElement::System | SystemElement::Interaction)]
+            );
+            ui.add_enabled_ui(can_remove, |ui| {
+                run_removal = ui
+                    .button("Disable selected system or interaction")
+                    .on_disabled_hover_text("Select exactly one system or interaction")
+                    .clicked();
+            });
+
+            if let Some(report) = &analysis.report {
+                ui.label(format!("Without \"{}\":", name_of(report.removed)));
+                names_list(ui, "Sinks losing supply", &report.lost_sinks, &name_of);
+                names_list(
+                    ui,
+                    "Products no longer produced",
+                    &report.lost_products,
+                    &name_of,
+                );
+            }
+
+            ui.separator();
+            run_ranking = ui.button("Rank single points of failure").clicked();
+            ui.checkbox(&mut show_heatmap, "Show heatmap on system circles");
+
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .id_source("Failure Ranking")
+                .show(ui, |ui| {
+                    egui::Grid::new("Failure Ranking Grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for rank in &analysis.ranking {
+                                ui.label(name_of(rank.system));
+                                ui.label(format!("{} products", rank.dependent_products));
+                                ui.end_row();
+                            }
+                        });
+                });
+
+            if !analysis.exposures.is_empty() {
+                ui.separator();
+                for exposure in &analysis.exposures {
+                    names_list(
+                        ui,
+                        &format!("Disruption \"{}\" affects", name_of(exposure.flow)),
+                        &exposure.exposed,
+                        &name_of,
+                    );
+                }
+            }
+        });
+
+    analysis.open = open;
+
+    if run_removal || run_ranking {
+        let graph = InteractionGraph::from_query(&flow_query, &interface_subsystem_query);
+
+        let mut children_by_system = HashMap::<Entity, Vec<Entity>>::new();
+        for (subsystem_entity, subsystem) in &subsystem_query {
+            children_by_system
+                .entry(subsystem.parent_system)
+                .or_default()
+                .push(subsystem_entity);
+        }
+
+        if run_removal {
+            let (removed, element) = selected[0];
+
+            let (removed_nodes, removed_flows) = if matches!(element, SystemElement::System) {
+                (
+                    system_with_descendants(removed, &children_by_system),
+                    HashSet::new(),
+                )
+            } else {
+                (HashSet::new(), HashSet::from([removed]))
+            };
+
+            analysis.report = Some(analyse_removal(
+                &graph,
+                removed,
+                &removed_nodes,
+                &removed_flows,
+            ));
+        }
+
+        if run_ranking {
+            let systems = subsystem_query.iter().map(|(e, _)| e).collect::<Vec<_>>();
+            analysis.ranking = rank_single_points_of_failure(&graph, &systems, &children_by_system);
+            analysis.exposures = disruption_exposures(&graph);
+        }
+    }
+
+    if show_heatmap != analysis.show_heatmap || (run_ranking && show_heatmap) {
+        analysis.show_heatmap = show_heatmap;
+
+        heatmap.clear();
+        if show_heatmap {
+            let max = analysis
+                .ranking
+                .iter()
+                .map(|rank| rank.dependent_products)
+                .max()
+                .unwrap_or(0)
+                .max(1);
+
+            for rank in &analysis.ranking {
+                heatmap.insert(rank.system, rank.dependent_products as f32 / max as f32);
+            }
+        }
+    }
+
+    if !analysis.open && analysis.show_heatmap {
+        analysis.show_heatmap = false;
+        heatmap.clear();
+    }
+}
+
+fn names_list(
+    ui: &mut egui::Ui,
+    heading: &str,
+    entities: &[Entity],
+    name_of: &impl Fn(Entity) -> String,
+) {
+    ui.collapsing(format!("{} ({})", heading, entities.len()), |ui| {
+        for entity in entities {
+            ui.label(name_of(*entity));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::analysis::InteractionEdge;
+    use rust_decimal::Decimal;
+
+    fn entity(idx: u32) -> Entity {
+        Entity::from_raw(idx)
+    }
+
+    fn edge(flow: u32, from: u32, to: u32, usability: InteractionUsability) -> InteractionEdge {
+        InteractionEdge {
+            flow: entity(flow),
+            from: entity(from),
+            to: entity(to),
+            usability,
+            substance_type: SubstanceType::Material,
+            amount: Decimal::ONE,
+        }
+    }
+
+    /// Source 1 supplies system 2 which supplies system 3 which delivers a product to sink 4.
+    /// Source 5 disrupts system 3.
+    fn chain() -> InteractionGraph {
+        InteractionGraph {
+            edges: vec![
+                edge(10, 1, 2, InteractionUsability::Resource),
+                edge(11, 2, 3, InteractionUsability::Resource),
+                edge(12, 3, 4, InteractionUsability::Product),
+                edge(13, 5, 3, InteractionUsability::Disruption),
+            ],
+            sources: vec![entity(1), entity(5)],
+            sinks: vec![entity(4)],
+        }
+    }
+
+    #[test]
+    fn removing_a_system_loses_everything_downstream() {
+        let report = analyse_removal(
+            &chain(),
+            entity(2),
+            &HashSet::from([entity(2)]),
+            &HashSet::new(),
+        );
+
+        assert_eq!(report.lost_sinks, vec![entity(4)]);
+        assert_eq!(report.lost_products, vec![entity(12)]);
+    }
+
+    #[test]
+    fn removing_a_disruption_loses_nothing() {
+        let report = analyse_removal(
+            &chain(),
+            entity(13),
+            &HashSet::new(),
+            &HashSet::from([entity(13)]),
+        );
+
+        assert!(report.lost_sinks.is_empty());
+        assert!(report.lost_products.is_empty());
+    }
+
+    #[test]
+    fn single_points_of_failure_are_ranked_first() {
+        let mut graph = chain();
+        // System 6 is a dead end that no product depends on.
+        graph
+            .edges
+            .push(edge(14, 2, 6, InteractionUsability::Waste));
+
+        let ranking =
+            rank_single_points_of_failure(&graph, &[entity(6), entity(3)], &HashMap::new());
+
+        assert_eq!(ranking[0].system, entity(3));
+        assert_eq!(ranking[0].dependent_products, 1);
+        assert_eq!(ranking[1].dependent_products, 0);
+    }
+
+    #[test]
+    fn disruptions_expose_everything_downstream() {
+        let exposures = disruption_exposures(&chain());
+
+        assert_eq!(exposures.len(), 1);
+        let exposed = exposures[0].exposed.iter().copied().collect::<HashSet<_>>();
+        assert_eq!(exposed, HashSet::from([entity(3), entity(4)]));
+    }
+
+    #[test]
+    fn descendants_include_all_nesting_levels() {
+        let children_by_system = HashMap::from([
+            (entity(1), vec![entity(2), entity(3)]),
+            (entity(2), vec![entity(4)]),
+        ]);
+
+        assert_eq!(
+            system_with_descendants(entity(1), &children_by_system),
+            HashSet::from([entity(1), entity(2), entity(3), entity(4)])
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! A directed graph view of the model that is used by the analyses.
+//! Nodes are systems & external entities and edges are the interactions between them.
+use crate::components::*;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use rust_decimal::Decimal;
+
+/// An interaction between two nodes of the [`InteractionGraph`].
+#[derive(Clone, Debug)]
+pub struct InteractionEdge {
+    pub flow: Entity,
+    pub from: Entity,
+    pub to: Entity,
+    pub usability: InteractionUsability,
+    pub substance_type: SubstanceType,
+    pub amount: Decimal,
+}
+
+/// All interactions of the model at all nesting levels.
+///
+/// If an interaction ends at an interface that has an interface subsystem, the interaction is
+/// connected to that interface subsystem instead of the parent system. This way decomposed systems
+/// are analysed through their subsystems.
+#[derive(Clone, Debug, Default)]
+pub struct InteractionGraph {
+    pub edges: Vec<InteractionEdge>,
+    pub sources: Vec<Entity>,
+    pub sinks: Vec<Entity>,
+}
+
+impl InteractionGraph {
+    pub fn from_query(
+        flow_query: &Query<(
+            Entity,
+            &Flow,
+            &FlowStartConnection,
+            &FlowEndConnection,
+            Option<&FlowStartInterfaceConnection>,
+            Option<&FlowEndInterfaceConnection>,
+        )>,
+        interface_subsystem_query: &Query<&InterfaceSubsystemConnection>,
+    ) -> Self {
+        let mut graph = Self::default();
+
+        for (
+            flow_entity,
+            flow,
+            flow_start_connection,
+            flow_end_connection,
+            flow_start_interface_connection,
+            flow_end_interface_connection,
+        ) in flow_query
+        {
+            if flow_start_connection.target_is_external_entity()
+                && !graph.sources.contains(&flow_start_connection.target)
+            {
+                graph.sources.push(flow_start_connection.target);
+            }
+
+            if flow_end_connection.target_is_external_entity()
+                && !graph.sinks.contains(&flow_end_connection.target)
+            {
+                graph.sinks.push(flow_end_connection.target);
+            }
+
+            graph.edges.push(InteractionEdge {
+                flow: flow_entity,
+                from: resolve_node(
+                    flow_start_connection,
+                    flow_start_interface_connection,
+                    interface_subsystem_query,
+                ),
+                to: resolve_node(
+                    flow_end_connection,
+                    flow_end_interface_connection,
+                    interface_subsystem_query,
+                ),
+                usability: flow.usability,
+                substance_type: flow.substance_type,
+                amount: flow.amount,
+            });
+        }
+
+        graph
+    }
+
+    /// Maps every node to the indices of the edges that start at it.
+    pub fn outgoing_edges(&self) -> HashMap<Entity, Vec<usize>> {
+        let mut outgoing = HashMap::<Entity, Vec<usize>>::new();
+
+        for (idx, edge) in self.edges.iter().enumerate() {
+            outgoing.entry(edge.from).or_default().push(idx);
+        }
+
+        outgoing
+    }
+
+    /// Returns all nodes that are supplied by at least one source while ignoring the removed nodes
+    /// and flows. Disruptions don't supply anything so they are never followed.
+    pub fn supplied_nodes(
+        &self,
+        removed_nodes: &HashSet<Entity>,
+        removed_flows: &HashSet<Entity>,
+    ) -> HashSet<Entity> {
+        let outgoing = self.outgoing_edges();
+
+        let mut supplied = HashSet::new();
+        let mut stack = vec![];
+
+        for source in &self.sources {
+            if !removed_nodes.contains(source) && supplied.insert(*source) {
+                stack.push(*source);
+            }
+        }
+
+        while let Some(node) = stack.pop() {
+            for idx in outgoing
+                .get(&node)
+                .map(|e| e.as_slice())
+                .unwrap_or_default()
+            {
+                let edge = &self.edges[*idx];
+
+                if matches!(edge.usability, InteractionUsability::Disruption)
+                    || removed_flows.contains(&edge.flow)
+                    || removed_nodes.contains(&edge.to)
+                {
+                    continue;
+                }
+
+                if supplied.insert(edge.to) {
+                    stack.push(edge.to);
+                }
+            }
+        }
+
+        supplied
+    }
+
+    /// Returns all product flows that are still produced, i.e. that start at a supplied node.
+    pub fn produced_products(
+        &self,
+        supplied_nodes: &HashSet<Entity>,
+        removed_flows: &HashSet<Entity>,
+    ) -> HashSet<Entity> {
+        self.edges
+            .iter()
+            .filter(|edge| {
+                matches!(edge.usability, InteractionUsability::Product)
+                    && supplied_nodes.contains(&edge.from)
+                    && !removed_flows.contains(&edge.flow)
+            })
+            .map(|edge| edge.flow)
+            .collect()
+    }
+
+    /// Returns all nodes that can be reached by following the edges from the given node.
+    pub fn downstream_nodes(&self, node: Entity) -> HashSet<Entity> {
+        let outgoing = self.outgoing_edges();
+
+        let mut downstream = HashSet::new();
+        let mut stack = vec![node];
+
+        while let Some(node) = stack.pop() {
+            for idx in outgoing
+                .get(&node)
+                .map(|e| e.as_slice())
+                .unwrap_or_default()
+            {
+                let to = self.edges[*idx].to;
+                if downstream.insert(to) {
+                    stack.push(to);
+                }
+            }
+        }
+
+        downstream
+    }
+}
+
+fn resolve_node<C: Connection, I: Connection>(
+    connection: &C,
+    interface_connection: Option<&I>,
+    interface_subsystem_query: &Query<&InterfaceSubsystemConnection>,
+) -> Entity {
+    interface_connection
+        .and_then(|c| interface_subsystem_query.get(c.target()).ok())
+        .map(|c| c.target)
+        .unwrap_or(connection.target())
+}
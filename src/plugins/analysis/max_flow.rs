@@ -0,0 +1,483 @@
+//! Bottleneck analysis that interprets interaction amounts as capacities and computes the maximum
+//! flow & minimum cut between a set of sources and a set of sinks for one substance type.
+use super::{FlowHighlight, InteractionGraph};
+use crate::components::*;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_egui::egui::ComboBox;
+use bevy_egui::{egui, EguiContexts};
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+
+/// Result of a max-flow computation.
+#[derive(Clone, Debug, Default)]
+pub struct MaxFlowResult {
+    /// Total amount that can be transported from the sources to the sinks.
+    pub max_flow: Decimal,
+    /// Interactions of the minimum cut. These are saturated and limit the total amount.
+    pub cut_flows: Vec<Entity>,
+    /// Systems whose outgoing interactions are part of the minimum cut.
+    pub limiting_systems: Vec<Entity>,
+}
+
+/// State of the bottleneck analysis window.
+#[derive(Resource, Default)]
+pub struct BottleneckAnalysis {
+    pub open: bool,
+    pub substance_type: SubstanceType,
+    pub sources: HashSet<Entity>,
+    pub sinks: HashSet<Entity>,
+    /// The last computed result or why it couldn't be computed.
+    pub result: Option<Result<MaxFlowResult, String>>,
+    pub highlight_cut: bool,
+}
+
+/// Residual network used by the Edmonds-Karp algorithm.
+struct Network {
+    /// (to, residual capacity, index of reverse arc, interaction entity if it is a forward arc)
+    arcs: Vec<(usize, Decimal, usize, Option<Entity>)>,
+    outgoing: Vec<Vec<usize>>,
+}
+
+impl Network {
+    fn new(node_count: usize) -> Self {
+        Self {
+            arcs: vec![],
+            outgoing: vec![vec![]; node_count],
+        }
+    }
+
+    fn add_arc(&mut self, from: usize, to: usize, capacity: Decimal, flow: Option<Entity>) {
+        let idx = self.arcs.len();
+        self.arcs.push((to, capacity, idx + 1, flow));
+        self.arcs.push((from, Decimal::ZERO, idx, None));
+        self.outgoing[from].push(idx);
+        self.outgoing[to].push(idx + 1);
+    }
+
+    /// Returns for every node the arc it was reached through, or `None` if it isn't reachable.
+    fn bfs(&self, start: usize) -> Vec<Option<usize>> {
+        let mut reached_by = vec![None; self.outgoing.len()];
+        let mut visited = vec![false; self.outgoing.len()];
+        visited[start] = true;
+
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(node) = queue.pop_front() {
+            for arc_idx in &self.outgoing[node] {
+                let (to, capacity, _, _) = self.arcs[*arc_idx];
+                if !visited[to] && capacity > Decimal::ZERO {
+                    visited[to] = true;
+                    reached_by[to] = Some(*arc_idx);
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        reached_by
+    }
+}
+
+/// Computes the maximum flow of the given substance type from the sources to the sinks.
+/// Disruptions are ignored. Interaction amounts are used as capacities.
+/// `is_system` tells systems apart from external entities for the limiting systems.
+pub fn compute_max_flow(
+    graph: &InteractionGraph,
+    substance_type: SubstanceType,
+    sources: &HashSet<Entity>,
+    sinks: &HashSet<Entity>,
+    is_system: impl Fn(Entity) -> bool,
+) -> Result<MaxFlowResult, String> {
+    if !sources.is_disjoint(sinks) {
+        return Err("An element can't be both a source and a sink".to_string());
+    }
+
+    let mut node_indices = HashMap::<Entity, usize>::new();
+    let mut nodes = vec![];
+
+    // index 0 is the super source and index 1 the super sink
+    let mut network = Network::new(2);
+    let mut node_index = |entity: Entity, network: &mut Network| {
+        *node_indices.entry(entity).or_insert_with(|| {
+            nodes.push(entity);
+            network.outgoing.push(vec![]);
+            network.outgoing.len() - 1
+        })
+    };
+
+    for edge in &graph.edges {
+        if edge.substance_type != substance_type
+            || matches!(edge.usability, InteractionUsability::Disruption)
+        {
+            continue;
+        }
+
+        let from = node_index(edge.from, &mut network);
+        let to = node_index(edge.to, &mut network);
+        network.add_arc(from, to, edge.amount.max(Decimal::ZERO), Some(edge.flow));
+    }
+
+    for source in sources {
+        let idx = node_index(*source, &mut network);
+        network.add_arc(0, idx, Decimal::MAX, None);
+    }
+    for sink in sinks {
+        let idx = node_index(*sink, &mut network);
+        network.add_arc(idx, 1, Decimal::MAX, None);
+    }
+
+    let mut max_flow = Decimal::ZERO;
+
+    loop {
+        let reached_by = network.bfs(0);
+        if reached_by[1].is_none() {
+            break;
+        }
+
+        let mut bottleneck = Decimal::MAX;
+        let mut node = 1;
+        while let Some(arc_idx) = reached_by[node] {
+            let (_, capacity, reverse_idx, _) = network.arcs[arc_idx];
+            bottleneck = bottleneck.min(capacity);
+            node = network.arcs[reverse_idx].0;
+        }
+
+        // Sources and sinks are disjoint, so every path contains at least one interaction.
+        if bottleneck == Decimal::ZERO {
+            break;
+        }
+
+        let mut node = 1;
+        while let Some(arc_idx) = reached_by[node] {
+            let reverse_idx = network.arcs[arc_idx].2;
+            network.arcs[arc_idx].1 -= bottleneck;
+            network.arcs[reverse_idx].1 += bottleneck;
+            node = network.arcs[reverse_idx].0;
+        }
+
+        max_flow = max_flow
+            .checked_add(bottleneck)
+            .ok_or_else(|| "The maximum flow is too large to compute".to_string())?;
+    }
+
+    // Everything reachable from the super source in the residual network is on the source side
+    // of the minimum cut.
+    let reached_by = network.bfs(0);
+    let is_source_side = |idx: usize| idx == 0 || reached_by[idx].is_some();
+
+    let mut result = MaxFlowResult {
+        max_flow,
+        ..default()
+    };
+
+    for (from, arc_indices) in network.outgoing.iter().enumerate() {
+        if !is_source_side(from) {
+            continue;
+        }
+
+        for arc_idx in arc_indices {
+            let (to, _, _, flow) = network.arcs[*arc_idx];
+
+            if let Some(flow) = flow {
+                if !is_source_side(to) {
+                    result.cut_flows.push(flow);
+
+                    let system = nodes[from - 2];
+                    if is_system(system) && !result.limiting_systems.contains(&system) {
+                        result.limiting_systems.push(system);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Shows the bottleneck analysis window where the user picks the substance type, sources and sinks.
+pub fn bottleneck_analysis_egui(
+    mut egui_contexts: EguiContexts,
+    mut analysis: ResMut<BottleneckAnalysis>,
+    mut flow_highlight: ResMut<FlowHighlight>,
+    flow_query: Query<(
+        Entity,
+        &Flow,
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>,
+    interface_subsystem_query: Query<&InterfaceSubsystemConnection>,
+    system_query: Query<(), With<crate::components::System>>,
+    name_query: Query<&Name>,
+) {
+    if !analysis.open {
+        if analysis.highlight_cut {
+            analysis.highlight_cut = false;
+            flow_highlight.clear();
+        }
+        return;
+    }
+
+    let graph = InteractionGraph::from_query(&flow_query, &interface_subsystem_query);
+
+    let name_of = |entity: Entity| {
+        name_query
+            .get(entity)
+            .map(|name| name.to_string())
+            .unwrap_or_else(|_| "<removed>".to_string())
+    };
+
+    let substance_type = analysis.substance_type;
+    let carries_substance = |entity: &Entity| {
+        graph.edges.iter().any(|edge| {
+            edge.substance_type == substance_type && (edge.from == *entity || edge.to == *entity)
+        })
+    };
+    let sources = graph
+        .sources
+        .iter()
+        .copied()
+        .filter(carries_substance)
+        .collect::<Vec<_>>();
+    let sinks = graph
+        .sinks
+        .iter()
+        .copied()
+        .filter(carries_substance)
+        .collect::<Vec<_>>();
+
+    let mut open = analysis.open;
+    let mut compute = false;
+    let mut highlight_cut = analysis.highlight_cut;
+
+    let analysis = &mut *analysis;
+
+    egui::Window::new("Bottleneck Analysis")
+        .open(&mut open)
+        .default_width(320.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Substance Type");
+                ComboBox::from_id_source("Bottleneck Substance Type")
+                    .selected_text(format!("{:?}", analysis.substance_type))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut analysis.substance_type,
+                            SubstanceType::Energy,
+                            "Energy",
+                        );
+                        ui.selectable_value(
+                            &mut analysis.substance_type,
+                            SubstanceType::Material,
+                            "Material",
+                        );
+                        ui.selectable_value(
+                            &mut analysis.substance_type,
+                            SubstanceType::Message,
+                            "Message",
+                        );
+                    });
+            });
+
+            ui.columns(2, |columns| {
+                columns[0].label("Sources");
+                for source in &sources {
+                    entity_checkbox(&mut columns[0], &mut analysis.sources, *source, &name_of);
+                }
+                columns[1].label("Sinks");
+                for sink in &sinks {
+                    entity_checkbox(&mut columns[1], &mut analysis.sinks, *sink, &name_of);
+                }
+            });
+
+            compute = ui.button("Compute max flow").clicked();
+            ui.checkbox(&mut highlight_cut, "Highlight minimum cut on diagram");
+
+            match &analysis.result {
+                Some(Ok(result)) => {
+                    ui.separator();
+                    ui.label(format!("Maximum flow: {}", result.max_flow));
+
+                    ui.label("Limiting interactions:");
+                    egui::Grid::new("Minimum Cut Grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for flow in &result.cut_flows {
+                                ui.label(name_of(*flow));
+                                if let Some(edge) = graph.edges.iter().find(|e| e.flow == *flow) {
+                                    ui.label(edge.amount.to_string());
+                                }
+                                ui.end_row();
+                            }
+                        });
+
+                    if !result.limiting_systems.is_empty() {
+                        ui.label("Limiting systems:");
+                        for system in &result.limiting_systems {
+                            ui.label(name_of(*system));
+                        }
+                    }
+                }
+                Some(Err(error)) => {
+                    ui.separator();
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                None => {}
+            }
+        });
+
+    analysis.open = open;
+
+    if compute {
+        analysis.sources.retain(|source| sources.contains(source));
+        analysis.sinks.retain(|sink| sinks.contains(sink));
+
+        analysis.result = Some(compute_max_flow(
+            &graph,
+            analysis.substance_type,
+            &analysis.sources,
+            &analysis.sinks,
+            |entity| system_query.contains(entity),
+        ));
+    }
+
+    if compute || highlight_cut != analysis.highlight_cut {
+        analysis.highlight_cut = highlight_cut;
+
+        flow_highlight.clear();
+        if highlight_cut {
+            if let Some(Ok(result)) = &analysis.result {
+                flow_highlight.extend(result.cut_flows.iter().copied());
+            }
+        }
+    }
+}
+
+fn entity_checkbox(
+    ui: &mut egui::Ui,
+    selected: &mut HashSet<Entity>,
+    entity: Entity,
+    name_of: &impl Fn(Entity) -> String,
+) {
+    let mut checked = selected.contains(&entity);
+    if ui.checkbox(&mut checked, name_of(entity)).changed() {
+        if checked {
+            selected.insert(entity);
+        } else {
+            selected.remove(&entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::analysis::InteractionEdge;
+    use rust_decimal_macros::dec;
+
+    fn edge(flow: u32, from: u32, to: u32, amount: Decimal) -> InteractionEdge {
+        InteractionEdge {
+            flow: Entity::from_raw(flow),
+            from: Entity::from_raw(from),
+            to: Entity::from_raw(to),
+            usability: InteractionUsability::Resource,
+            substance_type: SubstanceType::Material,
+            amount,
+        }
+    }
+
+    fn entities(indices: &[u32]) -> HashSet<Entity> {
+        indices.iter().map(|idx| Entity::from_raw(*idx)).collect()
+    }
+
+    /// Source 1 supplies systems 2 and 3 which both deliver to sink 4.
+    fn diamond() -> InteractionGraph {
+        InteractionGraph {
+            edges: vec![
+                edge(10, 1, 2, dec!(5)),
+                edge(11, 1, 3, dec!(3)),
+                edge(12, 2, 4, dec!(2)),
+                edge(13, 3, 4, dec!(4)),
+            ],
+            ..default()
+        }
+    }
+
+    #[test]
+    fn max_flow_is_limited_by_the_minimum_cut() {
+        let result = compute_max_flow(
+            &diamond(),
+            SubstanceType::Material,
+            &entities(&[1]),
+            &entities(&[4]),
+            |entity| matches!(entity.index(), 2 | 3),
+        )
+        .unwrap();
+
+        assert_eq!(result.max_flow, dec!(5));
+
+        let mut cut_flows = result.cut_flows.clone();
+        cut_flows.sort();
+        assert_eq!(cut_flows, vec![Entity::from_raw(11), Entity::from_raw(12)]);
+
+        let mut limiting_systems = result.limiting_systems.clone();
+        limiting_systems.sort();
+        assert_eq!(limiting_systems, vec![Entity::from_raw(2)]);
+    }
+
+    #[test]
+    fn other_substance_types_and_disruptions_are_ignored() {
+        let mut graph = diamond();
+        graph.edges[0].substance_type = SubstanceType::Energy;
+        graph.edges[1].usability = InteractionUsability::Disruption;
+
+        let result = compute_max_flow(
+            &graph,
+            SubstanceType::Material,
+            &entities(&[1]),
+            &entities(&[4]),
+            |_| true,
+        )
+        .unwrap();
+
+        assert_eq!(result.max_flow, Decimal::ZERO);
+        assert!(result.cut_flows.is_empty());
+    }
+
+    #[test]
+    fn overlapping_sources_and_sinks_are_rejected() {
+        let result = compute_max_flow(
+            &diamond(),
+            SubstanceType::Material,
+            &entities(&[1, 4]),
+            &entities(&[4]),
+            |_| true,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn too_large_max_flow_is_an_error() {
+        let graph = InteractionGraph {
+            edges: vec![
+                edge(10, 1, 3, Decimal::MAX),
+                edge(11, 2, 3, Decimal::MAX),
+                edge(12, 3, 4, Decimal::MAX),
+                edge(13, 3, 5, Decimal::MAX),
+            ],
+            ..default()
+        };
+
+        let result = compute_max_flow(
+            &graph,
+            SubstanceType::Material,
+            &entities(&[1, 2]),
+            &entities(&[4, 5]),
+            |_| true,
+        );
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,54 @@
+//! Analyses that are computed over the interactions of the whole model.
+mod disruption;
+mod graph;
+mod max_flow;
+mod monte_carlo;
+mod overlay;
+mod propagation;
+
+pub use disruption::*;
+pub use graph::*;
+pub use max_flow::*;
+pub use monte_carlo::*;
+pub use overlay::*;
+pub use propagation::*;
+
+use crate::components::{FlowEndConnection, FlowStartConnection};
+use crate::plugins::lyon_selection::apply_highlight_bundles;
+use crate::systems::{
+    update_color_from_substance_type, update_interface_subsystem_color,
+    update_system_color_from_subsystem,
+};
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+pub struct AnalysisPlugin;
+
+impl Plugin for AnalysisPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DisruptionAnalysis>()
+            .init_resource::<BottleneckAnalysis>()
+            .init_resource::<AmountPropagation>()
+            .init_resource::<MonteCarloAnalysis>()
+            .init_resource::<SystemHeatmap>()
+            .init_resource::<FlowHighlight>()
+            .add_systems(
+                Update,
+                (
+                    disruption_analysis_egui.after(bevy_egui::EguiSet::InitContexts),
+                    bottleneck_analysis_egui.after(bevy_egui::EguiSet::InitContexts),
+                    amount_propagation_egui.after(bevy_egui::EguiSet::InitContexts),
+                    monte_carlo_egui.after(bevy_egui::EguiSet::InitContexts),
+                    apply_system_heatmap
+                        .after(disruption_analysis_egui)
+                        .after(update_interface_subsystem_color)
+                        .after(update_system_color_from_subsystem),
+                    apply_flow_highlight
+                        .after(bottleneck_analysis_egui)
+                        .after(update_color_from_substance_type::<FlowStartConnection>)
+                        .after(update_color_from_substance_type::<FlowEndConnection>)
+                        .before(apply_highlight_bundles::<Stroke, Stroke>),
+                ),
+            );
+    }
+}
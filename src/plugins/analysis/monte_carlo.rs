@@ -0,0 +1,566 @@
+//! Monte Carlo analysis that samples the uncertain interaction amounts, propagates every sample
+//! through the transformation rules and aggregates the totals of systems & sinks.
+use super::{propagate_amounts, InteractionGraph, PropagationFlow};
+use crate::components::*;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy::utils::HashMap;
+use bevy_egui::egui::{self, Color32, Stroke, Ui};
+use bevy_egui::EguiContexts;
+use rfd::FileDialog;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use std::path::PathBuf;
+
+/// Small and fast pseudo random number generator (SplitMix64). Seeded runs are reproducible.
+struct SampleRng(u64);
+
+impl SampleRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal distributed (Box-Muller transform).
+    fn next_normal(&mut self) -> f64 {
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+    }
+}
+
+fn to_f64(decimal: Decimal) -> f64 {
+    decimal.to_f64().unwrap_or_default()
+}
+
+/// Draws one sample of an amount with the given nominal value and uncertainty.
+fn sample_amount(nominal: f64, uncertainty: &AmountUncertainty, rng: &mut SampleRng) -> f64 {
+    match *uncertainty {
+        AmountUncertainty::Range { min, max } => {
+            let (min, max) = (to_f64(min), to_f64(max));
+            if max <= min {
+                return min;
+            }
+            let mode = nominal.clamp(min, max);
+            let u = rng.next_f64();
+            if u < (mode - min) / (max - min) {
+                min + (u * (max - min) * (mode - min)).sqrt()
+            } else {
+                max - ((1.0 - u) * (max - min) * (max - mode)).sqrt()
+            }
+        }
+        AmountUncertainty::Uniform { min, max } => {
+            let (min, max) = (to_f64(min), to_f64(max));
+            min + rng.next_f64() * (max - min).max(0.0)
+        }
+        AmountUncertainty::Normal { std_dev } => {
+            (nominal + to_f64(std_dev) * rng.next_normal()).max(0.0)
+        }
+    }
+}
+
+/// Summary statistics of the samples of one quantity.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Percentiles {
+    pub mean: f64,
+    pub p5: f64,
+    pub p50: f64,
+    pub p95: f64,
+}
+
+impl Percentiles {
+    pub fn from_samples(mut samples: Vec<f64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        samples.sort_by(f64::total_cmp);
+        let percentile = |p: f64| samples[(p * (samples.len() - 1) as f64).round() as usize];
+
+        Self {
+            mean: samples.iter().sum::<f64>() / samples.len() as f64,
+            p5: percentile(0.05),
+            p50: percentile(0.5),
+            p95: percentile(0.95),
+        }
+    }
+}
+
+/// Percentiles of all aggregated quantities of a Monte Carlo run.
+#[derive(Clone, Debug, Default)]
+pub struct MonteCarloResult {
+    pub sample_count: usize,
+    pub flows: HashMap<Entity, Percentiles>,
+    pub system_inflows: HashMap<Entity, Percentiles>,
+    pub system_outflows: HashMap<Entity, Percentiles>,
+    pub sinks: HashMap<Entity, Percentiles>,
+}
+
+/// State of the Monte Carlo window.
+#[derive(Resource)]
+pub struct MonteCarloAnalysis {
+    pub open: bool,
+    pub sample_count: usize,
+    pub seed: u64,
+    pub result: Option<MonteCarloResult>,
+    /// The run in progress. Runs happen in the background so the UI stays responsive.
+    pub run_task: Option<Task<MonteCarloResult>>,
+    pub export_task: Option<Task<Option<PathBuf>>>,
+    pub export_status: Option<String>,
+}
+
+impl Default for MonteCarloAnalysis {
+    fn default() -> Self {
+        Self {
+            open: false,
+            sample_count: 1000,
+            seed: 1,
+            result: None,
+            run_task: None,
+            export_task: None,
+            export_status: None,
+        }
+    }
+}
+
+/// Samples all uncertain entered amounts `sample_count` times. Every sample is propagated through
+/// the transformation rules and then summed into the inflow & outflow of every node of the graph.
+pub fn run_monte_carlo(
+    graph: &InteractionGraph,
+    rules: &HashMap<Entity, TransformationRule>,
+    flows: &HashMap<Entity, PropagationFlow>,
+    uncertainties: &HashMap<Entity, AmountUncertainty>,
+    sample_count: usize,
+    seed: u64,
+) -> MonteCarloResult {
+    let mut rng = SampleRng(seed);
+
+    let mut flow_samples = HashMap::<Entity, Vec<f64>>::new();
+    let mut inflow_samples = HashMap::<Entity, Vec<f64>>::new();
+    let mut outflow_samples = HashMap::<Entity, Vec<f64>>::new();
+
+    let mut sampled_flows = flows.clone();
+
+    for _ in 0..sample_count {
+        for (entity, flow) in sampled_flows.iter_mut() {
+            if let (true, Some(uncertainty)) = (flow.is_entered, uncertainties.get(entity)) {
+                let nominal = to_f64(flows[entity].amount);
+                let sample = sample_amount(nominal, uncertainty, &mut rng);
+                flow.amount = Decimal::from_f64(sample).unwrap_or_default();
+            }
+        }
+
+        let computed = if rules.is_empty() {
+            HashMap::new()
+        } else {
+            propagate_amounts(graph, rules, &sampled_flows).amounts
+        };
+
+        let mut inflows = HashMap::<Entity, f64>::new();
+        let mut outflows = HashMap::<Entity, f64>::new();
+
+        for edge in &graph.edges {
+            let Some(flow) = sampled_flows.get(&edge.flow) else {
+                continue;
+            };
+            let amount = to_f64(computed.get(&edge.flow).copied().unwrap_or(flow.amount));
+
+            flow_samples.entry(edge.flow).or_default().push(amount);
+            *outflows.entry(edge.from).or_default() += amount;
+            *inflows.entry(edge.to).or_default() += amount;
+        }
+
+        for (node, amount) in inflows {
+            inflow_samples.entry(node).or_default().push(amount);
+        }
+        for (node, amount) in outflows {
+            outflow_samples.entry(node).or_default().push(amount);
+        }
+    }
+
+    let summarize = |samples: HashMap<Entity, Vec<f64>>| {
+        samples
+            .into_iter()
+            .map(|(entity, samples)| (entity, Percentiles::from_samples(samples)))
+            .collect::<HashMap<_, _>>()
+    };
+
+    let mut system_inflows = summarize(inflow_samples);
+    let system_outflows = summarize(outflow_samples)
+        .into_iter()
+        .filter(|(node, _)| !graph.sources.contains(node))
+        .collect();
+
+    let sinks = graph
+        .sinks
+        .iter()
+        .filter_map(|sink| system_inflows.remove(sink).map(|p| (*sink, p)))
+        .collect();
+
+    MonteCarloResult {
+        sample_count,
+        flows: summarize(flow_samples),
+        system_inflows,
+        system_outflows,
+        sinks,
+    }
+}
+
+/// Draws the median with a horizontal error bar from the 5th to the 95th percentile.
+pub fn error_bar_egui(ui: &mut Ui, label: &str, percentiles: &Percentiles) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(100.0, 12.0), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        let stroke = Stroke::new(1.5_f32, Color32::DARK_GRAY);
+
+        // The bar spans from zero to the 95th percentile with some margin.
+        let scale = if percentiles.p95 > 0.0 {
+            rect.width() / (percentiles.p95 * 1.1) as f32
+        } else {
+            0.0
+        };
+        let x = |value: f64| rect.left() + value as f32 * scale;
+
+        painter.hline(
+            x(percentiles.p5)..=x(percentiles.p95),
+            rect.center().y,
+            stroke,
+        );
+        painter.vline(x(percentiles.p5), rect.y_range(), stroke);
+        painter.vline(x(percentiles.p95), rect.y_range(), stroke);
+        painter.circle_filled(
+            egui::pos2(x(percentiles.p50), rect.center().y),
+            3.0,
+            Color32::from_rgb(0, 100, 200),
+        );
+
+        ui.label(format!(
+            "{:.3} ({:.3} – {:.3})",
+            percentiles.p50, percentiles.p5, percentiles.p95
+        ));
+    });
+}
+
+/// Shows the Monte Carlo results of the given element in the inspector.
+pub fn monte_carlo_element_egui(ui: &mut Ui, result: &MonteCarloResult, entity: Entity) {
+    let rows = [
+        ("Amount", result.flows.get(&entity)),
+        ("Inflow", result.system_inflows.get(&entity)),
+        ("Outflow", result.system_outflows.get(&entity)),
+        ("Sink Total", result.sinks.get(&entity)),
+    ];
+
+    if rows.iter().all(|(_, percentiles)| percentiles.is_none()) {
+        return;
+    }
+
+    ui.separator();
+    ui.label(format!(
+        "Monte Carlo ({} samples, median & 5th – 95th percentile)",
+        result.sample_count
+    ));
+
+    for (label, percentiles) in rows {
+        if let Some(percentiles) = percentiles {
+            error_bar_egui(ui, label, percentiles);
+        }
+    }
+}
+
+/// Renders the result as CSV with one row per element & quantity.
+fn result_to_csv(result: &MonteCarloResult, name_query: &Query<&Name>) -> String {
+    let mut csv = String::from("element,quantity,mean,p5,p50,p95\n");
+
+    let quantities = [
+        ("amount", &result.flows),
+        ("inflow", &result.system_inflows),
+        ("outflow", &result.system_outflows),
+        ("sink_total", &result.sinks),
+    ];
+
+    for (quantity, percentiles_map) in quantities {
+        let mut rows = percentiles_map
+            .iter()
+            .map(|(entity, percentiles)| {
+                let name = name_query
+                    .get(*entity)
+                    .map(|name| name.to_string())
+                    .unwrap_or_default();
+                (name, percentiles)
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (name, p) in rows {
+            csv.push_str(&format!(
+                "\"{}\",{},{},{},{},{}\n",
+                name.replace('"', "\"\""),
+                quantity,
+                p.mean,
+                p.p5,
+                p.p50,
+                p.p95
+            ));
+        }
+    }
+
+    csv
+}
+
+/// Shows the Monte Carlo window, runs the analysis and exports the result to CSV.
+pub fn monte_carlo_egui(
+    mut egui_contexts: EguiContexts,
+    mut monte_carlo: ResMut<MonteCarloAnalysis>,
+    flow_query: Query<(
+        Entity,
+        &Flow,
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>,
+    interface_subsystem_query: Query<&InterfaceSubsystemConnection>,
+    system_query: Query<(Entity, &crate::components::System)>,
+    name_query: Query<&Name>,
+) {
+    if let Some(task) = &mut monte_carlo.run_task {
+        if let Some(result) = future::block_on(future::poll_once(task)) {
+            monte_carlo.run_task = None;
+            monte_carlo.result = Some(result);
+            monte_carlo.export_status = None;
+        }
+    }
+
+    if let Some(task) = &mut monte_carlo.export_task {
+        if let Some(path) = future::block_on(future::poll_once(task)) {
+            monte_carlo.export_task = None;
+
+            if let (Some(path), Some(result)) = (path, &monte_carlo.result) {
+                let status = match std::fs::write(&path, result_to_csv(result, &name_query)) {
+                    Ok(()) => format!("Exported to {}", path.display()),
+                    Err(error) => format!("Export failed: {}", error),
+                };
+                monte_carlo.export_status = Some(status);
+            }
+        }
+    }
+
+    if !monte_carlo.open {
+        return;
+    }
+
+    let mut open = monte_carlo.open;
+    let mut run = false;
+    let mut export = false;
+
+    egui::Window::new("Monte Carlo")
+        .open(&mut open)
+        .default_width(360.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label("Samples the uncertain amounts of the interactions and propagates them through the transformation rules.");
+
+            egui::Grid::new("Monte Carlo Settings").show(ui, |ui| {
+                ui.label("Samples");
+                ui.add(egui::DragValue::new(&mut monte_carlo.sample_count).clamp_range(1..=100_000));
+                ui.end_row();
+                ui.label("Seed");
+                ui.add(egui::DragValue::new(&mut monte_carlo.seed));
+                ui.end_row();
+            });
+
+            ui.horizontal(|ui| {
+                run = ui
+                    .add_enabled(monte_carlo.run_task.is_none(), egui::Button::new("Run"))
+                    .clicked();
+                if monte_carlo.run_task.is_some() {
+                    ui.spinner();
+                    ui.label("Running...");
+                }
+                export = ui
+                    .add_enabled(
+                        monte_carlo.result.is_some() && monte_carlo.export_task.is_none(),
+                        egui::Button::new("Export CSV..."),
+                    )
+                    .clicked();
+            });
+
+            if let Some(status) = &monte_carlo.export_status {
+                ui.label(status);
+            }
+
+            let Some(result) = &monte_carlo.result else {
+                return;
+            };
+
+            ui.separator();
+
+            let name = |entity: &Entity| {
+                name_query
+                    .get(*entity)
+                    .map(|name| name.to_string())
+                    .unwrap_or_default()
+            };
+
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    ui.strong("Sinks");
+                    let mut sinks = result.sinks.iter().collect::<Vec<_>>();
+                    sinks.sort_by_key(|(entity, _)| name(entity));
+                    for (entity, percentiles) in sinks {
+                        error_bar_egui(ui, &name(entity), percentiles);
+                    }
+
+                    ui.strong("System Outflows");
+                    let mut systems = result
+                        .system_outflows
+                        .iter()
+                        .filter(|(entity, _)| system_query.contains(**entity))
+                        .collect::<Vec<_>>();
+                    systems.sort_by_key(|(entity, _)| name(entity));
+                    for (entity, percentiles) in systems {
+                        error_bar_egui(ui, &name(entity), percentiles);
+                    }
+                });
+        });
+
+    monte_carlo.open = open;
+
+    if run {
+        let graph = InteractionGraph::from_query(&flow_query, &interface_subsystem_query);
+
+        let rules = system_query
+            .iter()
+            .filter_map(|(entity, system)| {
+                system
+                    .transformation_rule
+                    .clone()
+                    .map(|rule| (entity, rule))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let flows = flow_query
+            .iter()
+            .filter_map(|(entity, flow, ..)| {
+                name_query
+                    .get(entity)
+                    .ok()
+                    .map(|name| (entity, PropagationFlow::new(name, flow)))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let uncertainties = flow_query
+            .iter()
+            .filter_map(|(entity, flow, ..)| flow.uncertainty.map(|u| (entity, u)))
+            .collect::<HashMap<_, _>>();
+
+        let sample_count = monte_carlo.sample_count;
+        let seed = monte_carlo.seed;
+
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            run_monte_carlo(&graph, &rules, &flows, &uncertainties, sample_count, seed)
+        });
+        monte_carlo.run_task = Some(task);
+    }
+
+    if export {
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            FileDialog::new()
+                .add_filter("csv", &["csv"])
+                .set_file_name("monte_carlo.csv")
+                .save_file()
+        });
+        monte_carlo.export_task = Some(task);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::analysis::InteractionEdge;
+    use rust_decimal_macros::dec;
+
+    fn entity(idx: u32) -> Entity {
+        Entity::from_raw(idx)
+    }
+
+    /// Source 1 delivers flow 10 to sink 2.
+    fn source_to_sink(amount: Decimal) -> (InteractionGraph, HashMap<Entity, PropagationFlow>) {
+        let graph = InteractionGraph {
+            edges: vec![InteractionEdge {
+                flow: entity(10),
+                from: entity(1),
+                to: entity(2),
+                usability: InteractionUsability::Product,
+                substance_type: SubstanceType::Material,
+                amount,
+            }],
+            sources: vec![entity(1)],
+            sinks: vec![entity(2)],
+        };
+        let flows = HashMap::from([(
+            entity(10),
+            PropagationFlow {
+                keys: vec!["product".to_string()],
+                unit: String::new(),
+                amount,
+                is_entered: true,
+            },
+        )]);
+
+        (graph, flows)
+    }
+
+    #[test]
+    fn percentiles_of_sorted_samples() {
+        let percentiles = Percentiles::from_samples((0..=100).rev().map(f64::from).collect());
+
+        assert_eq!(percentiles.mean, 50.0);
+        assert_eq!(percentiles.p5, 5.0);
+        assert_eq!(percentiles.p50, 50.0);
+        assert_eq!(percentiles.p95, 95.0);
+    }
+
+    #[test]
+    fn certain_amounts_are_not_sampled() {
+        let (graph, flows) = source_to_sink(dec!(3));
+
+        let result = run_monte_carlo(&graph, &HashMap::new(), &flows, &HashMap::new(), 10, 1);
+
+        let sink = result.sinks[&entity(2)];
+        assert_eq!((sink.p5, sink.p50, sink.p95), (3.0, 3.0, 3.0));
+        assert!(!result.system_outflows.contains_key(&entity(1)));
+    }
+
+    #[test]
+    fn samples_stay_in_range_and_are_reproducible() {
+        let (graph, flows) = source_to_sink(dec!(3));
+        let uncertainties = HashMap::from([(
+            entity(10),
+            AmountUncertainty::Range {
+                min: dec!(1),
+                max: dec!(4),
+            },
+        )]);
+
+        let run =
+            |seed| run_monte_carlo(&graph, &HashMap::new(), &flows, &uncertainties, 500, seed);
+        let result = run(7);
+
+        let amount = result.flows[&entity(10)];
+        assert!(amount.p5 >= 1.0 && amount.p95 <= 4.0);
+        assert!(amount.p5 < amount.p50 && amount.p50 < amount.p95);
+        assert_eq!(run(7).flows[&entity(10)].p50, amount.p50);
+    }
+}
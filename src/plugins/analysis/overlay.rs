@@ -0,0 +1,140 @@
+//! Visualizes analysis results on the diagram by temporarily recoloring system elements.
+use crate::components::Flow;
+use crate::plugins::lyon_selection::HighlightBundles;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_prototype_lyon::prelude::*;
+
+/// Score in `[0, 1]` per system entity. Systems without a score keep their regular color.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct SystemHeatmap(HashMap<Entity, f32>);
+
+/// Remembers the fill color a system had before the heatmap was applied so it can be restored.
+#[derive(Copy, Clone, Debug, Component, PartialEq)]
+pub struct HeatmapOriginalFill {
+    original: Color,
+    /// The heat color that was painted last. If the fill is something else, another system has
+    /// changed it in the meantime and that's the color to restore.
+    painted: Color,
+}
+
+impl HeatmapOriginalFill {
+    fn update(stored: Option<Self>, current: Color, heat: Color) -> Self {
+        match stored {
+            Some(stored) if stored.painted == current => Self {
+                painted: heat,
+                ..stored
+            },
+            _ => Self {
+                original: current,
+                painted: heat,
+            },
+        }
+    }
+}
+
+/// Applies the heatmap colors to the system circles or restores the original colors if a system
+/// isn't part of the heatmap anymore. Other systems reset the fill, e.g. when the theme changes,
+/// so the heat colors are re-applied on top of the new fill whenever that happens.
+pub fn apply_system_heatmap(
+    mut commands: Commands,
+    heatmap: Res<SystemHeatmap>,
+    mut system_query: Query<
+        (Entity, &mut Fill, Option<&HeatmapOriginalFill>),
+        With<crate::components::System>,
+    >,
+) {
+    for (entity, mut fill, original_fill) in &mut system_query {
+        if let Some(score) = heatmap.get(&entity) {
+            let heat = heat_color(*score);
+            let updated = HeatmapOriginalFill::update(original_fill.copied(), fill.color, heat);
+            if original_fill != Some(&updated) {
+                commands.entity(entity).insert(updated);
+            }
+            if fill.color != heat {
+                fill.color = heat;
+            }
+        } else if let Some(original_fill) = original_fill {
+            fill.color = original_fill.original;
+            commands.entity(entity).remove::<HeatmapOriginalFill>();
+        }
+    }
+}
+
+/// Interpolates from a pale yellow for a score of 0 to a deep red for a score of 1.
+pub fn heat_color(score: f32) -> Color {
+    let cold = Color::rgb_u8(255, 237, 160);
+    let hot = Color::rgb_u8(189, 0, 38);
+    let t = score.clamp(0.0, 1.0);
+
+    Color::rgb(
+        cold.r() + (hot.r() - cold.r()) * t,
+        cold.g() + (hot.g() - cold.g()) * t,
+        cold.b() + (hot.b() - cold.b()) * t,
+    )
+}
+
+/// Flows that are emphasized on the diagram, e.g. the interactions of a minimum cut.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct FlowHighlight(HashSet<Entity>);
+
+/// Remembers the stroke color a flow had before it was highlighted so it can be restored.
+#[derive(Copy, Clone, Debug, Component)]
+pub struct HighlightOriginalColor(Color);
+
+/// Color of flows that are part of the [`FlowHighlight`].
+pub const FLOW_HIGHLIGHT_COLOR: Color = Color::rgb(0.85, 0.1, 0.85);
+
+/// Applies the highlight color to the highlighted flows or restores the original colors if a flow
+/// isn't highlighted anymore. Flows get their color reset when they or the substance colors
+/// change, so the highlight is re-applied on top of the new color whenever that happens.
+pub fn apply_flow_highlight(
+    mut commands: Commands,
+    flow_highlight: Res<FlowHighlight>,
+    mut flow_query: Query<
+        (
+            Entity,
+            &mut HighlightBundles<Stroke, Stroke>,
+            Option<&HighlightOriginalColor>,
+        ),
+        With<Flow>,
+    >,
+) {
+    for (entity, mut highlight, original_color) in &mut flow_query {
+        if flow_highlight.contains(&entity) {
+            if highlight.idle.color != FLOW_HIGHLIGHT_COLOR {
+                commands
+                    .entity(entity)
+                    .insert(HighlightOriginalColor(highlight.idle.color));
+                highlight.idle.color = FLOW_HIGHLIGHT_COLOR;
+                highlight.selected.color = FLOW_HIGHLIGHT_COLOR;
+            }
+        } else if let Some(original_color) = original_color {
+            highlight.idle.color = original_color.0;
+            highlight.selected.color = original_color.0;
+            commands.entity(entity).remove::<HighlightOriginalColor>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_fill_before_the_heatmap_is_remembered_across_score_changes() {
+        let first = HeatmapOriginalFill::update(None, Color::WHITE, heat_color(0.2));
+        let second = HeatmapOriginalFill::update(Some(first), heat_color(0.2), heat_color(0.8));
+
+        assert_eq!(second.original, Color::WHITE);
+        assert_eq!(second.painted, heat_color(0.8));
+    }
+
+    #[test]
+    fn fills_changed_by_other_systems_replace_the_remembered_fill() {
+        let stored = HeatmapOriginalFill::update(None, Color::WHITE, heat_color(0.5));
+        let updated = HeatmapOriginalFill::update(Some(stored), Color::GRAY, heat_color(0.5));
+
+        assert_eq!(updated.original, Color::GRAY);
+    }
+}
@@ -0,0 +1,489 @@
+//! Propagates interaction amounts through the transformation rules of systems.
+//! Entered amounts are never changed. All other amounts that can be derived are computed.
+use super::InteractionGraph;
+use crate::components::*;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_egui::{egui, EguiContexts};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Relative tolerance when comparing amounts that were computed in different ways.
+const AMOUNT_TOLERANCE: Decimal = dec!(0.000001);
+
+/// Number of decimal places of computed amounts.
+const AMOUNT_DECIMAL_PLACES: u32 = 10;
+
+/// The data of an interaction that is required to match it to the terms of a rule.
+#[derive(Clone, Debug)]
+pub struct PropagationFlow {
+    /// Lower case identifiers that a rule term can match against.
+    pub keys: Vec<String>,
+    pub unit: String,
+    pub amount: Decimal,
+    pub is_entered: bool,
+}
+
+impl PropagationFlow {
+    pub fn new(name: &Name, flow: &Flow) -> Self {
+        Self {
+            keys: vec![
+                name.as_str().trim().to_lowercase(),
+                flow.substance_sub_type.trim().to_lowercase(),
+                format!("{:?}", flow.substance_type).to_lowercase(),
+                format!("{:?}", flow.usability).to_lowercase(),
+            ],
+            unit: flow.unit.clone(),
+            amount: flow.amount,
+            is_entered: matches!(flow.amount_origin, AmountOrigin::Entered),
+        }
+    }
+
+    fn matches(&self, term: &RuleTerm) -> bool {
+        let substance = term.substance.trim().to_lowercase();
+        !substance.is_empty() && self.keys.contains(&substance)
+    }
+}
+
+/// A problem found while propagating amounts through the rule of a system.
+#[derive(Clone, Debug)]
+pub struct PropagationIssue {
+    pub system: Entity,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PropagationResult {
+    /// Newly computed amounts by interaction entity.
+    pub amounts: HashMap<Entity, Decimal>,
+    pub issues: Vec<PropagationIssue>,
+}
+
+/// State of the amount propagation window.
+#[derive(Resource, Default)]
+pub struct AmountPropagation {
+    pub open: bool,
+    pub result: Option<PropagationResult>,
+}
+
+/// Repeatedly applies the rules of all systems whose scale can be determined from known amounts
+/// until no more amounts can be derived. A rule's scale is the known amount of one of its terms
+/// divided by the amount of that term. Different scales from different terms are contradictions.
+pub fn propagate_amounts(
+    graph: &InteractionGraph,
+    rules: &HashMap<Entity, TransformationRule>,
+    flows: &HashMap<Entity, PropagationFlow>,
+) -> PropagationResult {
+    let mut known = flows
+        .iter()
+        .filter(|(_, flow)| flow.is_entered)
+        .map(|(entity, flow)| (*entity, flow.amount))
+        .collect::<HashMap<_, _>>();
+
+    let mut result = PropagationResult::default();
+    let mut applied = HashSet::<Entity>::new();
+
+    let mut systems = rules.keys().copied().collect::<Vec<_>>();
+    systems.sort();
+
+    loop {
+        let mut progress = false;
+
+        for system in &systems {
+            if applied.contains(system) {
+                continue;
+            }
+
+            let rule = &rules[system];
+
+            let matched_terms = |terms: &[RuleTerm], incoming: bool| {
+                terms
+                    .iter()
+                    .map(|term| {
+                        let matched = graph
+                            .edges
+                            .iter()
+                            .filter(|edge| {
+                                (if incoming { edge.to } else { edge.from }) == *system
+                                    && flows.get(&edge.flow).is_some_and(|f| f.matches(term))
+                            })
+                            .map(|edge| edge.flow)
+                            .collect::<Vec<_>>();
+                        (term.clone(), matched)
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            let terms = matched_terms(&rule.inputs, true)
+                .into_iter()
+                .chain(matched_terms(&rule.outputs, false))
+                .collect::<Vec<_>>();
+
+            let mut scale: Option<(Decimal, &RuleTerm)> = None;
+
+            for (term, matched) in &terms {
+                if matched.is_empty()
+                    || term.amount.is_zero()
+                    || !matched.iter().all(|flow| known.contains_key(flow))
+                {
+                    continue;
+                }
+
+                let Some(term_scale) = checked_sum(matched.iter().map(|flow| known[flow]))
+                    .and_then(|total| total.checked_div(term.amount))
+                else {
+                    result.issues.push(PropagationIssue {
+                        system: *system,
+                        message: format!("The amounts of \"{}\" are too large to compute", term),
+                    });
+                    continue;
+                };
+
+                match scale {
+                    None => scale = Some((term_scale, term)),
+                    Some((existing, existing_term)) if !approx_eq(existing, term_scale) => {
+                        result.issues.push(PropagationIssue {
+                            system: *system,
+                            message: format!(
+                                "\"{}\" implies a scale of {} but \"{}\" implies {}",
+                                existing_term,
+                                round(existing),
+                                term,
+                                round(term_scale)
+                            ),
+                        });
+                    }
+                    _ => {}
+                }
+            }
+
+            let Some((scale, _)) = scale else {
+                continue;
+            };
+
+            applied.insert(*system);
+            progress = true;
+
+            for (term, matched) in &terms {
+                if matched.is_empty() {
+                    result.issues.push(PropagationIssue {
+                        system: *system,
+                        message: format!("No interaction matches \"{}\"", term.substance),
+                    });
+                    continue;
+                }
+
+                for flow in matched {
+                    let flow = &flows[flow];
+                    if !term.unit.is_empty()
+                        && !flow.unit.is_empty()
+                        && !term.unit.eq_ignore_ascii_case(&flow.unit)
+                    {
+                        result.issues.push(PropagationIssue {
+                            system: *system,
+                            message: format!(
+                                "\"{}\" is in {} but the matching interaction is in {}",
+                                term.substance, term.unit, flow.unit
+                            ),
+                        });
+                    }
+                }
+
+                let unknown = matched
+                    .iter()
+                    .filter(|flow| !known.contains_key(*flow))
+                    .copied()
+                    .collect::<Vec<_>>();
+
+                match unknown.as_slice() {
+                    [] => {}
+                    [flow] => {
+                        let amount =
+                            checked_sum(matched.iter().filter_map(|flow| known.get(flow).copied()))
+                                .and_then(|known_total| {
+                                    scale.checked_mul(term.amount)?.checked_sub(known_total)
+                                })
+                                .map(round);
+
+                        let Some(amount) = amount else {
+                            result.issues.push(PropagationIssue {
+                                system: *system,
+                                message: format!("\"{}\" would require a too large amount", term),
+                            });
+                            continue;
+                        };
+
+                        if amount.is_sign_negative() && !amount.is_zero() {
+                            result.issues.push(PropagationIssue {
+                                system: *system,
+                                message: format!(
+                                    "\"{}\" would require a negative amount of {}",
+                                    term, amount
+                                ),
+                            });
+                        } else {
+                            known.insert(*flow, amount);
+                            result.amounts.insert(*flow, amount);
+                        }
+                    }
+                    _ => result.issues.push(PropagationIssue {
+                        system: *system,
+                        message: format!(
+                            "\"{}\" matches {} interactions with unknown amounts",
+                            term.substance,
+                            unknown.len()
+                        ),
+                    }),
+                }
+            }
+        }
+
+        if !progress {
+            break;
+        }
+    }
+
+    for system in systems {
+        if !applied.contains(&system) {
+            result.issues.push(PropagationIssue {
+                system,
+                message: "Not enough known amounts to apply the transformation rule".to_string(),
+            });
+        }
+    }
+
+    result
+}
+
+/// Sums up the amounts or returns `None` if the sum is too large for a `Decimal`.
+fn checked_sum(amounts: impl IntoIterator<Item = Decimal>) -> Option<Decimal> {
+    amounts
+        .into_iter()
+        .try_fold(Decimal::ZERO, |sum, amount| sum.checked_add(amount))
+}
+
+fn approx_eq(a: Decimal, b: Decimal) -> bool {
+    a.checked_sub(b).is_some_and(|difference| {
+        difference.abs() <= AMOUNT_TOLERANCE * a.abs().max(b.abs()).max(Decimal::ONE)
+    })
+}
+
+fn round(amount: Decimal) -> Decimal {
+    amount.round_dp(AMOUNT_DECIMAL_PLACES).normalize()
+}
+
+/// Shows the amount propagation window and writes the computed amounts into the flows.
+pub fn amount_propagation_egui(
+    mut egui_contexts: EguiContexts,
+    mut propagation: ResMut<AmountPropagation>,
+    mut flow_query: Query<(
+        Entity,
+        &mut Flow,
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>,
+    interface_subsystem_query: Query<&InterfaceSubsystemConnection>,
+    system_query: Query<(Entity, &crate::components::System)>,
+    name_query: Query<&Name>,
+) {
+    if !propagation.open {
+        return;
+    }
+
+    let mut open = propagation.open;
+    let mut propagate = false;
+
+    egui::Window::new("Amount Propagation")
+        .open(&mut open)
+        .default_width(320.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label("Computes interaction amounts from the entered amounts and the transformation rules of the systems.");
+            propagate = ui.button("Propagate amounts").clicked();
+
+            if let Some(result) = &propagation.result {
+                ui.separator();
+                ui.label(format!("{} amounts computed", result.amounts.len()));
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for issue in &result.issues {
+                            let system_name = name_query
+                                .get(issue.system)
+                                .map(|name| name.to_string())
+                                .unwrap_or_default();
+                            ui.label(format!("{}: {}", system_name, issue.message));
+                        }
+                    });
+            }
+        });
+
+    propagation.open = open;
+
+    if propagate {
+        let mut lens = flow_query.transmute_lens::<(
+            Entity,
+            &Flow,
+            &FlowStartConnection,
+            &FlowEndConnection,
+            Option<&FlowStartInterfaceConnection>,
+            Option<&FlowEndInterfaceConnection>,
+        )>();
+        let graph = InteractionGraph::from_query(&lens.query(), &interface_subsystem_query);
+
+        let rules = system_query
+            .iter()
+            .filter_map(|(entity, system)| {
+                system
+                    .transformation_rule
+                    .clone()
+                    .map(|rule| (entity, rule))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let flows = flow_query
+            .iter()
+            .filter_map(|(entity, flow, ..)| {
+                name_query
+                    .get(entity)
+                    .ok()
+                    .map(|name| (entity, PropagationFlow::new(name, flow)))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let result = propagate_amounts(&graph, &rules, &flows);
+
+        for (entity, amount) in &result.amounts {
+            if let Ok((_, mut flow, ..)) = flow_query.get_mut(*entity) {
+                flow.amount = *amount;
+                flow.amount_origin = AmountOrigin::Computed;
+            }
+        }
+
+        propagation.result = Some(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::analysis::InteractionEdge;
+
+    fn entity(idx: u32) -> Entity {
+        Entity::from_raw(idx)
+    }
+
+    fn term(amount: Decimal, substance: &str) -> RuleTerm {
+        RuleTerm {
+            amount,
+            unit: String::new(),
+            substance: substance.to_string(),
+        }
+    }
+
+    fn flow(key: &str, amount: Option<Decimal>) -> PropagationFlow {
+        PropagationFlow {
+            keys: vec![key.to_string()],
+            unit: String::new(),
+            amount: amount.unwrap_or_default(),
+            is_entered: amount.is_some(),
+        }
+    }
+
+    /// Ore (flow 10) goes into system 2, which turns 2 ore into 1 steel (flow 11) and 1 slag
+    /// (flow 12).
+    fn smelter(
+        ore: Option<Decimal>,
+        steel: Option<Decimal>,
+    ) -> (
+        InteractionGraph,
+        HashMap<Entity, TransformationRule>,
+        HashMap<Entity, PropagationFlow>,
+    ) {
+        let edge = |flow, from, to| InteractionEdge {
+            flow: entity(flow),
+            from: entity(from),
+            to: entity(to),
+            usability: InteractionUsability::Resource,
+            substance_type: SubstanceType::Material,
+            amount: Decimal::ZERO,
+        };
+        let graph = InteractionGraph {
+            edges: vec![edge(10, 1, 2), edge(11, 2, 3), edge(12, 2, 4)],
+            ..default()
+        };
+
+        let rules = HashMap::from([(
+            entity(2),
+            TransformationRule {
+                inputs: vec![term(dec!(2), "ore")],
+                outputs: vec![term(dec!(1), "steel"), term(dec!(1), "slag")],
+            },
+        )]);
+
+        let flows = HashMap::from([
+            (entity(10), flow("ore", ore)),
+            (entity(11), flow("steel", steel)),
+            (entity(12), flow("slag", None)),
+        ]);
+
+        (graph, rules, flows)
+    }
+
+    #[test]
+    fn unknown_amounts_are_derived_from_the_rule() {
+        let (graph, rules, flows) = smelter(Some(dec!(10)), None);
+
+        let result = propagate_amounts(&graph, &rules, &flows);
+
+        assert!(result.issues.is_empty(), "{:?}", result.issues);
+        assert_eq!(result.amounts[&entity(11)], dec!(5));
+        assert_eq!(result.amounts[&entity(12)], dec!(5));
+        assert!(!result.amounts.contains_key(&entity(10)));
+    }
+
+    #[test]
+    fn contradicting_amounts_are_reported() {
+        let (graph, rules, flows) = smelter(Some(dec!(10)), Some(dec!(4)));
+
+        let result = propagate_amounts(&graph, &rules, &flows);
+
+        assert_eq!(result.issues.len(), 1);
+        assert_eq!(result.issues[0].system, entity(2));
+    }
+
+    #[test]
+    fn missing_amounts_are_reported() {
+        let (graph, rules, flows) = smelter(None, None);
+
+        let result = propagate_amounts(&graph, &rules, &flows);
+
+        assert!(result.amounts.is_empty());
+        assert_eq!(result.issues.len(), 1);
+    }
+
+    #[test]
+    fn too_large_amounts_are_reported_instead_of_overflowing() {
+        let (graph, mut rules, flows) = smelter(Some(Decimal::MAX), None);
+        rules.get_mut(&entity(2)).unwrap().inputs[0].amount = dec!(0.5);
+
+        let result = propagate_amounts(&graph, &rules, &flows);
+
+        assert!(result.amounts.is_empty());
+        assert!(!result.issues.is_empty());
+    }
+
+    #[test]
+    fn too_large_computed_amounts_are_reported_instead_of_overflowing() {
+        let (graph, mut rules, flows) = smelter(Some(Decimal::MAX), None);
+        rules.get_mut(&entity(2)).unwrap().outputs[0].amount = dec!(4);
+
+        let result = propagate_amounts(&graph, &rules, &flows);
+
+        assert!(!result.amounts.contains_key(&entity(11)));
+        assert_eq!(result.amounts[&entity(12)], round(Decimal::MAX / dec!(2)));
+        assert!(!result.issues.is_empty());
+    }
+}
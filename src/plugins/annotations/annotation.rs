@@ -0,0 +1,265 @@
+//! The annotation component and spawning of its visual parts.
+use super::{annotation_text_color, ArrowShape};
+use crate::components::{NestingLevel, SystemElement};
+use crate::events::AnnotationDrag;
+use crate::plugins::mouse_interaction::{DragPosition, PickParent, PickSelection};
+use crate::plugins::preferences::Preferences;
+use crate::plugins::theme::{srgb, Theme, Themes};
+use crate::resources::{FocusedSystem, Zoom};
+use bevy::math::vec2;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy::text::{BreakLineOn, Text2dBounds};
+use bevy_mod_picking::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Reflect)]
+pub enum AnnotationKind {
+    /// Sticky note with free text.
+    Note,
+    /// Arrow that points at its anchor with an optional caption at its tail.
+    Arrow,
+    /// Translucent rectangle with a title that groups the elements below it.
+    GroupBox,
+}
+
+impl AnnotationKind {
+    pub const ALL: [AnnotationKind; 3] = [
+        AnnotationKind::Note,
+        AnnotationKind::Arrow,
+        AnnotationKind::GroupBox,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AnnotationKind::Note => "Note",
+            AnnotationKind::Arrow => "Arrow",
+            AnnotationKind::GroupBox => "Group Box",
+        }
+    }
+
+    fn default_text(self) -> &'static str {
+        match self {
+            AnnotationKind::Note => "Note",
+            AnnotationKind::Arrow => "",
+            AnnotationKind::GroupBox => "Group",
+        }
+    }
+
+    /// Where a new annotation is placed relative to its anchor at zoom level 1.
+    fn default_anchor_offset(self) -> Vec2 {
+        match self {
+            AnnotationKind::Note => vec2(140.0, 110.0),
+            AnnotationKind::Arrow => vec2(120.0, 120.0),
+            AnnotationKind::GroupBox => Vec2::ZERO,
+        }
+    }
+
+    fn default_extent(self) -> Vec2 {
+        match self {
+            AnnotationKind::Note => vec2(160.0, 100.0),
+            AnnotationKind::Arrow => vec2(-100.0, -100.0),
+            AnnotationKind::GroupBox => vec2(320.0, 220.0),
+        }
+    }
+}
+
+/// Note, arrow or group box on the canvas that isn't part of the model. It's shown at the scale
+/// of the elements of its `NestingLevel`.
+#[derive(Clone, Debug, Component, Reflect, PartialEq)]
+#[reflect(Component)]
+pub struct Annotation {
+    pub kind: AnnotationKind,
+    pub text: String,
+    /// Element that the annotation moves with. Arrows point at it.
+    pub anchor: Option<Entity>,
+    /// Center of notes and group boxes and tail of arrows at zoom level 1. Relative to the anchor
+    /// if there is one.
+    pub position: Vec2,
+    /// Size of notes and group boxes at zoom level 1. For arrows without an anchor this is the
+    /// offset from the tail to the head.
+    pub extent: Vec2,
+}
+
+/// The child entities that make up an annotation.
+#[derive(Copy, Clone, Debug, Component, Reflect, PartialEq)]
+#[reflect(Component)]
+pub struct AnnotationParts {
+    /// Sprite that is picked and dragged: the note itself, the tail of an arrow or the title bar
+    /// of a group box.
+    pub handle: Entity,
+    /// Translucent area of a group box.
+    pub fill: Option<Entity>,
+    pub text: Entity,
+    pub arrow: Option<Entity>,
+}
+
+/// Creates an annotation next to the selected element or in the center of the view.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct CreateAnnotation(pub AnnotationKind);
+
+pub fn spawn_annotation(
+    commands: &mut Commands,
+    annotation: Annotation,
+    nesting_level: u16,
+    is_selected: bool,
+    theme: &Theme,
+    asset_server: &AssetServer,
+) -> Entity {
+    let kind = annotation.kind;
+
+    let font = if kind == AnnotationKind::GroupBox {
+        "fonts/Fira_Sans/FiraSans-Bold.ttf"
+    } else {
+        "fonts/Fira_Sans/FiraSans-Regular.ttf"
+    };
+    let text = commands
+        .spawn((
+            Text2dBundle {
+                text: Text {
+                    sections: vec![TextSection::new(
+                        annotation.text.clone(),
+                        TextStyle {
+                            font: asset_server.load(font),
+                            font_size: 14.0,
+                            color: annotation_text_color(kind, theme),
+                        },
+                    )],
+                    justify: JustifyText::Left,
+                    linebreak_behavior: BreakLineOn::WordBoundary,
+                },
+                text_anchor: if kind == AnnotationKind::Arrow {
+                    Anchor::BottomCenter
+                } else {
+                    Anchor::TopLeft
+                },
+                text_2d_bounds: Text2dBounds::default(),
+                transform: Transform::from_xyz(0.0, 0.0, 0.2),
+                ..default()
+            },
+            Pickable::IGNORE,
+            Name::new("Annotation Text"),
+        ))
+        .id();
+
+    let handle = commands
+        .spawn((
+            SpriteBundle {
+                transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                ..default()
+            },
+            PickableBundle::default(),
+            PickParent,
+            Name::new("Annotation Handle"),
+        ))
+        .id();
+
+    let fill = (kind == AnnotationKind::GroupBox).then(|| {
+        commands
+            .spawn((
+                SpriteBundle::default(),
+                Pickable::IGNORE,
+                Name::new("Annotation Fill"),
+            ))
+            .id()
+    });
+
+    let arrow = (kind == AnnotationKind::Arrow).then(|| {
+        commands
+            .spawn((
+                ShapeBundle::default(),
+                Stroke::new(srgb(theme.stroke), 1.0),
+                ArrowShape::default(),
+                Pickable::IGNORE,
+                Name::new("Annotation Arrow"),
+            ))
+            .id()
+    });
+
+    let mut children = vec![handle, text];
+    children.extend(fill);
+    children.extend(arrow);
+
+    commands
+        .spawn((
+            SpatialBundle::default(),
+            Name::new(format!("Annotation ({})", kind.label())),
+            annotation,
+            AnnotationParts {
+                handle,
+                fill,
+                text,
+                arrow,
+            },
+            NestingLevel::new(nesting_level),
+            PickSelection { is_selected },
+            On::<DragPosition>::send_event::<AnnotationDrag>(),
+        ))
+        .push_children(&children)
+        .id()
+}
+
+pub fn create_annotation(
+    mut commands: Commands,
+    mut create_annotation_reader: EventReader<CreateAnnotation>,
+    mut pick_selection_query: Query<(Entity, &mut PickSelection)>,
+    element_query: Query<(), With<SystemElement>>,
+    nesting_query: Query<&NestingLevel>,
+    camera_query: Query<&Transform, With<Camera>>,
+    focused_system: Res<FocusedSystem>,
+    zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
+    themes: Res<Themes>,
+    asset_server: Res<AssetServer>,
+) {
+    for CreateAnnotation(kind) in create_annotation_reader.read() {
+        let kind = *kind;
+
+        let selected = pick_selection_query
+            .iter()
+            .filter(|(entity, selection)| selection.is_selected && element_query.contains(*entity))
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        let anchor = match selected.as_slice() {
+            [anchor] => Some(*anchor),
+            _ => None,
+        };
+
+        let nesting_level =
+            NestingLevel::current(anchor.unwrap_or(**focused_system), &nesting_query);
+        // Size of the elements of this nesting level at zoom level 1.
+        let level_scale = preferences.nesting_scale(nesting_level, 1.0);
+
+        let position = match anchor {
+            Some(_) => kind.default_anchor_offset() * level_scale,
+            None => camera_query
+                .get_single()
+                .map_or(Vec2::ZERO, |camera| camera.translation.truncate() / **zoom),
+        };
+        let extent = match kind {
+            AnnotationKind::Note => kind.default_extent(),
+            _ => kind.default_extent() * level_scale,
+        };
+
+        for (_, mut selection) in &mut pick_selection_query {
+            if selection.is_selected {
+                selection.is_selected = false;
+            }
+        }
+
+        spawn_annotation(
+            &mut commands,
+            Annotation {
+                kind,
+                text: kind.default_text().to_string(),
+                anchor,
+                position,
+                extent,
+            },
+            nesting_level,
+            true,
+            &themes.current(),
+            &asset_server,
+        );
+    }
+}
@@ -0,0 +1,324 @@
+//! Keeps the visual parts of annotations in sync with their anchor, the zoom and the selection.
+use super::{Annotation, AnnotationKind, AnnotationParts};
+use crate::components::NestingLevel;
+use crate::constants::{LABEL_Z, SUBSYSTEM_Z};
+use crate::events::AnnotationDrag;
+use crate::plugins::mouse_interaction::PickSelection;
+use crate::plugins::preferences::Preferences;
+use crate::plugins::theme::{srgb, Theme, Themes};
+use crate::resources::Zoom;
+use bevy::math::{vec2, vec3};
+use bevy::prelude::*;
+use bevy::text::Text2dBounds;
+use bevy_prototype_lyon::prelude::*;
+
+/// Notes and arrows are drawn above the labels.
+const ANNOTATION_Z: f32 = LABEL_Z + 5.0;
+/// Group boxes are drawn above the fill of the system they're in but below its elements.
+const GROUP_BOX_Z: f32 = 0.5;
+const TEXT_PADDING: f32 = 8.0;
+const GROUP_BOX_TITLE_HEIGHT: f32 = 26.0;
+const ARROW_TAIL_SIZE: f32 = 12.0;
+const ARROW_LINE_WIDTH: f32 = 2.0;
+const ARROW_HEAD_LENGTH: f32 = 14.0;
+/// Distance between the head of an arrow and the center of its anchor.
+const ARROW_GAP: f32 = 24.0;
+const ARROW_CAPTION_WIDTH: f32 = 180.0;
+
+const NOTE_COLOR: Color = Color::rgb(1.0, 0.93, 0.55);
+const NOTE_SELECTED_COLOR: Color = Color::rgb(1.0, 0.84, 0.3);
+const NOTE_TEXT_COLOR: Color = Color::BLACK;
+const GROUP_BOX_COLOR: Color = Color::rgba(0.35, 0.55, 0.95, 0.12);
+const GROUP_BOX_TITLE_COLOR: Color = Color::rgba(0.35, 0.55, 0.95, 0.35);
+const GROUP_BOX_SELECTED_TITLE_COLOR: Color = Color::rgba(0.35, 0.55, 0.95, 0.6);
+const ARROW_SELECTED_COLOR: Color = Color::rgb(0.1, 0.4, 0.9);
+
+/// The head and scale the arrow path was last built with, so it's only rebuilt when they change.
+#[derive(Copy, Clone, Debug, Component, Default, PartialEq)]
+pub struct ArrowShape {
+    head: Vec2,
+    scale: f32,
+}
+
+/// Notes keep dark text on their own background. Arrow captions and group box titles are on the
+/// canvas and use the outline color of the theme like the arrows.
+pub fn annotation_text_color(kind: AnnotationKind, theme: &Theme) -> Color {
+    match kind {
+        AnnotationKind::Note => NOTE_TEXT_COLOR,
+        AnnotationKind::Arrow | AnnotationKind::GroupBox => srgb(theme.stroke),
+    }
+}
+
+fn anchor_position(
+    annotation: &Annotation,
+    anchor_query: &Query<&GlobalTransform, Without<Annotation>>,
+) -> Option<Vec2> {
+    let anchor = anchor_query.get(annotation.anchor?).ok()?;
+    Some(anchor.translation().truncate())
+}
+
+fn arrow_path(head: Vec2, scale: f32) -> Path {
+    let wing = -head.normalize_or_zero() * ARROW_HEAD_LENGTH * scale;
+
+    let mut path_builder = PathBuilder::new();
+    path_builder.move_to(Vec2::ZERO);
+    path_builder.line_to(head);
+    path_builder.move_to(head + Vec2::from_angle(0.4).rotate(wing));
+    path_builder.line_to(head);
+    path_builder.line_to(head + Vec2::from_angle(-0.4).rotate(wing));
+    path_builder.build()
+}
+
+pub fn update_annotation_geometry(
+    mut annotation_query: Query<(
+        &Annotation,
+        &AnnotationParts,
+        &NestingLevel,
+        &PickSelection,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+    anchor_query: Query<&GlobalTransform, Without<Annotation>>,
+    mut sprite_query: Query<(&mut Sprite, &mut Transform), Without<Annotation>>,
+    mut text_query: Query<
+        (&mut Transform, &mut Text2dBounds, &mut Text),
+        (Without<Annotation>, Without<Sprite>),
+    >,
+    mut arrow_query: Query<(&mut Path, &mut Stroke, &mut ArrowShape)>,
+    zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
+    themes: Res<Themes>,
+) {
+    let zoom = **zoom;
+    let theme = themes.current();
+
+    for (annotation, parts, nesting_level, selection, mut transform, mut visibility) in
+        &mut annotation_query
+    {
+        let scale = preferences.nesting_scale(**nesting_level, zoom);
+        let threshold = match annotation.kind {
+            AnnotationKind::GroupBox => preferences.scale_visibility_threshold,
+            _ => preferences.label_scale_visibility_threshold,
+        };
+        let new_visibility = if scale > threshold {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != new_visibility {
+            *visibility = new_visibility;
+        }
+
+        let anchor_position = anchor_position(annotation, &anchor_query);
+        let position = anchor_position.unwrap_or_default() + annotation.position * zoom;
+        let z = match annotation.kind {
+            AnnotationKind::GroupBox => **nesting_level as f32 * SUBSYSTEM_Z + GROUP_BOX_Z,
+            _ => ANNOTATION_Z,
+        };
+        transform.translation = position.extend(z);
+
+        let Ok((mut text_transform, mut text_bounds, mut text)) = text_query.get_mut(parts.text)
+        else {
+            continue;
+        };
+        text_transform.scale = vec3(scale, scale, 1.0);
+
+        let text_color = annotation_text_color(annotation.kind, &theme);
+        if text.sections[0].style.color != text_color {
+            text.sections[0].style.color = text_color;
+        }
+
+        let mut set_sprite = |entity: Option<Entity>, size: Vec2, center: Vec2, color: Color| {
+            if let Some(Ok((mut sprite, mut sprite_transform))) =
+                entity.map(|entity| sprite_query.get_mut(entity))
+            {
+                sprite.custom_size = Some(size);
+                sprite.color = color;
+                sprite_transform.translation = center.extend(sprite_transform.translation.z);
+            }
+        };
+
+        match annotation.kind {
+            AnnotationKind::Note => {
+                let size = annotation.extent * scale;
+                let color = if selection.is_selected {
+                    NOTE_SELECTED_COLOR
+                } else {
+                    NOTE_COLOR
+                };
+                set_sprite(Some(parts.handle), size, Vec2::ZERO, color);
+
+                let padding = TEXT_PADDING * scale;
+                text_transform.translation = vec3(
+                    -size.x / 2.0 + padding,
+                    size.y / 2.0 - padding,
+                    text_transform.translation.z,
+                );
+                text_bounds.size = (annotation.extent - 2.0 * TEXT_PADDING).max(Vec2::ZERO);
+            }
+            AnnotationKind::GroupBox => {
+                let size = annotation.extent * zoom;
+                set_sprite(parts.fill, size, Vec2::ZERO, GROUP_BOX_COLOR);
+
+                let title_height = (GROUP_BOX_TITLE_HEIGHT * scale).min(size.y);
+                let color = if selection.is_selected {
+                    GROUP_BOX_SELECTED_TITLE_COLOR
+                } else {
+                    GROUP_BOX_TITLE_COLOR
+                };
+                set_sprite(
+                    Some(parts.handle),
+                    vec2(size.x, title_height),
+                    vec2(0.0, (size.y - title_height) / 2.0),
+                    color,
+                );
+
+                let padding = TEXT_PADDING * scale;
+                text_transform.translation = vec3(
+                    -size.x / 2.0 + padding,
+                    size.y / 2.0 - padding / 2.0,
+                    text_transform.translation.z,
+                );
+                text_bounds.size = vec2(
+                    (size.x / scale - 2.0 * TEXT_PADDING).max(0.0),
+                    GROUP_BOX_TITLE_HEIGHT,
+                );
+            }
+            AnnotationKind::Arrow => {
+                let color = if selection.is_selected {
+                    ARROW_SELECTED_COLOR
+                } else {
+                    srgb(theme.stroke)
+                };
+                set_sprite(
+                    Some(parts.handle),
+                    Vec2::splat(ARROW_TAIL_SIZE * scale),
+                    Vec2::ZERO,
+                    color,
+                );
+
+                let head = match anchor_position {
+                    Some(anchor_position) => {
+                        let to_anchor = anchor_position - position;
+                        to_anchor
+                            - to_anchor.normalize_or_zero()
+                                * (ARROW_GAP * scale).min(to_anchor.length())
+                    }
+                    None => annotation.extent * zoom,
+                };
+
+                if let Some(Ok((mut path, mut stroke, mut arrow_shape))) =
+                    parts.arrow.map(|entity| arrow_query.get_mut(entity))
+                {
+                    let new_shape = ArrowShape { head, scale };
+                    if *arrow_shape != new_shape {
+                        *path = arrow_path(head, scale);
+                        *arrow_shape = new_shape;
+                    }
+                    let line_width = ARROW_LINE_WIDTH * scale;
+                    if stroke.color != color || stroke.options.line_width != line_width {
+                        *stroke = Stroke::new(color, line_width);
+                    }
+                }
+
+                text_transform.translation =
+                    vec3(0.0, ARROW_TAIL_SIZE * scale, text_transform.translation.z);
+                text_bounds.size = vec2(ARROW_CAPTION_WIDTH, f32::INFINITY);
+            }
+        }
+    }
+}
+
+pub fn update_annotation_text(
+    annotation_query: Query<(&Annotation, &AnnotationParts), Changed<Annotation>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (annotation, parts) in &annotation_query {
+        if let Ok(mut text) = text_query.get_mut(parts.text) {
+            if text.sections[0].value != annotation.text {
+                text.sections[0].value = annotation.text.clone();
+            }
+        }
+    }
+}
+
+pub fn drag_annotation(
+    mut events: EventReader<AnnotationDrag>,
+    mut annotation_query: Query<(&mut Annotation, &GlobalTransform)>,
+    anchor_query: Query<&GlobalTransform, Without<Annotation>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut grab_offset: Local<Option<(Entity, Vec2)>>,
+    zoom: Res<Zoom>,
+) {
+    if mouse_input.just_pressed(MouseButton::Left) {
+        *grab_offset = None;
+    }
+
+    for event in events.read() {
+        if event.has_bubbled() {
+            continue;
+        }
+
+        let Ok((mut annotation, transform)) = annotation_query.get_mut(event.target) else {
+            continue;
+        };
+
+        // Keep the point where the annotation was grabbed under the cursor.
+        let offset = match *grab_offset {
+            Some((entity, offset)) if entity == event.target => offset,
+            _ => {
+                let offset = transform.translation().truncate() - event.position;
+                *grab_offset = Some((event.target, offset));
+                offset
+            }
+        };
+
+        let anchor_position = anchor_position(&annotation, &anchor_query).unwrap_or_default();
+        annotation.position = (event.position + offset - anchor_position) / **zoom;
+    }
+}
+
+/// Keeps annotations where they are when their anchor is removed.
+pub fn detach_annotations_from_removed_anchors(
+    mut annotation_query: Query<(&mut Annotation, &GlobalTransform)>,
+    entity_query: Query<Entity>,
+    zoom: Res<Zoom>,
+) {
+    for (mut annotation, transform) in &mut annotation_query {
+        let Some(anchor) = annotation.anchor else {
+            continue;
+        };
+        if entity_query.contains(anchor) {
+            continue;
+        }
+
+        if annotation.kind == AnnotationKind::Arrow {
+            annotation.extent = -annotation.position;
+        }
+        annotation.position = transform.translation().truncate() / **zoom;
+        annotation.anchor = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captions_follow_the_theme_and_notes_keep_their_text_color() {
+        for theme in [Theme::light(), Theme::dark()] {
+            assert_eq!(
+                annotation_text_color(AnnotationKind::Arrow, &theme),
+                srgb(theme.stroke)
+            );
+            assert_eq!(
+                annotation_text_color(AnnotationKind::GroupBox, &theme),
+                srgb(theme.stroke)
+            );
+            assert_eq!(
+                annotation_text_color(AnnotationKind::Note, &theme),
+                NOTE_TEXT_COLOR
+            );
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Notes, arrows and group boxes to mark up the diagram. They're saved with the model but aren't
+//! part of it.
+mod annotation;
+mod geometry;
+mod window;
+
+pub use annotation::*;
+pub use geometry::*;
+pub use window::*;
+
+use crate::events::AnnotationDrag;
+use bevy::prelude::*;
+
+pub struct AnnotationsPlugin;
+
+impl Plugin for AnnotationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CreateAnnotation>()
+            .add_event::<AnnotationDrag>()
+            .register_type::<Annotation>()
+            .register_type::<AnnotationParts>()
+            .add_systems(
+                Update,
+                (
+                    create_annotation,
+                    drag_annotation,
+                    detach_annotations_from_removed_anchors,
+                    annotation_egui.after(bevy_egui::EguiSet::InitContexts),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    update_annotation_text,
+                    update_annotation_geometry
+                        .before(bevy::transform::TransformSystem::TransformPropagate),
+                ),
+            );
+    }
+}
@@ -0,0 +1,120 @@
+//! Window to edit the selected annotation.
+use super::{Annotation, AnnotationKind};
+use crate::plugins::mouse_interaction::PickSelection;
+use crate::resources::Zoom;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+fn size_row(ui: &mut egui::Ui, label: &str, value: &mut f32) {
+    ui.label(label);
+    ui.add(
+        egui::DragValue::new(value)
+            .speed(1.0)
+            .clamp_range(20.0..=5000.0),
+    );
+    ui.end_row();
+}
+
+pub fn annotation_egui(
+    mut commands: Commands,
+    mut egui_contexts: EguiContexts,
+    mut annotation_query: Query<(Entity, &mut Annotation, &PickSelection)>,
+    anchor_query: Query<&GlobalTransform, Without<Annotation>>,
+    name_query: Query<&Name>,
+    zoom: Res<Zoom>,
+) {
+    let selected = annotation_query
+        .iter()
+        .filter(|(_, _, selection)| selection.is_selected)
+        .map(|(entity, ..)| entity)
+        .collect::<Vec<_>>();
+    let [entity] = selected[..] else {
+        return;
+    };
+    let Ok((_, mut annotation, _)) = annotation_query.get_mut(entity) else {
+        return;
+    };
+
+    // Only mutate the component on actual changes so the text is only laid out again when needed.
+    let mut edited = annotation.clone();
+    let mut remove = false;
+
+    egui::Window::new(annotation.kind.label())
+        .id(egui::Id::new("Annotation"))
+        .default_width(260.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label(match edited.kind {
+                AnnotationKind::Note => "Text",
+                AnnotationKind::Arrow => "Caption",
+                AnnotationKind::GroupBox => "Title",
+            });
+            ui.add(
+                egui::TextEdit::multiline(&mut edited.text)
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY),
+            );
+
+            egui::Grid::new("Annotation Geometry")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    match edited.kind {
+                        AnnotationKind::Note | AnnotationKind::GroupBox => {
+                            size_row(ui, "Width", &mut edited.extent.x);
+                            size_row(ui, "Height", &mut edited.extent.y);
+                        }
+                        AnnotationKind::Arrow if edited.anchor.is_none() => {
+                            ui.label("Direction");
+                            ui.horizontal(|ui| {
+                                ui.add(egui::DragValue::new(&mut edited.extent.x).prefix("x: "));
+                                ui.add(egui::DragValue::new(&mut edited.extent.y).prefix("y: "));
+                            });
+                            ui.end_row();
+                        }
+                        AnnotationKind::Arrow => {}
+                    }
+
+                    ui.label("Anchor");
+                    match edited.anchor {
+                        Some(anchor) => {
+                            ui.horizontal(|ui| {
+                                let name = name_query
+                                    .get(anchor)
+                                    .map(|name| name.as_str())
+                                    .unwrap_or_default();
+                                ui.label(if name.is_empty() { "Unnamed" } else { name });
+                                if ui
+                                    .button("Detach")
+                                    .on_hover_text("Stop moving with the element")
+                                    .clicked()
+                                {
+                                    let anchor_position = anchor_query
+                                        .get(anchor)
+                                        .map(|transform| transform.translation().truncate())
+                                        .unwrap_or_default();
+                                    if edited.kind == AnnotationKind::Arrow {
+                                        edited.extent = -edited.position;
+                                    }
+                                    edited.position += anchor_position / **zoom;
+                                    edited.anchor = None;
+                                }
+                            });
+                        }
+                        None => {
+                            ui.weak("None");
+                        }
+                    }
+                    ui.end_row();
+                });
+
+            ui.separator();
+            if ui.button("Delete").clicked() {
+                remove = true;
+            }
+        });
+
+    if remove {
+        commands.entity(entity).despawn_recursive();
+    } else if edited != *annotation {
+        *annotation = edited;
+    }
+}
@@ -0,0 +1,76 @@
+//! Filters elements by the values of their attributes.
+use crate::components::{Parameter, ParameterValue};
+use rust_decimal::Decimal;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum FilterOperator {
+    #[default]
+    Contains,
+    Equals,
+    NotEquals,
+    Less,
+    Greater,
+}
+
+impl FilterOperator {
+    pub const ALL: [FilterOperator; 5] = [
+        FilterOperator::Contains,
+        FilterOperator::Equals,
+        FilterOperator::NotEquals,
+        FilterOperator::Less,
+        FilterOperator::Greater,
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            FilterOperator::Contains => "contains",
+            FilterOperator::Equals => "=",
+            FilterOperator::NotEquals => "≠",
+            FilterOperator::Less => "<",
+            FilterOperator::Greater => ">",
+        }
+    }
+}
+
+/// Condition on the value of one attribute.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct AttributeFilter {
+    pub attribute: String,
+    pub operator: FilterOperator,
+    pub value: String,
+}
+
+impl AttributeFilter {
+    /// Numbers are compared numerically, everything else as case-insensitive text.
+    /// Dates compare correctly as text because they are in the ISO format.
+    pub fn matches(&self, attributes: &[Parameter]) -> bool {
+        let Some(attribute) = attributes
+            .iter()
+            .find(|a| a.name.trim() == self.attribute.trim())
+        else {
+            return self.operator == FilterOperator::NotEquals;
+        };
+
+        let ordering = match (&attribute.value, Decimal::from_str(self.value.trim())) {
+            (ParameterValue::Number { value, .. }, Ok(filter_value)) => value.cmp(&filter_value),
+            (value, _) => value
+                .to_string()
+                .to_lowercase()
+                .cmp(&self.value.trim().to_lowercase()),
+        };
+
+        match self.operator {
+            FilterOperator::Contains => attribute
+                .value
+                .to_string()
+                .to_lowercase()
+                .contains(&self.value.trim().to_lowercase()),
+            FilterOperator::Equals => ordering == Ordering::Equal,
+            FilterOperator::NotEquals => ordering != Ordering::Equal,
+            FilterOperator::Less => ordering == Ordering::Less,
+            FilterOperator::Greater => ordering == Ordering::Greater,
+        }
+    }
+}
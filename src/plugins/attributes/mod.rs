@@ -0,0 +1,19 @@
+//! Table of the attributes of all elements that can be filtered and exported.
+mod filter;
+mod table;
+
+pub use filter::*;
+pub use table::*;
+
+use bevy::prelude::*;
+
+pub struct AttributesPlugin;
+
+impl Plugin for AttributesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AttributeTable>().add_systems(
+            Update,
+            attribute_table_egui.after(bevy_egui::EguiSet::InitContexts),
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Window that lists the attributes of all elements, filters them and exports them to CSV.
+use super::{AttributeFilter, FilterOperator};
+use crate::components::*;
+use crate::plugins::mouse_interaction::{do_deselect_all, PickSelection};
+use crate::resources::{AttributeElementType, AttributeSchemas};
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy_egui::egui::ComboBox;
+use bevy_egui::{egui, EguiContexts};
+use rfd::FileDialog;
+use std::path::PathBuf;
+
+/// One element with its attributes. For interactions these are the flow parameters.
+#[derive(Clone, Debug)]
+pub struct AttributeRow {
+    pub entity: Entity,
+    pub name: String,
+    pub element: SystemElement,
+    pub attributes: Vec<Parameter>,
+}
+
+/// State of the attribute table window.
+#[derive(Resource, Default)]
+pub struct AttributeTable {
+    pub open: bool,
+    /// Only show elements of this type. Shows all elements if `None`.
+    pub element: Option<SystemElement>,
+    /// All filters have to match for an element to be shown.
+    pub filters: Vec<AttributeFilter>,
+    pub export_task: Option<Task<Option<PathBuf>>>,
+    pub export_status: Option<String>,
+}
+
+impl AttributeTable {
+    pub fn matches(&self, row: &AttributeRow) -> bool {
+        self.element.is_none_or(|element| element == row.element)
+            && self
+                .filters
+                .iter()
+                .all(|filter| filter.matches(&row.attributes))
+    }
+}
+
+/// Collects the attributes of all elements. Attributes that an element hasn't set have the
+/// default value of the schema.
+pub fn collect_attribute_rows(
+    element_query: &Query<(Entity, &Name, &SystemElement)>,
+    system_query: &Query<&crate::components::System>,
+    interface_query: &Query<&Interface>,
+    external_entity_query: &Query<&ExternalEntity>,
+    flow_query: &Query<&Flow>,
+    attribute_schemas: &AttributeSchemas,
+) -> Vec<AttributeRow> {
+    let mut rows = element_query
+        .iter()
+        .filter_map(|(entity, name, element)| {
+            let attributes = match element {
+                SystemElement::System => attribute_schemas.resolve(
+                    AttributeElementType::System,
+                    &system_query.get(entity).ok()?.attributes,
+                ),
+                SystemElement::Interface => attribute_schemas.resolve(
+                    AttributeElementType::Interface,
+                    &interface_query.get(entity).ok()?.attributes,
+                ),
+                SystemElement::ExternalEntity => attribute_schemas.resolve(
+                    AttributeElementType::ExternalEntity,
+                    &external_entity_query.get(entity).ok()?.attributes,
+                ),
+                SystemElement::Interaction => flow_query.get(entity).ok()?.parameters.clone(),
+            };
+
+            Some(AttributeRow {
+                entity,
+                name: name.to_string(),
+                element: *element,
+                attributes,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    rows.sort_by(|a, b| a.name.cmp(&b.name));
+    rows
+}
+
+/// Names of all attributes of the rows in the order they first appear.
+fn attribute_columns(rows: &[AttributeRow]) -> Vec<String> {
+    let mut columns = Vec::<String>::new();
+    for attribute in rows.iter().flat_map(|row| &row.attributes) {
+        let name = attribute.name.trim();
+        if !name.is_empty() && !columns.iter().any(|c| c == name) {
+            columns.push(name.to_string());
+        }
+    }
+    columns
+}
+
+fn attribute_value(row: &AttributeRow, column: &str) -> String {
+    row.attributes
+        .iter()
+        .find(|a| a.name.trim() == column)
+        .map(|a| a.value.to_string())
+        .unwrap_or_default()
+}
+
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+fn rows_to_csv(rows: &[AttributeRow]) -> String {
+    let columns = attribute_columns(rows);
+
+    let mut csv = ["element", "type"]
+        .into_iter()
+        .map(csv_field)
+        .chain(columns.iter().map(|c| csv_field(c)))
+        .collect::<Vec<_>>()
+        .join(",");
+    csv.push('\n');
+
+    for row in rows {
+        let line = [row.name.clone(), row.element.to_string()]
+            .into_iter()
+            .chain(columns.iter().map(|c| attribute_value(row, c)))
+            .map(|value| csv_field(&value))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push_str(&line);
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Shows the attribute table window with its filters and exports the filtered rows to CSV.
+pub fn attribute_table_egui(
+    mut egui_contexts: EguiContexts,
+    mut attribute_table: ResMut<AttributeTable>,
+    element_query: Query<(Entity, &Name, &SystemElement)>,
+    system_query: Query<&crate::components::System>,
+    interface_query: Query<&Interface>,
+    external_entity_query: Query<&ExternalEntity>,
+    flow_query: Query<&Flow>,
+    attribute_schemas: Res<AttributeSchemas>,
+    mut pick_selection_query: Query<&mut PickSelection>,
+) {
+    let export_finished = attribute_table
+        .export_task
+        .as_mut()
+        .and_then(|task| future::block_on(future::poll_once(task)));
+
+    if !attribute_table.open && export_finished.is_none() {
+        return;
+    }
+
+    let rows = collect_attribute_rows(
+        &element_query,
+        &system_query,
+        &interface_query,
+        &external_entity_query,
+        &flow_query,
+        &attribute_schemas,
+    )
+    .into_iter()
+    .filter(|row| attribute_table.matches(row))
+    .collect::<Vec<_>>();
+
+    if let Some(path) = export_finished {
+        attribute_table.export_task = None;
+
+        if let Some(path) = path {
+            let status = match std::fs::write(&path, rows_to_csv(&rows)) {
+                Ok(()) => format!("Exported to {}", path.display()),
+                Err(error) => format!("Export failed: {}", error),
+            };
+            attribute_table.export_status = Some(status);
+        }
+    }
+
+    if !attribute_table.open {
+        return;
+    }
+
+    let columns = attribute_columns(&rows);
+
+    let mut open = attribute_table.open;
+    let mut export = false;
+    let mut selected = None;
+
+    egui::Window::new("Attributes")
+        .open(&mut open)
+        .default_width(500.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            let table = &mut *attribute_table;
+
+            ui.horizontal(|ui| {
+                ui.label("Element Type");
+                ComboBox::from_id_source("Attribute Table Element")
+                    .selected_text(table.element.map_or("All".to_string(), |e| e.to_string()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut table.element, None, "All");
+                        for element in [
+                            SystemElement::System,
+                            SystemElement::Interface,
+                            SystemElement::Interaction,
+                            SystemElement::ExternalEntity,
+                        ] {
+                            ui.selectable_value(
+                                &mut table.element,
+                                Some(element),
+                                element.to_string(),
+                            );
+                        }
+                    });
+            });
+
+            let mut removed = None;
+            for (idx, filter) in table.filters.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut filter.attribute)
+                            .hint_text("Attribute...")
+                            .desired_width(100.0),
+                    );
+                    ComboBox::from_id_source(("Attribute Filter Operator", idx))
+                        .selected_text(filter.operator.symbol())
+                        .show_ui(ui, |ui| {
+                            for operator in FilterOperator::ALL {
+                                ui.selectable_value(
+                                    &mut filter.operator,
+                                    operator,
+                                    operator.symbol(),
+                                );
+                            }
+                        });
+                    ui.add(
+                        egui::TextEdit::singleline(&mut filter.value)
+                            .hint_text("Value...")
+                            .desired_width(100.0),
+                    );
+                    if ui.button("Delete").clicked() {
+                        removed = Some(idx);
+                    }
+                });
+            }
+            if let Some(idx) = removed {
+                table.filters.remove(idx);
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Add Filter").clicked() {
+                    table.filters.push(AttributeFilter::default());
+                }
+                export = ui
+                    .add_enabled(
+                        table.export_task.is_none(),
+                        egui::Button::new("Export CSV..."),
+                    )
+                    .clicked();
+            });
+
+            if let Some(status) = &table.export_status {
+                ui.label(status);
+            }
+
+            ui.separator();
+
+            egui::ScrollArea::both().max_height(400.0).show(ui, |ui| {
+                egui::Grid::new("Attribute Table")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Element");
+                        ui.strong("Type");
+                        for column in &columns {
+                            ui.strong(column);
+                        }
+                        ui.end_row();
+
+                        for row in &rows {
+                            if ui.link(&row.name).clicked() {
+                                selected = Some(row.entity);
+                            }
+                            ui.label(row.element.to_string());
+                            for column in &columns {
+                                ui.label(attribute_value(row, column));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+    attribute_table.open = open;
+
+    if let Some(entity) = selected {
+        do_deselect_all(&mut pick_selection_query);
+        if let Ok(mut pick_selection) = pick_selection_query.get_mut(entity) {
+            pick_selection.is_selected = true;
+        }
+    }
+
+    if export {
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            FileDialog::new()
+                .add_filter("csv", &["csv"])
+                .set_file_name("attributes.csv")
+                .save_file()
+        });
+        attribute_table.export_task = Some(task);
+        attribute_table.export_status = None;
+    }
+}
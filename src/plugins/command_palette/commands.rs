@@ -0,0 +1,302 @@
+//! The actions that can be run from the command palette.
+use crate::components::*;
+use crate::events::CreateButtonClick;
+use crate::plugins::analysis::{
+    AmountPropagation, BottleneckAnalysis, DisruptionAnalysis, MonteCarloAnalysis,
+};
+use crate::plugins::annotations::{AnnotationKind, CreateAnnotation};
+use crate::plugins::attributes::AttributeTable;
+use crate::plugins::file_dialog::{open_file_dialog, ExportFile, ImportFile};
+use crate::plugins::keybindings::{KeyAction, Keybindings, KeybindingsWindow};
+use crate::plugins::layout::AutoLayout;
+use crate::plugins::navigation::{BookmarkList, FitView, Minimap, SearchPanel};
+use crate::plugins::outline::OutlinePanel;
+use crate::plugins::preferences::PreferencesWindow;
+use crate::plugins::protocols::ProblemsList;
+use crate::plugins::scenarios::ScenarioEditor;
+use crate::plugins::theme::ThemeEditor;
+use crate::resources::{
+    AttributeSchemaEditor, FocusedSystem, ParameterSchemaEditor, ProtocolEditor,
+    SubstanceCatalogEditor,
+};
+use crate::systems::remove_selected_elements;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+
+/// What happens when a command is run.
+#[derive(Copy, Clone, Debug)]
+pub enum PaletteAction {
+    /// Does the same as clicking the create button.
+    ClickCreateButton(Entity),
+    Run(fn(&mut World)),
+}
+
+impl PaletteAction {
+    pub fn run(self, world: &mut World) {
+        match self {
+            PaletteAction::ClickCreateButton(button) => click_create_button(world, button),
+            PaletteAction::Run(run) => run(world),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PaletteCommand {
+    pub label: String,
+    pub shortcut: Option<String>,
+    pub action: PaletteAction,
+}
+
+impl PaletteCommand {
+    fn new(label: impl Into<String>, shortcut: Option<String>, run: fn(&mut World)) -> Self {
+        Self {
+            label: label.into(),
+            shortcut,
+            action: PaletteAction::Run(run),
+        }
+    }
+}
+
+/// What the commands that don't depend on a create button need to know about the model.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PaletteContext {
+    /// Something other than the root system is selected.
+    pub can_delete: bool,
+    /// The focused system is a subsystem.
+    pub can_focus_parent: bool,
+}
+
+/// Runs the click handler of the create button, which spawns the element.
+fn click_create_button(world: &mut World, button: Entity) {
+    if world.get::<CreateButton>(button).is_some() {
+        world.send_event(CreateButtonClick(button));
+    }
+}
+
+fn create_button_label(button: &CreateButton, source_name: Option<&Name>) -> String {
+    let substance = button
+        .substance_type
+        .map(|substance_type| format!(" ({substance_type:?})"))
+        .unwrap_or_default();
+
+    let action = match button.ty {
+        CreateButtonType::ImportInterface => format!("Add Import Interface{substance}"),
+        CreateButtonType::ExportInterface => format!("Add Export Interface{substance}"),
+        CreateButtonType::Inflow => "Add Inflow".to_string(),
+        CreateButtonType::Outflow => "Add Outflow".to_string(),
+        CreateButtonType::Source => format!("Add Source{substance}"),
+        CreateButtonType::Sink => format!("Add Sink{substance}"),
+        CreateButtonType::InterfaceSubsystem { .. } => "Add Interface Subsystem".to_string(),
+        CreateButtonType::FlowTerminalStart => "Connect Flow Start".to_string(),
+        CreateButtonType::FlowTerminalEnd => "Connect Flow End".to_string(),
+        CreateButtonType::Subsystem => "Add Subsystem".to_string(),
+    };
+
+    match source_name {
+        Some(name) if !name.is_empty() => format!("{action} at {name}"),
+        _ => action,
+    }
+}
+
+/// Commands for all create buttons that are currently shown on the canvas.
+pub fn create_button_commands(
+    button_query: &Query<(Entity, &CreateButton)>,
+    name_query: &Query<&Name>,
+) -> Vec<PaletteCommand> {
+    let mut commands = button_query
+        .iter()
+        .map(|(entity, button)| PaletteCommand {
+            label: create_button_label(button, name_query.get(button.connection_source).ok()),
+            shortcut: None,
+            action: PaletteAction::ClickCreateButton(entity),
+        })
+        .collect::<Vec<_>>();
+    commands.sort_by(|a, b| a.label.cmp(&b.label));
+    commands
+}
+
+/// Commands that don't depend on the create buttons.
+pub fn general_commands(context: PaletteContext, keybindings: &Keybindings) -> Vec<PaletteCommand> {
+    let shortcut = |action| Some(keybindings.get(action).label());
+    let mut commands = vec![];
+
+    if context.can_focus_parent {
+        commands.push(PaletteCommand::new("Focus Parent System", None, |world| {
+            let focused_system = **world.resource::<FocusedSystem>();
+            if let Some(subsystem) = world.get::<Subsystem>(focused_system) {
+                let parent_system = subsystem.parent_system;
+                **world.resource_mut::<FocusedSystem>() = parent_system;
+            }
+        }));
+    }
+    if context.can_delete {
+        commands.push(PaletteCommand::new(
+            "Delete Selection",
+            Some("Delete".to_string()),
+            |world| world.run_system_once(remove_selected_elements),
+        ));
+    }
+
+    commands.extend([
+        PaletteCommand::new("Open Model...", shortcut(KeyAction::OpenModel), |world| {
+            world.run_system_once(open_file_dialog::<ImportFile>)
+        }),
+        PaletteCommand::new("Save Model...", shortcut(KeyAction::SaveModel), |world| {
+            world.run_system_once(open_file_dialog::<ExportFile>)
+        }),
+        PaletteCommand::new("Fit Model", shortcut(KeyAction::FitModel), |world| {
+            world.send_event(FitView::Model);
+        }),
+        PaletteCommand::new(
+            "Fit Selection",
+            shortcut(KeyAction::FitSelection),
+            |world| {
+                world.send_event(FitView::Selection);
+            },
+        ),
+        PaletteCommand::new(
+            "Fit Focused System",
+            shortcut(KeyAction::FitFocusedSystem),
+            |world| {
+                world.send_event(FitView::FocusedSystem);
+            },
+        ),
+        PaletteCommand::new("Auto Layout Focused System", None, |world| {
+            world.send_event(AutoLayout);
+        }),
+        PaletteCommand::new("Add Note", None, |world| {
+            world.send_event(CreateAnnotation(AnnotationKind::Note));
+        }),
+        PaletteCommand::new("Add Arrow", None, |world| {
+            world.send_event(CreateAnnotation(AnnotationKind::Arrow));
+        }),
+        PaletteCommand::new("Add Group Box", None, |world| {
+            world.send_event(CreateAnnotation(AnnotationKind::GroupBox));
+        }),
+        PaletteCommand::new("Search...", shortcut(KeyAction::Search), |world| {
+            world.resource_mut::<SearchPanel>().open = true;
+        }),
+        PaletteCommand::new("Toggle Outline", None, |world| {
+            let mut outline_panel = world.resource_mut::<OutlinePanel>();
+            outline_panel.open = !outline_panel.open;
+        }),
+        PaletteCommand::new("Toggle Minimap", None, |world| {
+            let mut minimap = world.resource_mut::<Minimap>();
+            minimap.open = !minimap.open;
+        }),
+        PaletteCommand::new("Camera Bookmarks...", None, |world| {
+            world.resource_mut::<BookmarkList>().open = true;
+        }),
+        PaletteCommand::new("Keyboard Shortcuts...", None, |world| {
+            world.resource_mut::<KeybindingsWindow>().open = true;
+        }),
+        PaletteCommand::new("Preferences...", None, |world| {
+            world.resource_mut::<PreferencesWindow>().open = true;
+        }),
+        PaletteCommand::new("Theme Editor...", None, |world| {
+            world.resource_mut::<ThemeEditor>().open = true;
+        }),
+        PaletteCommand::new("Parameter Schemas...", None, |world| {
+            world.resource_mut::<ParameterSchemaEditor>().open = true;
+        }),
+        PaletteCommand::new("Attribute Schemas...", None, |world| {
+            world.resource_mut::<AttributeSchemaEditor>().open = true;
+        }),
+        PaletteCommand::new("Substance Catalog...", None, |world| {
+            world.resource_mut::<SubstanceCatalogEditor>().open = true;
+        }),
+        PaletteCommand::new("Protocols...", None, |world| {
+            world.resource_mut::<ProtocolEditor>().open = true;
+        }),
+        PaletteCommand::new("Attribute Table...", None, |world| {
+            world.resource_mut::<AttributeTable>().open = true;
+        }),
+        PaletteCommand::new("Scenarios...", None, |world| {
+            world.resource_mut::<ScenarioEditor>().open = true;
+        }),
+        PaletteCommand::new("Resilience...", None, |world| {
+            world.resource_mut::<DisruptionAnalysis>().open = true;
+        }),
+        PaletteCommand::new("Bottlenecks...", None, |world| {
+            world.resource_mut::<BottleneckAnalysis>().open = true;
+        }),
+        PaletteCommand::new("Propagate Amounts...", None, |world| {
+            world.resource_mut::<AmountPropagation>().open = true;
+        }),
+        PaletteCommand::new("Problems...", None, |world| {
+            world.resource_mut::<ProblemsList>().open = true;
+        }),
+        PaletteCommand::new("Monte Carlo...", None, |world| {
+            world.resource_mut::<MonteCarloAnalysis>().open = true;
+        }),
+    ]);
+
+    commands
+}
+
+/// Scores how well the label matches the query. The characters of the query have to appear in
+/// the label in order. Consecutive characters and matches at word starts score higher.
+pub fn fuzzy_score(label: &str, query: &str) -> Option<i32> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let label = label.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match = None;
+
+    for c in query.chars().filter(|c| !c.is_whitespace()) {
+        let index = (position..label.len()).find(|&i| label[i] == c)?;
+
+        score += 1;
+        if previous_match == Some(index.wrapping_sub(1)) {
+            score += 3;
+        }
+        if index == 0 || !label[index - 1].is_alphanumeric() {
+            score += 2;
+        }
+
+        previous_match = Some(index);
+        position = index + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_characters_have_to_appear_in_order() {
+        assert!(fuzzy_score("Add Inflow", "ainf").is_some());
+        assert!(fuzzy_score("Add Inflow", "fni").is_none());
+        assert!(fuzzy_score("Add Inflow", "x").is_none());
+    }
+
+    #[test]
+    fn empty_queries_match_everything() {
+        assert_eq!(fuzzy_score("Add Inflow", "  "), Some(0));
+    }
+
+    #[test]
+    fn matching_is_case_insensitive_and_ignores_spaces() {
+        assert_eq!(
+            fuzzy_score("Add Inflow", "ADD IN"),
+            fuzzy_score("Add Inflow", "addin")
+        );
+    }
+
+    #[test]
+    fn consecutive_and_word_start_matches_score_higher() {
+        let consecutive = fuzzy_score("Fit View", "fit").unwrap();
+        let scattered = fuzzy_score("Fix Item", "fit").unwrap();
+        assert!(consecutive > scattered);
+
+        let word_start = fuzzy_score("Open", "o").unwrap();
+        let inside = fuzzy_score("Zoom", "o").unwrap();
+        assert!(word_start > inside);
+    }
+}
@@ -0,0 +1,25 @@
+//! Command palette that lists and runs the actions available in the current context.
+mod commands;
+mod palette;
+
+pub use commands::*;
+pub use palette::*;
+
+use crate::plugins::keybindings::{key_action_just_pressed, KeyAction};
+use bevy::prelude::*;
+
+pub struct CommandPalettePlugin;
+
+impl Plugin for CommandPalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandPalette>().add_systems(
+            Update,
+            (
+                toggle_command_palette.run_if(key_action_just_pressed(KeyAction::CommandPalette)),
+                command_palette_egui
+                    .after(toggle_command_palette)
+                    .after(bevy_egui::EguiSet::InitContexts),
+            ),
+        );
+    }
+}
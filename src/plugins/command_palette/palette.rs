@@ -0,0 +1,159 @@
+//! Window that lists the commands that are available in the current context and runs the chosen
+//! one.
+use super::{create_button_commands, fuzzy_score, general_commands, PaletteContext};
+use crate::components::*;
+use crate::plugins::keybindings::Keybindings;
+use crate::plugins::mouse_interaction::PickSelection;
+use crate::resources::FocusedSystem;
+use crate::states::AppState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+/// Maximum number of commands that are listed at once.
+const MAX_VISIBLE_COMMANDS: usize = 12;
+
+/// State of the command palette.
+#[derive(Resource, Default)]
+pub struct CommandPalette {
+    pub open: bool,
+    pub query: String,
+    /// Index of the highlighted command in the filtered list.
+    highlighted: usize,
+    /// Moves the keyboard focus to the query field in the next frame.
+    request_focus: bool,
+}
+
+/// Toggles the command palette and focuses its query field.
+pub fn toggle_command_palette(mut command_palette: ResMut<CommandPalette>) {
+    command_palette.open = !command_palette.open;
+    command_palette.query.clear();
+    command_palette.highlighted = 0;
+    command_palette.request_focus = command_palette.open;
+}
+
+pub fn command_palette_egui(
+    mut commands: Commands,
+    mut egui_contexts: EguiContexts,
+    mut command_palette: ResMut<CommandPalette>,
+    button_query: Query<(Entity, &CreateButton)>,
+    name_query: Query<&Name>,
+    selection_query: Query<
+        (
+            &PickSelection,
+            Has<crate::components::System>,
+            Has<Subsystem>,
+        ),
+        With<SystemElement>,
+    >,
+    subsystem_query: Query<(), With<Subsystem>>,
+    focused_system: Res<FocusedSystem>,
+    app_state: Res<State<AppState>>,
+    keybindings: Res<Keybindings>,
+) {
+    if !command_palette.open {
+        return;
+    }
+
+    let is_normal = *app_state.get() == AppState::Normal;
+    let context = PaletteContext {
+        can_delete: is_normal
+            && selection_query
+                .iter()
+                .any(|(selection, is_system, is_subsystem)| {
+                    // The root system can't be removed.
+                    selection.is_selected && (!is_system || is_subsystem)
+                }),
+        can_focus_parent: subsystem_query.contains(**focused_system),
+    };
+
+    let mut available = if is_normal {
+        create_button_commands(&button_query, &name_query)
+    } else {
+        vec![]
+    };
+    available.extend(general_commands(context, &keybindings));
+
+    let mut matches = available
+        .into_iter()
+        .filter_map(|command| {
+            fuzzy_score(&command.label, &command_palette.query).map(|score| (score, command))
+        })
+        .collect::<Vec<_>>();
+    // Stable sort keeps the context specific commands first among equally good matches.
+    matches.sort_by_key(|(score, _)| -score);
+    matches.truncate(MAX_VISIBLE_COMMANDS);
+
+    let ctx = egui_contexts.ctx_mut();
+    let (up, down, enter, escape) = ctx.input(|i| {
+        (
+            i.key_pressed(egui::Key::ArrowUp),
+            i.key_pressed(egui::Key::ArrowDown),
+            i.key_pressed(egui::Key::Enter),
+            i.key_pressed(egui::Key::Escape),
+        )
+    });
+
+    if down {
+        command_palette.highlighted += 1;
+    }
+    if up {
+        command_palette.highlighted = command_palette.highlighted.saturating_sub(1);
+    }
+    command_palette.highlighted = command_palette
+        .highlighted
+        .min(matches.len().saturating_sub(1));
+
+    let mut chosen = enter
+        .then(|| matches.get(command_palette.highlighted))
+        .flatten()
+        .map(|(_, command)| command.action);
+
+    egui::Window::new("Command Palette")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 80.0))
+        .fixed_size(egui::vec2(420.0, 0.0))
+        .show(ctx, |ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut command_palette.query)
+                    .hint_text("Type a command")
+                    .desired_width(f32::INFINITY),
+            );
+            if command_palette.request_focus {
+                response.request_focus();
+                command_palette.request_focus = false;
+            }
+            if response.changed() {
+                command_palette.highlighted = 0;
+            }
+
+            ui.separator();
+
+            if matches.is_empty() {
+                ui.weak("No matching commands");
+            }
+            for (index, (_, command)) in matches.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let label = ui.selectable_label(
+                        index == command_palette.highlighted,
+                        command.label.as_str(),
+                    );
+                    if label.clicked() {
+                        chosen = Some(command.action);
+                    }
+                    if let Some(shortcut) = &command.shortcut {
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.weak(shortcut);
+                        });
+                    }
+                });
+            }
+        });
+
+    if let Some(action) = chosen {
+        commands.add(move |world: &mut World| action.run(world));
+    }
+    if chosen.is_some() || escape {
+        command_palette.open = false;
+    }
+}
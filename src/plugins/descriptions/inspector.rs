@@ -0,0 +1,193 @@
+//! The description section of the element details: rendered Markdown or the editor, and the
+//! descriptions that link to the element.
+use super::{element_links, markdown_egui};
+use crate::components::ElementDescription;
+use crate::plugins::navigation::JumpToElement;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::egui;
+
+/// Whether descriptions are shown rendered or as editable text. Applies to all elements.
+#[derive(Resource, Default)]
+pub struct DescriptionView {
+    pub editing: bool,
+}
+
+/// What the description section needs besides the element itself.
+#[derive(SystemParam)]
+pub struct DescriptionLinks<'w> {
+    view: ResMut<'w, DescriptionView>,
+    jump_writer: EventWriter<'w, JumpToElement>,
+}
+
+/// Resolves `[[Name]]` links between the elements. It's built once per frame from the names and
+/// descriptions of all elements.
+pub struct LinkIndex<'a> {
+    elements: &'a HashMap<Entity, (String, String)>,
+    by_name: HashMap<&'a str, Entity>,
+    by_lowercase_name: HashMap<String, Entity>,
+}
+
+impl<'a> LinkIndex<'a> {
+    /// `elements` maps all elements to their name and description.
+    pub fn new(elements: &'a HashMap<Entity, (String, String)>) -> Self {
+        let mut by_name = HashMap::<&str, Entity>::new();
+        let mut by_lowercase_name = HashMap::<String, Entity>::new();
+        for (entity, (name, _)) in elements {
+            let entity = *entity;
+            by_name
+                .entry(name)
+                .and_modify(|e| *e = (*e).min(entity))
+                .or_insert(entity);
+            by_lowercase_name
+                .entry(name.to_lowercase())
+                .and_modify(|e| *e = (*e).min(entity))
+                .or_insert(entity);
+        }
+
+        Self {
+            elements,
+            by_name,
+            by_lowercase_name,
+        }
+    }
+
+    /// Finds the element that a `[[Name]]` link refers to. Names don't have to be unique, so
+    /// exact matches win over case-insensitive ones and the lowest `Entity` wins among those, so
+    /// the same element is found every frame.
+    pub fn find(&self, name: &str) -> Option<Entity> {
+        self.by_name
+            .get(name)
+            .or_else(|| self.by_lowercase_name.get(&name.to_lowercase()))
+            .copied()
+    }
+
+    /// The elements whose descriptions link to the entity, sorted by name.
+    fn backlinks(&self, entity: Entity) -> Vec<(Entity, String)> {
+        let mut backlinks = self
+            .elements
+            .iter()
+            .filter(|(other, (_, description))| {
+                **other != entity
+                    && element_links(description)
+                        .iter()
+                        .any(|name| self.find(name) == Some(entity))
+            })
+            .map(|(other, (name, _))| (*other, name.clone()))
+            .collect::<Vec<_>>();
+        backlinks.sort_by(|(_, a), (_, b)| a.cmp(b));
+        backlinks
+    }
+}
+
+pub fn description_egui(
+    ui: &mut egui::Ui,
+    entity: Entity,
+    description: &mut Mut<ElementDescription>,
+    link_index: &LinkIndex,
+    links: &mut DescriptionLinks,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Description");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let mut editing = links.view.editing;
+            ui.selectable_value(&mut editing, true, "Edit");
+            ui.selectable_value(&mut editing, false, "View");
+            if editing != links.view.editing {
+                links.view.editing = editing;
+            }
+        });
+    });
+
+    let mut jump_target = None;
+
+    if links.view.editing {
+        ui.vertical_centered_justified(|ui| {
+            ui.text_edit_multiline(&mut description.text);
+        });
+        ui.weak("Markdown. Link to other elements with [[Name]].");
+    } else if description.text.trim().is_empty() {
+        ui.weak("No description");
+    } else if let Some(name) = markdown_egui(ui, &description.text, &|name| {
+        link_index.find(name).is_some()
+    }) {
+        jump_target = link_index.find(&name);
+    }
+
+    let backlinks = link_index.backlinks(entity);
+    egui::CollapsingHeader::new(format!("Linked From ({})", backlinks.len()))
+        .id_source("Backlinks")
+        .show(ui, |ui| {
+            if backlinks.is_empty() {
+                ui.weak("No description links to this element.");
+            }
+            for (other, name) in backlinks {
+                let label = if name.is_empty() { "Unnamed" } else { &name };
+                if ui.link(label).clicked() {
+                    jump_target = Some(other);
+                }
+            }
+        });
+
+    if let Some(target) = jump_target {
+        links.jump_writer.send(JumpToElement(target));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn elements(entries: &[(u32, &str, &str)]) -> HashMap<Entity, (String, String)> {
+        entries
+            .iter()
+            .map(|(index, name, description)| {
+                (
+                    Entity::from_raw(*index),
+                    (name.to_string(), description.to_string()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exact_names_win_over_case_insensitive_ones() {
+        let elements = elements(&[(1, "pump", ""), (2, "Pump", ""), (3, "Tank", "")]);
+        let index = LinkIndex::new(&elements);
+
+        assert_eq!(index.find("Pump"), Some(Entity::from_raw(2)));
+        assert_eq!(index.find("tank"), Some(Entity::from_raw(3)));
+        assert_eq!(index.find("Valve"), None);
+    }
+
+    #[test]
+    fn duplicate_names_resolve_to_the_lowest_entity() {
+        let elements = elements(&[(5, "Pump", ""), (4, "Pump", ""), (6, "Pump", "")]);
+
+        assert_eq!(
+            LinkIndex::new(&elements).find("Pump"),
+            Some(Entity::from_raw(4))
+        );
+    }
+
+    #[test]
+    fn backlinks_are_sorted_by_name_and_skip_the_element_itself() {
+        let elements = elements(&[
+            (1, "Pump", "Feeds [[Tank]]. See [[pump]]."),
+            (2, "Tank", ""),
+            (3, "Valve", "Before the [[tank]]."),
+            (4, "Auger", "Unrelated to [[Pipe]]."),
+        ]);
+        let index = LinkIndex::new(&elements);
+
+        assert_eq!(
+            index.backlinks(Entity::from_raw(2)),
+            vec![
+                (Entity::from_raw(1), "Pump".to_string()),
+                (Entity::from_raw(3), "Valve".to_string()),
+            ]
+        );
+        assert!(index.backlinks(Entity::from_raw(1)).is_empty());
+    }
+}
@@ -0,0 +1,512 @@
+//! A small Markdown subset for element descriptions: headings, lists, quotes, code, emphasis,
+//! URLs and `[[Name]]` links to other elements.
+use bevy_egui::egui;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Span {
+    Text {
+        text: String,
+        strong: bool,
+        italics: bool,
+        code: bool,
+    },
+    Element(String),
+    Url {
+        label: String,
+        url: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Block {
+    Heading(usize, Vec<Span>),
+    Paragraph(Vec<Span>),
+    Bullet(Vec<Span>),
+    Numbered(String, Vec<Span>),
+    Quote(Vec<Span>),
+    Code(String),
+    Rule,
+}
+
+/// Names of all elements that are linked with `[[Name]]` in the text.
+pub fn element_links(text: &str) -> Vec<String> {
+    let mut names = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else {
+            break;
+        };
+        let name = rest[..end].trim();
+        if !name.is_empty() && !name.contains('\n') {
+            names.push(name.to_string());
+        }
+        rest = &rest[end + 2..];
+    }
+    names
+}
+
+fn is_url_start(text: &str) -> bool {
+    text.starts_with("https://") || text.starts_with("http://")
+}
+
+/// Whether the emphasis marker at the start of `rest` is followed by text and a closing marker.
+fn is_closed_emphasis(rest: &str, marker: &str) -> bool {
+    let inner = &rest[marker.len()..];
+    if inner.starts_with(char::is_whitespace) {
+        return false;
+    }
+    if marker == "**" {
+        return inner.contains("**");
+    }
+
+    let mut inner = inner;
+    while let Some(start) = inner.find('*') {
+        if inner[start..].starts_with("**") {
+            inner = &inner[start + 2..];
+        } else {
+            return true;
+        }
+    }
+    false
+}
+
+fn parse_spans(text: &str) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut current = String::new();
+    let mut strong = false;
+    let mut italics = false;
+
+    let push_text = |spans: &mut Vec<Span>, current: &mut String, strong, italics| {
+        if !current.is_empty() {
+            spans.push(Span::Text {
+                text: std::mem::take(current),
+                strong,
+                italics,
+                code: false,
+            });
+        }
+    };
+
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+
+        if let Some(inner) = rest.strip_prefix("[[") {
+            if let Some(end) = inner.find("]]") {
+                push_text(&mut spans, &mut current, strong, italics);
+                spans.push(Span::Element(inner[..end].trim().to_string()));
+                i += 2 + end + 2;
+                continue;
+            }
+        }
+
+        if let Some(inner) = rest.strip_prefix('[') {
+            if let Some((label, after)) = inner.split_once("](") {
+                if let Some(end) = after.find(')') {
+                    let url = after[..end].trim();
+                    if !label.contains(']') && is_url_start(url) {
+                        push_text(&mut spans, &mut current, strong, italics);
+                        spans.push(Span::Url {
+                            label: label.to_string(),
+                            url: url.to_string(),
+                        });
+                        i += 1 + label.len() + 2 + end + 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        let at_word_start = current.is_empty() || current.ends_with(char::is_whitespace);
+        if at_word_start && is_url_start(rest) {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            // Trailing punctuation usually belongs to the sentence.
+            let url = rest[..end].trim_end_matches(['.', ',', ';', ':', ')', '!', '?']);
+            push_text(&mut spans, &mut current, strong, italics);
+            spans.push(Span::Url {
+                label: url.to_string(),
+                url: url.to_string(),
+            });
+            i += url.len();
+            continue;
+        }
+
+        if let Some(inner) = rest.strip_prefix('`') {
+            if let Some(end) = inner.find('`') {
+                push_text(&mut spans, &mut current, strong, italics);
+                spans.push(Span::Text {
+                    text: inner[..end].to_string(),
+                    strong,
+                    italics,
+                    code: true,
+                });
+                i += 1 + end + 1;
+                continue;
+            }
+        }
+
+        // Markers that don't open or close emphasis are kept as text, e.g. in `2 * 3`.
+        if rest.starts_with("**") {
+            if strong || is_closed_emphasis(rest, "**") {
+                push_text(&mut spans, &mut current, strong, italics);
+                strong = !strong;
+            } else {
+                current.push_str("**");
+            }
+            i += 2;
+            continue;
+        }
+
+        if rest.starts_with('*') {
+            if italics || is_closed_emphasis(rest, "*") {
+                push_text(&mut spans, &mut current, strong, italics);
+                italics = !italics;
+            } else {
+                current.push('*');
+            }
+            i += 1;
+            continue;
+        }
+
+        let c = rest.chars().next().expect("rest isn't empty");
+        current.push(c);
+        i += c.len_utf8();
+    }
+
+    push_text(&mut spans, &mut current, strong, italics);
+    spans
+}
+
+fn numbered_item(line: &str) -> Option<(&str, &str)> {
+    let digits = line.find(|c: char| !c.is_ascii_digit())?;
+    let rest = line[digits..].strip_prefix(". ")?;
+    (digits > 0).then(|| (&line[..digits], rest))
+}
+
+fn parse_blocks(text: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut paragraph = Vec::<&str>::new();
+    let mut code = None::<Vec<&str>>;
+
+    let flush_paragraph = |blocks: &mut Vec<Block>, paragraph: &mut Vec<&str>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(parse_spans(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    };
+
+    for line in text.lines() {
+        if let Some(code_lines) = &mut code {
+            if line.trim_start().starts_with("```") {
+                blocks.push(Block::Code(code_lines.join("\n")));
+                code = None;
+            } else {
+                code_lines.push(line);
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            code = Some(vec![]);
+        } else if trimmed.is_empty() {
+            flush_paragraph(&mut blocks, &mut paragraph);
+        } else if trimmed.starts_with('#') {
+            let level = trimmed.chars().take_while(|c| *c == '#').count();
+            match trimmed[level..].strip_prefix(' ') {
+                Some(heading) if level <= 6 => {
+                    flush_paragraph(&mut blocks, &mut paragraph);
+                    blocks.push(Block::Heading(level, parse_spans(heading)));
+                }
+                _ => paragraph.push(trimmed),
+            }
+        } else if trimmed.len() >= 3
+            && (trimmed.chars().all(|c| c == '-') || trimmed.chars().all(|c| c == '*'))
+        {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            blocks.push(Block::Rule);
+        } else if let Some(item) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|prefix| trimmed.strip_prefix(prefix))
+        {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            blocks.push(Block::Bullet(parse_spans(item)));
+        } else if let Some((number, item)) = numbered_item(trimmed) {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            blocks.push(Block::Numbered(number.to_string(), parse_spans(item)));
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            blocks.push(Block::Quote(parse_spans(quote.trim_start())));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+
+    if let Some(code_lines) = code {
+        blocks.push(Block::Code(code_lines.join("\n")));
+    }
+    flush_paragraph(&mut blocks, &mut paragraph);
+
+    blocks
+}
+
+fn spans_egui(
+    ui: &mut egui::Ui,
+    spans: &[Span],
+    text_style: Option<egui::TextStyle>,
+    weak: bool,
+    element_exists: &dyn Fn(&str) -> bool,
+    clicked: &mut Option<String>,
+) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+
+        for span in spans {
+            match span {
+                Span::Text {
+                    text,
+                    strong,
+                    italics,
+                    code,
+                } => {
+                    let mut rich_text = egui::RichText::new(text);
+                    if let Some(text_style) = &text_style {
+                        rich_text = rich_text.text_style(text_style.clone());
+                    }
+                    if *strong {
+                        rich_text = rich_text.strong();
+                    }
+                    if *italics {
+                        rich_text = rich_text.italics();
+                    }
+                    if *code {
+                        rich_text = rich_text.code();
+                    }
+                    if weak {
+                        rich_text = rich_text.weak();
+                    }
+                    ui.label(rich_text);
+                }
+                Span::Element(name) => {
+                    if element_exists(name) {
+                        if ui.link(name).on_hover_text("Go to this element").clicked() {
+                            *clicked = Some(name.clone());
+                        }
+                    } else {
+                        ui.colored_label(ui.visuals().warn_fg_color, name)
+                            .on_hover_text("No element has this name");
+                    }
+                }
+                Span::Url { label, url } => {
+                    ui.hyperlink_to(label, url).on_hover_text(url);
+                }
+            }
+        }
+    });
+}
+
+/// Shows the Markdown text and returns the name in the `[[Name]]` link that was clicked, if any.
+pub fn markdown_egui(
+    ui: &mut egui::Ui,
+    text: &str,
+    element_exists: &dyn Fn(&str) -> bool,
+) -> Option<String> {
+    let mut clicked = None;
+
+    ui.vertical(|ui| {
+        ui.spacing_mut().item_spacing.y = 4.0;
+
+        for block in parse_blocks(text) {
+            match block {
+                Block::Heading(level, spans) => {
+                    let text_style = if level == 1 {
+                        egui::TextStyle::Heading
+                    } else {
+                        egui::TextStyle::Body
+                    };
+                    let spans = spans
+                        .into_iter()
+                        .map(|span| match span {
+                            Span::Text {
+                                text,
+                                italics,
+                                code,
+                                ..
+                            } => Span::Text {
+                                text,
+                                strong: true,
+                                italics,
+                                code,
+                            },
+                            span => span,
+                        })
+                        .collect::<Vec<_>>();
+                    spans_egui(
+                        ui,
+                        &spans,
+                        Some(text_style),
+                        false,
+                        element_exists,
+                        &mut clicked,
+                    );
+                }
+                Block::Paragraph(spans) => {
+                    spans_egui(ui, &spans, None, false, element_exists, &mut clicked);
+                }
+                Block::Bullet(spans) => {
+                    ui.horizontal(|ui| {
+                        ui.label("•");
+                        spans_egui(ui, &spans, None, false, element_exists, &mut clicked);
+                    });
+                }
+                Block::Numbered(number, spans) => {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{number}."));
+                        spans_egui(ui, &spans, None, false, element_exists, &mut clicked);
+                    });
+                }
+                Block::Quote(spans) => {
+                    ui.horizontal(|ui| {
+                        ui.separator();
+                        spans_egui(ui, &spans, None, true, element_exists, &mut clicked);
+                    });
+                }
+                Block::Code(code) => {
+                    egui::Frame::group(ui.style()).show(ui, |ui| {
+                        ui.set_width(ui.available_width());
+                        ui.label(egui::RichText::new(code).monospace());
+                    });
+                }
+                Block::Rule => {
+                    ui.separator();
+                }
+            }
+        }
+    });
+
+    clicked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str, strong: bool, italics: bool) -> Span {
+        Span::Text {
+            text: text.to_string(),
+            strong,
+            italics,
+            code: false,
+        }
+    }
+
+    fn url(label: &str, url: &str) -> Span {
+        Span::Url {
+            label: label.to_string(),
+            url: url.to_string(),
+        }
+    }
+
+    #[test]
+    fn element_links_are_trimmed_and_skip_empty_and_multi_line_names() {
+        assert_eq!(
+            element_links("See [[ Pump ]] and [[Tank]], not [[]] or [[a\nb]]."),
+            vec!["Pump".to_string(), "Tank".to_string()]
+        );
+        assert!(element_links("[[unclosed").is_empty());
+    }
+
+    #[test]
+    fn emphasis_code_and_element_links() {
+        assert_eq!(
+            parse_spans("a **b** *c* `d` [[E]]"),
+            vec![
+                text("a ", false, false),
+                text("b", true, false),
+                text(" ", false, false),
+                text("c", false, true),
+                text(" ", false, false),
+                Span::Text {
+                    text: "d".to_string(),
+                    strong: false,
+                    italics: false,
+                    code: true,
+                },
+                text(" ", false, false),
+                Span::Element("E".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_emphasis_markers_are_text() {
+        assert_eq!(
+            parse_spans("2 * 3 = 6"),
+            vec![text("2 * 3 = 6", false, false)]
+        );
+        assert_eq!(parse_spans("*open"), vec![text("*open", false, false)]);
+        assert_eq!(parse_spans("**open"), vec![text("**open", false, false)]);
+        assert_eq!(
+            parse_spans("*a **b** c*"),
+            vec![
+                text("a ", false, true),
+                text("b", true, true),
+                text(" c", false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_http_links_are_urls() {
+        assert_eq!(
+            parse_spans("[docs](https://example.com)"),
+            vec![url("docs", "https://example.com")]
+        );
+        assert_eq!(
+            parse_spans("[run](file:///etc/passwd)"),
+            vec![text("[run](file:///etc/passwd)", false, false)]
+        );
+        assert_eq!(
+            parse_spans("see http://example.com."),
+            vec![
+                text("see ", false, false),
+                url("http://example.com", "http://example.com"),
+                text(".", false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks() {
+        let blocks = parse_blocks(
+            "# Title\nfirst\nline\n\n- item\n2. second\n> quote\n---\n```\nlet a = *b*;\n```",
+        );
+
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Heading(1, vec![text("Title", false, false)]),
+                Block::Paragraph(vec![text("first line", false, false)]),
+                Block::Bullet(vec![text("item", false, false)]),
+                Block::Numbered("2".to_string(), vec![text("second", false, false)]),
+                Block::Quote(vec![text("quote", false, false)]),
+                Block::Rule,
+                Block::Code("let a = *b*;".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn hashes_without_a_space_and_unclosed_code_blocks() {
+        assert_eq!(
+            parse_blocks("#tag\n```\ncode"),
+            vec![
+                Block::Paragraph(vec![text("#tag", false, false)]),
+                Block::Code("code".to_string()),
+            ]
+        );
+    }
+}
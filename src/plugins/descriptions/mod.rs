@@ -0,0 +1,16 @@
+//! Element descriptions written in Markdown with links between elements.
+mod inspector;
+mod markdown;
+
+pub use inspector::*;
+pub use markdown::*;
+
+use bevy::prelude::*;
+
+pub struct DescriptionsPlugin;
+
+impl Plugin for DescriptionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DescriptionView>();
+    }
+}
@@ -1,6 +1,6 @@
 mod systems;
 
-use bevy::input::common_conditions::{input_just_pressed, input_pressed};
+use crate::plugins::keybindings::{key_action_just_pressed, KeyAction};
 use std::path::PathBuf;
 pub use systems::*;
 
@@ -18,14 +18,10 @@ impl Plugin for FileDialogPlugin {
                 Update,
                 (
                     (
-                        open_file_dialog::<ImportFile>.run_if(
-                            input_pressed(KeyCode::SuperLeft)
-                                .and_then(input_just_pressed(KeyCode::KeyL)),
-                        ),
-                        open_file_dialog::<ExportFile>.run_if(
-                            input_pressed(KeyCode::SuperLeft)
-                                .and_then(input_just_pressed(KeyCode::KeyS)),
-                        ),
+                        open_file_dialog::<ImportFile>
+                            .run_if(key_action_just_pressed(KeyAction::OpenModel)),
+                        open_file_dialog::<ExportFile>
+                            .run_if(key_action_just_pressed(KeyAction::SaveModel)),
                     )
                         .run_if(in_state(FileState::Inactive)),
                     poll_for_selected_file.run_if(not(in_state(FileState::Inactive))),
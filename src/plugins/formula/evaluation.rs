@@ -0,0 +1,271 @@
+//! Evaluates all formulas of the model in dependency order whenever an interaction changes.
+use super::{Expression, Reference};
+use crate::components::*;
+use crate::plugins::analysis::InteractionGraph;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use rust_decimal::Decimal;
+
+/// Number of decimal places of amounts computed by formulas.
+const FORMULA_DECIMAL_PLACES: u32 = 10;
+
+/// The error of every formula that couldn't be evaluated by interaction entity.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct FormulaErrors(HashMap<Entity, String>);
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum VisitState {
+    Visiting,
+    Done,
+}
+
+/// Depth first search that appends the formulas to `order` after their dependencies.
+/// Formulas that are part of a cycle are collected in `cycles`.
+fn visit(
+    entity: Entity,
+    dependencies: &HashMap<Entity, Vec<Entity>>,
+    states: &mut HashMap<Entity, VisitState>,
+    path: &mut Vec<Entity>,
+    order: &mut Vec<Entity>,
+    cycles: &mut Vec<Vec<Entity>>,
+) {
+    match states.get(&entity) {
+        Some(VisitState::Done) => return,
+        Some(VisitState::Visiting) => {
+            let start = path.iter().position(|e| *e == entity).unwrap_or(0);
+            cycles.push(path[start..].to_vec());
+            return;
+        }
+        None => {}
+    }
+
+    states.insert(entity, VisitState::Visiting);
+    path.push(entity);
+
+    for dependency in &dependencies[&entity] {
+        if dependencies.contains_key(dependency) {
+            visit(*dependency, dependencies, states, path, order, cycles);
+        }
+    }
+
+    path.pop();
+    states.insert(entity, VisitState::Done);
+    order.push(entity);
+}
+
+/// Recomputes the amounts of all flows with a formula if any interaction was changed, renamed or
+/// removed. Amounts are only written if they differ so the evaluation doesn't trigger itself.
+/// Editing a flow in the inspector marks it as changed every frame while it's selected, so the
+/// parsed formulas are cached by their text in `parsed`.
+pub fn evaluate_formulas(
+    changed_query: Query<(), (With<Flow>, Or<(Changed<Flow>, Changed<Name>)>)>,
+    mut removed_flows: RemovedComponents<Flow>,
+    mut flow_query: Query<(
+        Entity,
+        &mut Flow,
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>,
+    interface_subsystem_query: Query<&InterfaceSubsystemConnection>,
+    name_query: Query<&Name>,
+    mut formula_errors: ResMut<FormulaErrors>,
+    mut parsed: Local<HashMap<String, Result<Expression, String>>>,
+) {
+    if changed_query.is_empty() && removed_flows.read().count() == 0 {
+        return;
+    }
+
+    let mut lens = flow_query.transmute_lens::<(
+        Entity,
+        &Flow,
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>();
+    let graph = InteractionGraph::from_query(&lens.query(), &interface_subsystem_query);
+
+    let flow_name = |entity: Entity| {
+        name_query
+            .get(entity)
+            .map(|name| name.to_string())
+            .unwrap_or_default()
+    };
+    let matches = |entity: Entity, flow: &Flow, name: &str| {
+        let name = name.trim();
+        flow_name(entity).trim().eq_ignore_ascii_case(name)
+            || flow.substance_sub_type.trim().eq_ignore_ascii_case(name)
+    };
+
+    let mut errors = HashMap::<Entity, String>::new();
+    let mut expressions = HashMap::<Entity, Expression>::new();
+    let mut references = HashMap::<(Entity, Reference), Vec<Entity>>::new();
+    let mut dependencies = HashMap::<Entity, Vec<Entity>>::new();
+
+    let mut still_parsed = HashMap::new();
+
+    for (entity, flow, ..) in &flow_query {
+        let Some(formula) = &flow.formula else {
+            continue;
+        };
+
+        let parse_result = parsed
+            .remove(formula)
+            .or_else(|| still_parsed.get(formula).cloned())
+            .unwrap_or_else(|| formula.parse::<Expression>());
+        still_parsed.insert(formula.clone(), parse_result.clone());
+
+        let expression = match parse_result {
+            Ok(expression) => expression,
+            Err(error) => {
+                errors.insert(entity, error);
+                continue;
+            }
+        };
+
+        let start_node = graph
+            .edges
+            .iter()
+            .find(|edge| edge.flow == entity)
+            .map(|edge| edge.from);
+
+        let mut flow_dependencies = vec![];
+
+        for reference in expression.references() {
+            let matched = match reference {
+                Reference::Flow(name) => flow_query
+                    .iter()
+                    .filter(|(other, ..)| {
+                        flow_name(*other).trim().eq_ignore_ascii_case(name.trim())
+                    })
+                    .map(|(other, ..)| other)
+                    .collect::<Vec<_>>(),
+                Reference::Inflow(name) | Reference::Outflow(name) => graph
+                    .edges
+                    .iter()
+                    .filter(|edge| {
+                        let node = if matches!(reference, Reference::Inflow(_)) {
+                            edge.to
+                        } else {
+                            edge.from
+                        };
+                        Some(node) == start_node
+                            && edge.flow != entity
+                            && flow_query.get(edge.flow).is_ok_and(|(_, other_flow, ..)| {
+                                matches(edge.flow, other_flow, name)
+                            })
+                    })
+                    .map(|edge| edge.flow)
+                    .collect(),
+                Reference::Param(_) => vec![],
+            };
+
+            flow_dependencies.extend(matched.iter().copied());
+            references.insert((entity, reference.clone()), matched);
+        }
+
+        dependencies.insert(entity, flow_dependencies);
+        expressions.insert(entity, expression);
+    }
+
+    *parsed = still_parsed;
+
+    let mut states = HashMap::new();
+    let mut order = vec![];
+    let mut cycles = vec![];
+    let mut formulas = dependencies.keys().copied().collect::<Vec<_>>();
+    formulas.sort();
+
+    for entity in formulas {
+        visit(
+            entity,
+            &dependencies,
+            &mut states,
+            &mut vec![],
+            &mut order,
+            &mut cycles,
+        );
+    }
+
+    for cycle in cycles {
+        let mut names = cycle.iter().map(|e| flow_name(*e)).collect::<Vec<_>>();
+        names.push(flow_name(cycle[0]));
+        let message = format!("Cycle: {}", names.join(" → "));
+        for entity in cycle {
+            errors.insert(entity, message.clone());
+        }
+    }
+
+    let mut amounts = flow_query
+        .iter()
+        .map(|(entity, flow, ..)| (entity, flow.amount))
+        .collect::<HashMap<_, _>>();
+    let mut computed = HashSet::new();
+
+    for entity in order {
+        if errors.contains_key(&entity) {
+            continue;
+        }
+
+        if let Some(failed) = dependencies[&entity]
+            .iter()
+            .find(|dependency| errors.contains_key(*dependency))
+        {
+            let message = format!("Depends on \"{}\" which has an error", flow_name(*failed));
+            errors.insert(entity, message);
+            continue;
+        }
+
+        let (_, flow, ..) = flow_query.get(entity).expect("Flow exists");
+
+        let resolve = |reference: &Reference| -> Result<Decimal, String> {
+            if let Reference::Param(name) = reference {
+                return flow
+                    .parameters
+                    .iter()
+                    .find(|p| p.name.trim() == name.trim())
+                    .and_then(|p| match p.value {
+                        ParameterValue::Number { value, .. } => Some(value),
+                        _ => None,
+                    })
+                    .ok_or_else(|| format!("No number parameter \"{}\"", name));
+            }
+
+            let matched = &references[&(entity, reference.clone())];
+            match (reference, matched.as_slice()) {
+                (_, []) => Err(format!("Nothing matches {}", reference)),
+                (Reference::Flow(_), [_, _, ..]) => {
+                    Err(format!("{} matches several interactions", reference))
+                }
+                _ => matched
+                    .iter()
+                    .try_fold(Decimal::ZERO, |sum, e| sum.checked_add(amounts[e]))
+                    .ok_or_else(|| format!("The total of {} is too large", reference)),
+            }
+        };
+
+        match expressions[&entity].evaluate(&resolve) {
+            Ok(amount) if amount.is_sign_negative() && !amount.is_zero() => {
+                errors.insert(entity, format!("Results in a negative amount ({})", amount));
+            }
+            Ok(amount) => {
+                amounts.insert(entity, amount.round_dp(FORMULA_DECIMAL_PLACES).normalize());
+                computed.insert(entity);
+            }
+            Err(error) => {
+                errors.insert(entity, error);
+            }
+        }
+    }
+
+    for entity in computed {
+        let (_, mut flow, ..) = flow_query.get_mut(entity).expect("Flow exists");
+        if flow.amount != amounts[&entity] {
+            flow.amount = amounts[&entity];
+        }
+    }
+
+    **formula_errors = errors;
+}
@@ -0,0 +1,390 @@
+//! Parser & evaluator of the small expression language used for formula amounts.
+//!
+//! Supported are decimal numbers, `+ - * /`, parentheses and the functions
+//! `flow("Name")`, `inflow("Name")`, `outflow("Name")`, `param("name")`, `min(a, b, ...)` and
+//! `max(a, b, ...)`.
+use rust_decimal::Decimal;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expression {
+    Number(Decimal),
+    Negate(Box<Expression>),
+    Binary(Box<Expression>, BinaryOperator, Box<Expression>),
+    /// `min` or `max` of the arguments.
+    Aggregate(Aggregate, Vec<Expression>),
+    /// A value of the model that is referenced by name.
+    Reference(Reference),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Aggregate {
+    Min,
+    Max,
+}
+
+/// A reference to another value of the model.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Reference {
+    /// Amount of the interaction with the given name.
+    Flow(String),
+    /// Total amount of the interactions with the given name or substance sub type that flow into
+    /// the system this flow starts at.
+    Inflow(String),
+    /// Total amount of the other interactions with the given name or substance sub type that flow
+    /// out of the system this flow starts at.
+    Outflow(String),
+    /// Number parameter of this flow.
+    Param(String),
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reference::Flow(name) => write!(f, "flow(\"{}\")", name),
+            Reference::Inflow(name) => write!(f, "inflow(\"{}\")", name),
+            Reference::Outflow(name) => write!(f, "outflow(\"{}\")", name),
+            Reference::Param(name) => write!(f, "param(\"{}\")", name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Number(Decimal),
+    String(String),
+    Identifier(String),
+    Operator(char),
+    OpenParen,
+    CloseParen,
+    Comma,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                    number.push(c);
+                    chars.next();
+                }
+                let number = Decimal::from_str(&number)
+                    .map_err(|_| format!("\"{}\" is not a valid number", number))?;
+                tokens.push(Token::Number(number));
+            }
+            '"' => {
+                chars.next();
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => string.push(c),
+                        None => return Err("Missing closing quote".to_string()),
+                    }
+                }
+                tokens.push(Token::String(string));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut identifier = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                    identifier.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Identifier(identifier));
+            }
+            '+' | '-' | '*' | '/' => {
+                tokens.push(Token::Operator(c));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::OpenParen);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::CloseParen);
+                chars.next();
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                chars.next();
+            }
+            c => return Err(format!("Unexpected character '{}'", c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser. Grammar:
+/// ```text
+/// expression = term (("+" | "-") term)*
+/// term       = factor (("*" | "/") factor)*
+/// factor     = "-" factor | number | call | "(" expression ")"
+/// call       = identifier "(" arguments ")"
+/// ```
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token, description: &str) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            _ => Err(format!("Expected {}", description)),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, String> {
+        let mut left = self.term()?;
+        while let Some(Token::Operator(c @ ('+' | '-'))) = self.peek().cloned() {
+            self.next();
+            let operator = if c == '+' {
+                BinaryOperator::Add
+            } else {
+                BinaryOperator::Subtract
+            };
+            left = Expression::Binary(Box::new(left), operator, Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expression, String> {
+        let mut left = self.factor()?;
+        while let Some(Token::Operator(c @ ('*' | '/'))) = self.peek().cloned() {
+            self.next();
+            let operator = if c == '*' {
+                BinaryOperator::Multiply
+            } else {
+                BinaryOperator::Divide
+            };
+            left = Expression::Binary(Box::new(left), operator, Box::new(self.factor()?));
+        }
+        Ok(left)
+    }
+
+    fn factor(&mut self) -> Result<Expression, String> {
+        match self.next() {
+            Some(Token::Operator('-')) => Ok(Expression::Negate(Box::new(self.factor()?))),
+            Some(Token::Number(number)) => Ok(Expression::Number(number)),
+            Some(Token::OpenParen) => {
+                let expression = self.expression()?;
+                self.expect(Token::CloseParen, "')'")?;
+                Ok(expression)
+            }
+            Some(Token::Identifier(identifier)) => self.call(&identifier),
+            Some(token) => Err(format!("Unexpected {:?}", token)),
+            None => Err("Unexpected end of formula".to_string()),
+        }
+    }
+
+    fn call(&mut self, function: &str) -> Result<Expression, String> {
+        self.expect(Token::OpenParen, &format!("'(' after {}", function))?;
+
+        if matches!(function, "flow" | "inflow" | "outflow" | "param") {
+            let Some(Token::String(name)) = self.next() else {
+                return Err(format!("{} expects a name in quotes", function));
+            };
+            self.expect(Token::CloseParen, "')'")?;
+
+            let reference = match function {
+                "flow" => Reference::Flow(name),
+                "inflow" => Reference::Inflow(name),
+                "outflow" => Reference::Outflow(name),
+                _ => Reference::Param(name),
+            };
+            return Ok(Expression::Reference(reference));
+        }
+
+        let aggregate = match function {
+            "min" => Aggregate::Min,
+            "max" => Aggregate::Max,
+            _ => return Err(format!("Unknown function \"{}\"", function)),
+        };
+
+        let mut arguments = vec![self.expression()?];
+        while self.peek() == Some(&Token::Comma) {
+            self.next();
+            arguments.push(self.expression()?);
+        }
+        self.expect(Token::CloseParen, "')'")?;
+
+        Ok(Expression::Aggregate(aggregate, arguments))
+    }
+}
+
+impl FromStr for Expression {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        let expression = parser.expression()?;
+
+        if parser.position < parser.tokens.len() {
+            return Err(format!("Unexpected {:?}", parser.tokens[parser.position]));
+        }
+
+        Ok(expression)
+    }
+}
+
+impl Expression {
+    /// All references in the expression. These are the dependencies of the formula.
+    pub fn references(&self) -> Vec<&Reference> {
+        match self {
+            Expression::Number(_) => vec![],
+            Expression::Negate(expression) => expression.references(),
+            Expression::Binary(left, _, right) => {
+                let mut references = left.references();
+                references.extend(right.references());
+                references
+            }
+            Expression::Aggregate(_, arguments) => {
+                arguments.iter().flat_map(|a| a.references()).collect()
+            }
+            Expression::Reference(reference) => vec![reference],
+        }
+    }
+
+    /// Evaluates the expression. `resolve` returns the values of the references.
+    pub fn evaluate(
+        &self,
+        resolve: &impl Fn(&Reference) -> Result<Decimal, String>,
+    ) -> Result<Decimal, String> {
+        match self {
+            Expression::Number(number) => Ok(*number),
+            Expression::Negate(expression) => Ok(-expression.evaluate(resolve)?),
+            Expression::Binary(left, operator, right) => {
+                let left = left.evaluate(resolve)?;
+                let right = right.evaluate(resolve)?;
+                match operator {
+                    BinaryOperator::Add => left.checked_add(right),
+                    BinaryOperator::Subtract => left.checked_sub(right),
+                    BinaryOperator::Multiply => left.checked_mul(right),
+                    BinaryOperator::Divide if right.is_zero() => {
+                        return Err("Division by zero".to_string())
+                    }
+                    BinaryOperator::Divide => left.checked_div(right),
+                }
+                .ok_or_else(|| "The result is too large".to_string())
+            }
+            Expression::Aggregate(aggregate, arguments) => {
+                let values = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(resolve))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let result = match aggregate {
+                    Aggregate::Min => values.into_iter().min(),
+                    Aggregate::Max => values.into_iter().max(),
+                };
+                Ok(result.unwrap_or_default())
+            }
+            Expression::Reference(reference) => resolve(reference),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn evaluate(source: &str) -> Result<Decimal, String> {
+        source
+            .parse::<Expression>()?
+            .evaluate(&|reference| match reference {
+                Reference::Flow(name) if name == "Water" => Ok(dec!(4)),
+                Reference::Param(name) if name == "big" => Ok(Decimal::MAX),
+                _ => Err(format!("Nothing matches {}", reference)),
+            })
+    }
+
+    #[test]
+    fn operators_have_the_usual_precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), Ok(dec!(7)));
+        assert_eq!(evaluate("(1 + 2) * 3"), Ok(dec!(9)));
+        assert_eq!(evaluate("10 - 4 - 3"), Ok(dec!(3)));
+        assert_eq!(evaluate("12 / 3 / 2"), Ok(dec!(2)));
+        assert_eq!(evaluate("-2 * -3"), Ok(dec!(6)));
+        assert_eq!(evaluate("0.5 * 3"), Ok(dec!(1.5)));
+    }
+
+    #[test]
+    fn functions_and_references() {
+        assert_eq!(evaluate("flow(\"Water\") * 2"), Ok(dec!(8)));
+        assert_eq!(evaluate("min(3, flow(\"Water\"), 5)"), Ok(dec!(3)));
+        assert_eq!(evaluate("max(3, flow(\"Water\") + 2)"), Ok(dec!(6)));
+        assert!(evaluate("flow(\"Air\")").is_err());
+    }
+
+    #[test]
+    fn references_are_collected() {
+        let expression = "flow(\"A\") + inflow(\"B\") * max(outflow(\"C\"), param(\"d\"))"
+            .parse::<Expression>()
+            .unwrap();
+
+        assert_eq!(
+            expression.references(),
+            vec![
+                &Reference::Flow("A".to_string()),
+                &Reference::Inflow("B".to_string()),
+                &Reference::Outflow("C".to_string()),
+                &Reference::Param("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_formulas_are_rejected() {
+        for source in [
+            "",
+            "1 +",
+            "(1 + 2",
+            "1 2",
+            "1..2",
+            "foo(1)",
+            "flow(Water)",
+            "flow(\"Water",
+            "min()",
+            "3 % 2",
+        ] {
+            assert!(source.parse::<Expression>().is_err(), "{source:?}");
+        }
+    }
+
+    #[test]
+    fn arithmetic_errors_are_reported() {
+        assert_eq!(evaluate("1 / 0"), Err("Division by zero".to_string()));
+        assert!(evaluate("param(\"big\") * 2").is_err());
+        assert!(evaluate("param(\"big\") + 1").is_err());
+    }
+}
@@ -0,0 +1,17 @@
+//! Flow amounts that are computed from formulas which reference other values of the model.
+mod evaluation;
+mod expression;
+
+pub use evaluation::*;
+pub use expression::*;
+
+use bevy::prelude::*;
+
+pub struct FormulaPlugin;
+
+impl Plugin for FormulaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FormulaErrors>()
+            .add_systems(Update, evaluate_formulas);
+    }
+}
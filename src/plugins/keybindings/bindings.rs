@@ -0,0 +1,549 @@
+//! Actions that can be triggered from the keyboard, their bindings and the shortcuts that are
+//! built in and can't be rebound.
+use crate::utils::{load_user_config, save_user_config};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const KEYBINDINGS_FILE_NAME: &str = "keybindings.json";
+
+/// Keys that can be bound together with the name used in the configuration file.
+const BINDABLE_KEYS: &[(KeyCode, &str)] = &[
+    (KeyCode::KeyA, "A"),
+    (KeyCode::KeyB, "B"),
+    (KeyCode::KeyC, "C"),
+    (KeyCode::KeyD, "D"),
+    (KeyCode::KeyE, "E"),
+    (KeyCode::KeyF, "F"),
+    (KeyCode::KeyG, "G"),
+    (KeyCode::KeyH, "H"),
+    (KeyCode::KeyI, "I"),
+    (KeyCode::KeyJ, "J"),
+    (KeyCode::KeyK, "K"),
+    (KeyCode::KeyL, "L"),
+    (KeyCode::KeyM, "M"),
+    (KeyCode::KeyN, "N"),
+    (KeyCode::KeyO, "O"),
+    (KeyCode::KeyP, "P"),
+    (KeyCode::KeyQ, "Q"),
+    (KeyCode::KeyR, "R"),
+    (KeyCode::KeyS, "S"),
+    (KeyCode::KeyT, "T"),
+    (KeyCode::KeyU, "U"),
+    (KeyCode::KeyV, "V"),
+    (KeyCode::KeyW, "W"),
+    (KeyCode::KeyX, "X"),
+    (KeyCode::KeyY, "Y"),
+    (KeyCode::KeyZ, "Z"),
+    (KeyCode::Digit0, "0"),
+    (KeyCode::Digit1, "1"),
+    (KeyCode::Digit2, "2"),
+    (KeyCode::Digit3, "3"),
+    (KeyCode::Digit4, "4"),
+    (KeyCode::Digit5, "5"),
+    (KeyCode::Digit6, "6"),
+    (KeyCode::Digit7, "7"),
+    (KeyCode::Digit8, "8"),
+    (KeyCode::Digit9, "9"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::ArrowLeft, "Left"),
+    (KeyCode::ArrowRight, "Right"),
+    (KeyCode::ArrowUp, "Up"),
+    (KeyCode::ArrowDown, "Down"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Minus, "Minus"),
+    (KeyCode::Equal, "Equal"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Backslash, "Backslash"),
+    (KeyCode::Semicolon, "Semicolon"),
+    (KeyCode::Quote, "Quote"),
+    (KeyCode::Backquote, "Backquote"),
+    (KeyCode::BracketLeft, "BracketLeft"),
+    (KeyCode::BracketRight, "BracketRight"),
+];
+
+/// Keys of the platform's command modifier: Cmd on macOS and Ctrl everywhere else.
+pub const COMMAND_KEYS: [KeyCode; 2] = if cfg!(target_os = "macos") {
+    [KeyCode::SuperLeft, KeyCode::SuperRight]
+} else {
+    [KeyCode::ControlLeft, KeyCode::ControlRight]
+};
+
+/// Modifier that has to be held to zoom with the mouse wheel.
+pub const WHEEL_ZOOM_LABEL: &str = if cfg!(target_os = "macos") {
+    "Cmd+Wheel"
+} else {
+    "Ctrl+Wheel"
+};
+
+const SHIFT_KEYS: [KeyCode; 2] = [KeyCode::ShiftLeft, KeyCode::ShiftRight];
+const ALT_KEYS: [KeyCode; 2] = [KeyCode::AltLeft, KeyCode::AltRight];
+
+/// A shortcut of the canvas that can't be rebound. Bindings that it would also react to are
+/// reported as conflicts.
+#[derive(Copy, Clone, Debug)]
+pub struct BuiltInShortcut {
+    pub label: &'static str,
+    /// How the keys are shown in the UI.
+    pub keys_label: &'static str,
+    pub keys: &'static [KeyCode],
+    /// Whether the shortcut also reacts while command or alt are held. Shift never matters.
+    pub ignores_modifiers: bool,
+}
+
+impl BuiltInShortcut {
+    pub fn conflicts_with(&self, binding: &KeyBinding) -> bool {
+        self.keys.contains(&binding.key)
+            && (self.ignores_modifiers || !binding.command && !binding.alt)
+    }
+}
+
+/// The shortcuts that are handled by the canvas directly. Zooming with the wheel uses
+/// [`COMMAND_KEYS`] as modifier, which doesn't clash with key bindings.
+pub const BUILT_IN_SHORTCUTS: [BuiltInShortcut; 5] = [
+    BuiltInShortcut {
+        label: "Select Next Sibling",
+        keys_label: "Tab",
+        keys: &[KeyCode::Tab],
+        ignores_modifiers: false,
+    },
+    BuiltInShortcut {
+        label: "Follow Flows",
+        keys_label: "← →",
+        keys: &[KeyCode::ArrowLeft, KeyCode::ArrowRight],
+        ignores_modifiers: false,
+    },
+    BuiltInShortcut {
+        label: "Select Parent or Child",
+        keys_label: "↑ ↓",
+        keys: &[KeyCode::ArrowUp, KeyCode::ArrowDown],
+        ignores_modifiers: false,
+    },
+    BuiltInShortcut {
+        label: "Zoom",
+        keys_label: "- =",
+        keys: &[KeyCode::Minus, KeyCode::Equal],
+        ignores_modifiers: true,
+    },
+    BuiltInShortcut {
+        label: "Delete Selection",
+        keys_label: "Delete",
+        keys: &[KeyCode::Delete, KeyCode::Backspace],
+        ignores_modifiers: true,
+    },
+];
+
+/// Actions that can be triggered with a keyboard shortcut.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum KeyAction {
+    OpenModel,
+    SaveModel,
+    ResetCamera,
+    CommandPalette,
+    Search,
+    FitModel,
+    FitSelection,
+    FitFocusedSystem,
+    NavigateBack,
+    NavigateForward,
+}
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 10] = [
+        KeyAction::OpenModel,
+        KeyAction::SaveModel,
+        KeyAction::ResetCamera,
+        KeyAction::CommandPalette,
+        KeyAction::Search,
+        KeyAction::FitModel,
+        KeyAction::FitSelection,
+        KeyAction::FitFocusedSystem,
+        KeyAction::NavigateBack,
+        KeyAction::NavigateForward,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyAction::OpenModel => "Open Model",
+            KeyAction::SaveModel => "Save Model",
+            KeyAction::ResetCamera => "Reset Camera",
+            KeyAction::CommandPalette => "Command Palette",
+            KeyAction::Search => "Search",
+            KeyAction::FitModel => "Fit Model",
+            KeyAction::FitSelection => "Fit Selection",
+            KeyAction::FitFocusedSystem => "Fit Focused System",
+            KeyAction::NavigateBack => "Back",
+            KeyAction::NavigateForward => "Forward",
+        }
+    }
+
+    pub fn default_binding(self) -> KeyBinding {
+        match self {
+            KeyAction::OpenModel => KeyBinding::command(KeyCode::KeyL),
+            KeyAction::SaveModel => KeyBinding::command(KeyCode::KeyS),
+            KeyAction::ResetCamera => KeyBinding::command(KeyCode::KeyR),
+            KeyAction::CommandPalette => KeyBinding {
+                shift: true,
+                ..KeyBinding::command(KeyCode::KeyP)
+            },
+            KeyAction::Search => KeyBinding::command(KeyCode::KeyF),
+            KeyAction::FitModel => KeyBinding::shift(KeyCode::Digit1),
+            KeyAction::FitSelection => KeyBinding::shift(KeyCode::Digit2),
+            KeyAction::FitFocusedSystem => KeyBinding::shift(KeyCode::Digit3),
+            KeyAction::NavigateBack => KeyBinding::alt(KeyCode::ArrowLeft),
+            KeyAction::NavigateForward => KeyBinding::alt(KeyCode::ArrowRight),
+        }
+    }
+}
+
+/// A key together with the modifiers that have to be held. Stored as text like "Mod+Shift+P"
+/// where "Mod" is the platform's command modifier.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyBinding {
+    pub key: KeyCode,
+    pub command: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    pub fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            command: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub fn command(key: KeyCode) -> Self {
+        Self {
+            command: true,
+            ..Self::new(key)
+        }
+    }
+
+    pub fn shift(key: KeyCode) -> Self {
+        Self {
+            shift: true,
+            ..Self::new(key)
+        }
+    }
+
+    pub fn alt(key: KeyCode) -> Self {
+        Self {
+            alt: true,
+            ..Self::new(key)
+        }
+    }
+
+    /// True if the key was just pressed and exactly the binding's modifiers are held.
+    pub fn just_pressed(&self, input: &ButtonInput<KeyCode>) -> bool {
+        input.just_pressed(self.key)
+            && input.any_pressed(COMMAND_KEYS) == self.command
+            && input.any_pressed(SHIFT_KEYS) == self.shift
+            && input.any_pressed(ALT_KEYS) == self.alt
+    }
+
+    /// The binding of the first bindable key that was just pressed with the held modifiers.
+    pub fn from_input(input: &ButtonInput<KeyCode>) -> Option<Self> {
+        let key = BINDABLE_KEYS
+            .iter()
+            .map(|(key, _)| *key)
+            .find(|key| input.just_pressed(*key))?;
+
+        Some(Self {
+            key,
+            command: input.any_pressed(COMMAND_KEYS),
+            shift: input.any_pressed(SHIFT_KEYS),
+            alt: input.any_pressed(ALT_KEYS),
+        })
+    }
+
+    /// Text shown in the UI, e.g. "Ctrl+Shift+P" or "Cmd+Shift+P".
+    pub fn label(&self) -> String {
+        let mut parts = vec![];
+        if self.command {
+            parts.push(if cfg!(target_os = "macos") {
+                "Cmd"
+            } else {
+                "Ctrl"
+            });
+        }
+        if self.shift {
+            parts.push("Shift");
+        }
+        if self.alt {
+            parts.push(if cfg!(target_os = "macos") {
+                "Option"
+            } else {
+                "Alt"
+            });
+        }
+        let key = match self.key {
+            KeyCode::ArrowLeft => "←",
+            KeyCode::ArrowRight => "→",
+            KeyCode::ArrowUp => "↑",
+            KeyCode::ArrowDown => "↓",
+            key => key_name(key),
+        };
+        parts.push(key);
+        parts.join("+")
+    }
+}
+
+fn key_name(key: KeyCode) -> &'static str {
+    BINDABLE_KEYS
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map_or("?", |(_, name)| name)
+}
+
+impl From<KeyBinding> for String {
+    fn from(binding: KeyBinding) -> Self {
+        let mut parts = vec![];
+        if binding.command {
+            parts.push("Mod");
+        }
+        if binding.shift {
+            parts.push("Shift");
+        }
+        if binding.alt {
+            parts.push("Alt");
+        }
+        parts.push(key_name(binding.key));
+        parts.join("+")
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        let mut parts = text.split('+').map(str::trim).collect::<Vec<_>>();
+        let key_name = parts.pop().ok_or("Empty key binding")?;
+        let key = BINDABLE_KEYS
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(key_name))
+            .map(|(key, _)| *key)
+            .ok_or_else(|| format!("Unknown key \"{key_name}\""))?;
+
+        let mut binding = KeyBinding::new(key);
+        for modifier in parts {
+            match modifier.to_lowercase().as_str() {
+                "mod" | "cmd" | "ctrl" => binding.command = true,
+                "shift" => binding.shift = true,
+                "alt" | "option" => binding.alt = true,
+                _ => return Err(format!("Unknown modifier \"{modifier}\"")),
+            }
+        }
+        Ok(binding)
+    }
+}
+
+/// The keyboard shortcuts of all actions. Stored in the user's configuration directory. Actions
+/// that are missing from the file use their default binding.
+#[derive(Resource, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keybindings {
+    bindings: BTreeMap<KeyAction, KeyBinding>,
+}
+
+/// The keybindings file before its entries are parsed. Each entry is parsed on its own so that
+/// a mistake in one of them doesn't reset the others.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct KeybindingsFile {
+    bindings: BTreeMap<String, String>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            bindings: KeyAction::ALL
+                .into_iter()
+                .map(|action| (action, action.default_binding()))
+                .collect(),
+        }
+    }
+}
+
+impl Keybindings {
+    pub fn load() -> Self {
+        let file: KeybindingsFile = load_user_config(KEYBINDINGS_FILE_NAME);
+        Self::from_entries(file.bindings)
+    }
+
+    /// Parses the entries of the keybindings file. Invalid entries are skipped with a warning
+    /// and their actions keep the default binding.
+    fn from_entries(entries: BTreeMap<String, String>) -> Self {
+        let mut keybindings = Self::default();
+        for (action_name, text) in entries {
+            let action = match serde_json::from_value::<KeyAction>(action_name.clone().into()) {
+                Ok(action) => action,
+                Err(_) => {
+                    warn!(
+                        "Ignoring the shortcut of the unknown action \"{}\"",
+                        action_name
+                    );
+                    continue;
+                }
+            };
+            match KeyBinding::try_from(text) {
+                Ok(binding) => keybindings.set(action, binding),
+                Err(error) => warn!("Ignoring the shortcut of {}: {}", action.label(), error),
+            }
+        }
+        keybindings
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_user_config(KEYBINDINGS_FILE_NAME, self)
+    }
+
+    pub fn get(&self, action: KeyAction) -> KeyBinding {
+        self.bindings
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_binding())
+    }
+
+    pub fn set(&mut self, action: KeyAction, binding: KeyBinding) {
+        self.bindings.insert(action, binding);
+    }
+
+    pub fn just_pressed(&self, action: KeyAction, input: &ButtonInput<KeyCode>) -> bool {
+        self.get(action).just_pressed(input)
+    }
+
+    /// Actions whose binding is also used by other actions or built-in shortcuts together with
+    /// the labels of those.
+    pub fn conflicts(&self) -> HashMap<KeyAction, Vec<&'static str>> {
+        let mut conflicts = HashMap::<KeyAction, Vec<&'static str>>::new();
+        for a in KeyAction::ALL {
+            let binding = self.get(a);
+            for b in KeyAction::ALL {
+                if a != b && binding == self.get(b) {
+                    conflicts.entry(a).or_default().push(b.label());
+                }
+            }
+            for shortcut in &BUILT_IN_SHORTCUTS {
+                if shortcut.conflicts_with(&binding) {
+                    conflicts.entry(a).or_default().push(shortcut.label);
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_round_trip_through_text() {
+        for (key, _) in BINDABLE_KEYS {
+            for modifiers in 0..8 {
+                let binding = KeyBinding {
+                    key: *key,
+                    command: modifiers & 1 != 0,
+                    shift: modifiers & 2 != 0,
+                    alt: modifiers & 4 != 0,
+                };
+                let text = String::from(binding);
+
+                assert_eq!(KeyBinding::try_from(text.clone()), Ok(binding), "{text}");
+            }
+        }
+    }
+
+    #[test]
+    fn parsing_accepts_platform_names() {
+        let expected = KeyBinding {
+            shift: true,
+            alt: true,
+            ..KeyBinding::command(KeyCode::KeyP)
+        };
+
+        assert_eq!(
+            KeyBinding::try_from("ctrl + option + SHIFT + p".to_string()),
+            Ok(expected)
+        );
+        assert_eq!(String::from(expected), "Mod+Shift+Alt+P");
+    }
+
+    #[test]
+    fn invalid_bindings_are_errors() {
+        assert!(KeyBinding::try_from("Mod+Tab".to_string()).is_err());
+        assert!(KeyBinding::try_from("Hyper+P".to_string()).is_err());
+        assert!(KeyBinding::try_from(String::new()).is_err());
+    }
+
+    #[test]
+    fn invalid_entries_keep_their_default() {
+        let entries = [
+            ("SaveModel", "Mod+Shift+S"),
+            ("OpenModel", "Mod+Nonsense"),
+            ("Teleport", "T"),
+        ]
+        .into_iter()
+        .map(|(action, binding)| (action.to_string(), binding.to_string()))
+        .collect();
+        let keybindings = Keybindings::from_entries(entries);
+
+        assert_eq!(
+            keybindings.get(KeyAction::SaveModel),
+            KeyBinding {
+                shift: true,
+                ..KeyBinding::command(KeyCode::KeyS)
+            }
+        );
+        assert_eq!(
+            keybindings.get(KeyAction::OpenModel),
+            KeyAction::OpenModel.default_binding()
+        );
+    }
+
+    #[test]
+    fn defaults_have_no_conflicts() {
+        assert!(Keybindings::default().conflicts().is_empty());
+    }
+
+    #[test]
+    fn conflicts_include_built_in_shortcuts() {
+        let mut keybindings = Keybindings::default();
+        keybindings.set(KeyAction::Search, KeyBinding::new(KeyCode::ArrowUp));
+        keybindings.set(KeyAction::FitModel, KeyBinding::command(KeyCode::Minus));
+        keybindings.set(KeyAction::NavigateBack, KeyBinding::command(KeyCode::KeyS));
+
+        let conflicts = keybindings.conflicts();
+        assert_eq!(
+            conflicts[&KeyAction::Search],
+            vec!["Select Parent or Child"]
+        );
+        assert_eq!(conflicts[&KeyAction::FitModel], vec!["Zoom"]);
+        assert_eq!(conflicts[&KeyAction::NavigateBack], vec!["Save Model"]);
+        assert_eq!(conflicts[&KeyAction::SaveModel], vec!["Back"]);
+    }
+}
@@ -0,0 +1,25 @@
+//! Keyboard shortcuts that are loaded from the user's configuration and can be rebound in the UI.
+mod bindings;
+mod window;
+
+pub use bindings::*;
+pub use window::*;
+
+use bevy::prelude::*;
+
+pub struct KeybindingsPlugin;
+
+impl Plugin for KeybindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Keybindings::load())
+            .init_resource::<KeybindingsWindow>()
+            .add_systems(PostUpdate, capture_keybinding)
+            .add_systems(
+                Update,
+                (
+                    keybindings_egui.after(bevy_egui::EguiSet::InitContexts),
+                    save_keybindings,
+                ),
+            );
+    }
+}
@@ -0,0 +1,132 @@
+//! Window to view and rebind the keyboard shortcuts.
+use super::{KeyAction, KeyBinding, Keybindings, BUILT_IN_SHORTCUTS, WHEEL_ZOOM_LABEL};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+#[derive(Resource, Default)]
+pub struct KeybindingsWindow {
+    pub open: bool,
+    /// The action whose new binding is recorded from the next key press.
+    pub capturing: Option<KeyAction>,
+}
+
+/// Records the next key press for the action that is being rebound. Escape cancels. Runs after
+/// `Update` so the new shortcut doesn't also trigger its action.
+pub fn capture_keybinding(
+    input: Res<ButtonInput<KeyCode>>,
+    mut keybindings_window: ResMut<KeybindingsWindow>,
+    mut keybindings: ResMut<Keybindings>,
+) {
+    let Some(action) = keybindings_window.capturing else {
+        return;
+    };
+
+    if input.just_pressed(KeyCode::Escape) {
+        keybindings_window.capturing = None;
+    } else if let Some(binding) = KeyBinding::from_input(&input) {
+        if keybindings.get(action) != binding {
+            keybindings.set(action, binding);
+        }
+        keybindings_window.capturing = None;
+    }
+}
+
+pub fn keybindings_egui(
+    mut egui_contexts: EguiContexts,
+    mut keybindings_window: ResMut<KeybindingsWindow>,
+    mut keybindings: ResMut<Keybindings>,
+) {
+    if !keybindings_window.open {
+        keybindings_window.capturing = None;
+        return;
+    }
+
+    let conflicts = keybindings.conflicts();
+    let keybindings_window = &mut *keybindings_window;
+    let mut reset = false;
+
+    egui::Window::new("Keyboard Shortcuts")
+        .open(&mut keybindings_window.open)
+        .default_width(360.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Grid::new("Keybindings")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for action in KeyAction::ALL {
+                        ui.label(action.label());
+
+                        let text = if keybindings_window.capturing == Some(action) {
+                            "Press a key…".to_string()
+                        } else {
+                            keybindings.get(action).label()
+                        };
+                        let mut button = ui.button(text);
+                        if let Some(others) = conflicts.get(&action) {
+                            let names = others.join(", ");
+                            button = button.on_hover_text(format!("Also used by {}", names));
+                            ui.colored_label(egui::Color32::RED, "⚠");
+                        }
+                        if button.clicked() {
+                            keybindings_window.capturing = Some(action);
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            if !conflicts.is_empty() {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    "Shortcuts marked with ⚠ are used by more than one action.",
+                );
+            }
+
+            ui.collapsing("Built-in Shortcuts", |ui| {
+                egui::Grid::new("Built-in Shortcuts")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for shortcut in &BUILT_IN_SHORTCUTS {
+                            ui.label(shortcut.label);
+                            ui.label(shortcut.keys_label);
+                            ui.end_row();
+                        }
+                        ui.label("Zoom");
+                        ui.label(WHEEL_ZOOM_LABEL);
+                        ui.end_row();
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("Reset to Defaults").clicked() {
+                    reset = true;
+                }
+            });
+        });
+
+    if reset && *keybindings != Keybindings::default() {
+        *keybindings = Keybindings::default();
+    }
+}
+
+/// Persists the keybindings whenever they are changed in the UI.
+pub fn save_keybindings(keybindings: Res<Keybindings>) {
+    if keybindings.is_changed() && !keybindings.is_added() {
+        if let Err(error) = keybindings.save() {
+            warn!("Failed to save keybindings: {}", error);
+        }
+    }
+}
+
+/// Run condition that is true in the frame the action's shortcut is pressed. Never true while a
+/// shortcut is being rebound.
+pub fn key_action_just_pressed(
+    action: KeyAction,
+) -> impl FnMut(Res<Keybindings>, Res<KeybindingsWindow>, Res<ButtonInput<KeyCode>>) -> bool + Clone
+{
+    move |keybindings: Res<Keybindings>,
+          keybindings_window: Res<KeybindingsWindow>,
+          input: Res<ButtonInput<KeyCode>>| {
+        keybindings_window.capturing.is_none() && keybindings.just_pressed(action, &input)
+    }
+}
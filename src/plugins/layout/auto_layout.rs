@@ -0,0 +1,189 @@
+//! Auto-layout of the focused system: subsystems inside the circle, external entities around.
+use super::{force_directed_layout, EdgeEnd};
+use crate::components::*;
+use crate::constants::EXTERNAL_ENTITY_HEIGHT_HALF;
+use crate::plugins::preferences::Preferences;
+use crate::resources::{FocusedSystem, Zoom};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Lays out the subsystems and external entities of the focused system.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct AutoLayout;
+
+pub fn auto_layout(
+    mut auto_layout_reader: EventReader<AutoLayout>,
+    focused_system: Res<FocusedSystem>,
+    zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
+    system_query: Query<&crate::components::System>,
+    subsystem_query: Query<(Entity, &Subsystem, Option<&InterfaceSubsystem>)>,
+    interface_query: Query<(Entity, &Parent), With<Interface>>,
+    external_entity_query: Query<&NestingLevel, With<ExternalEntity>>,
+    flow_query: Query<(
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>,
+    mut transform_query: Query<(&mut Transform, &mut InitialPosition)>,
+) {
+    if auto_layout_reader.read().last().is_none() {
+        return;
+    }
+
+    let focused = **focused_system;
+    let Ok(focused_component) = system_query.get(focused) else {
+        return;
+    };
+    let parent_radius = focused_component.radius;
+    let zoom = **zoom;
+
+    let position_of = |entity: Entity| {
+        transform_query
+            .get(entity)
+            .map(|(transform, _)| transform.translation.truncate() / zoom)
+            .unwrap_or_default()
+    };
+
+    // Subsystems that the layout moves. Interface subsystems stay attached to their interfaces.
+    let mut movable = subsystem_query
+        .iter()
+        .filter(|(_, subsystem, interface_subsystem)| {
+            subsystem.parent_system == focused && interface_subsystem.is_none()
+        })
+        .map(|(entity, ..)| entity)
+        .collect::<Vec<_>>();
+    movable.sort();
+    let node_index = movable
+        .iter()
+        .enumerate()
+        .map(|(i, entity)| (*entity, i))
+        .collect::<HashMap<_, _>>();
+    let radii = movable
+        .iter()
+        .map(|entity| system_query.get(*entity).map_or(0.0, |s| s.radius))
+        .collect::<Vec<_>>();
+
+    let is_fixed_inside = |entity: Entity| {
+        subsystem_query
+            .get(entity)
+            .is_ok_and(|(_, subsystem, interface_subsystem)| {
+                subsystem.parent_system == focused && interface_subsystem.is_some()
+            })
+    };
+    let edge_end = |entity: Entity| {
+        if let Some(i) = node_index.get(&entity) {
+            Some(EdgeEnd::Node(*i))
+        } else if is_fixed_inside(entity) {
+            Some(EdgeEnd::Anchor(position_of(entity)))
+        } else {
+            None
+        }
+    };
+
+    let mut edges = vec![];
+    for (start, end, ..) in &flow_query {
+        if let (Some(a), Some(b)) = (edge_end(start.target), edge_end(end.target)) {
+            edges.push((a, b));
+        }
+    }
+    // The interfaces of the focused system pull the interface subsystems' neighbors outwards.
+    for (interface, parent) in &interface_query {
+        if parent.get() != focused {
+            continue;
+        }
+        let anchor = position_of(interface);
+        for (entity, _, interface_subsystem) in &subsystem_query {
+            if interface_subsystem.is_some_and(|s| s.interface_entity == interface) {
+                for (start, end, ..) in &flow_query {
+                    if start.target == entity {
+                        if let Some(b) = edge_end(end.target) {
+                            edges.push((EdgeEnd::Anchor(anchor), b));
+                        }
+                    } else if end.target == entity {
+                        if let Some(a) = edge_end(start.target) {
+                            edges.push((a, EdgeEnd::Anchor(anchor)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let positions = force_directed_layout(parent_radius, &radii, &edges);
+
+    // Planned positions of the systems that external entities are placed around. They are in
+    // the frame of the parent of the external entities.
+    let mut system_positions = movable
+        .iter()
+        .copied()
+        .zip(positions.iter().copied())
+        .map(|(entity, position)| (entity, (position, position.normalize_or_zero())))
+        .collect::<HashMap<_, _>>();
+    system_positions.insert(focused, (position_of(focused), Vec2::ZERO));
+
+    // External entities grouped by the system and interface they're connected to.
+    let mut external_entities = HashMap::<(Entity, Option<Entity>), Vec<Entity>>::new();
+    for (start, end, start_interface, end_interface) in &flow_query {
+        if start.target_type == StartTargetType::Source
+            && system_positions.contains_key(&end.target)
+        {
+            external_entities
+                .entry((end.target, end_interface.map(|c| c.target)))
+                .or_default()
+                .push(start.target);
+        }
+        if end.target_type == EndTargetType::Sink && system_positions.contains_key(&start.target) {
+            external_entities
+                .entry((start.target, start_interface.map(|c| c.target)))
+                .or_default()
+                .push(end.target);
+        }
+    }
+
+    let mut placements = vec![];
+    let mut groups = external_entities.into_iter().collect::<Vec<_>>();
+    groups.sort_by_key(|((system, interface), _)| (*system, *interface));
+    for (i, ((system, interface), mut entities)) in groups.into_iter().enumerate() {
+        entities.sort();
+        let (center, outwards) = system_positions[&system];
+        let radius = system_query.get(system).map_or(0.0, |s| s.radius);
+
+        let direction = interface
+            .map(|interface| position_of(interface).normalize_or_zero())
+            .filter(|d| *d != Vec2::ZERO)
+            .or((outwards != Vec2::ZERO).then_some(outwards))
+            .unwrap_or_else(|| Vec2::from_angle(i as f32 * 2.4));
+
+        for (k, entity) in entities.iter().enumerate() {
+            let Ok(nesting_level) = external_entity_query.get(*entity) else {
+                continue;
+            };
+            let scale = preferences
+                .subsystem_scaling_factor
+                .powi(**nesting_level as i32);
+            // External entities sit a flow length away from the system they're connected to.
+            let distance = preferences.flow_length * scale;
+            let spacing = EXTERNAL_ENTITY_HEIGHT_HALF * 2.5 * scale;
+            let offset = (k as f32 - (entities.len() - 1) as f32 * 0.5) * spacing;
+            let position = center + direction * (radius + distance) + direction.perp() * offset;
+            placements.push((*entity, position, direction));
+        }
+    }
+
+    for (entity, position, direction) in placements {
+        if let Ok((mut transform, mut initial_position)) = transform_query.get_mut(entity) {
+            **initial_position = position;
+            transform.translation = (position * zoom).extend(transform.translation.z);
+            transform.rotation = Quat::from_rotation_z(direction.to_angle());
+        }
+    }
+
+    for (entity, position) in movable.into_iter().zip(positions) {
+        if let Ok((mut transform, mut initial_position)) = transform_query.get_mut(entity) {
+            **initial_position = position;
+            transform.translation = (position * zoom).extend(transform.translation.z);
+        }
+    }
+}
@@ -0,0 +1,353 @@
+//! Force-directed placement of circles inside a circle.
+use bevy::prelude::*;
+
+/// Number of simulation steps of the force-directed layout.
+const ITERATIONS: usize = 300;
+
+/// Space that is kept between neighboring subsystems and between subsystems and the boundary
+/// relative to the radius of the parent.
+const GAP: f32 = 0.04;
+
+/// Number of passes that swap nodes to remove crossings after the simulation.
+const UNTANGLE_PASSES: usize = 4;
+
+/// Maximum number of edge pairs that are tested for crossings while untangling. Keeps the layout
+/// of large systems from blocking the app since every tried swap counts all crossings again.
+const UNTANGLE_BUDGET: usize = 2_000_000;
+
+/// One end of an edge of the layout graph.
+#[derive(Copy, Clone, Debug)]
+pub enum EdgeEnd {
+    /// A node that is placed by the layout.
+    Node(usize),
+    /// A fixed point, e.g. an interface on the boundary.
+    Anchor(Vec2),
+}
+
+impl EdgeEnd {
+    fn position(&self, positions: &[Vec2]) -> Vec2 {
+        match self {
+            EdgeEnd::Node(i) => positions[*i],
+            EdgeEnd::Anchor(position) => *position,
+        }
+    }
+}
+
+/// Keeps a node of the given radius inside the parent circle.
+fn clamp_inside(position: Vec2, node_radius: f32, parent_radius: f32) -> Vec2 {
+    let max = (parent_radius * (1.0 - GAP) - node_radius).max(0.0);
+    position.clamp_length_max(max)
+}
+
+/// Initial positions: nodes that are connected to anchors are put at the average direction of
+/// those anchors. The other nodes are put at the average of their placed neighbors or evenly
+/// around the center. This places nodes in the angular order of their interfaces on the
+/// boundary, which avoids most crossings.
+fn seed(parent_radius: f32, radii: &[f32], edges: &[(EdgeEnd, EdgeEnd)]) -> Vec<Vec2> {
+    let n = radii.len();
+    let mut positions = vec![None; n];
+
+    for (i, radius) in radii.iter().enumerate() {
+        let direction = edges
+            .iter()
+            .filter_map(|(a, b)| match (a, b) {
+                (EdgeEnd::Node(j), EdgeEnd::Anchor(p)) | (EdgeEnd::Anchor(p), EdgeEnd::Node(j))
+                    if *j == i =>
+                {
+                    Some(p.normalize_or_zero())
+                }
+                _ => None,
+            })
+            .sum::<Vec2>();
+        if direction != Vec2::ZERO {
+            positions[i] = Some(direction.normalize() * (parent_radius - radius) * 0.6);
+        }
+    }
+
+    // Propagates positions along edges between nodes.
+    for _ in 0..n {
+        let placed = positions.clone();
+        for (i, position) in positions.iter_mut().enumerate() {
+            if position.is_some() {
+                continue;
+            }
+            let neighbors = edges
+                .iter()
+                .filter_map(|(a, b)| match (a, b) {
+                    (EdgeEnd::Node(j), EdgeEnd::Node(k)) if *j == i => placed[*k],
+                    (EdgeEnd::Node(k), EdgeEnd::Node(j)) if *j == i => placed[*k],
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            if !neighbors.is_empty() {
+                *position = Some(neighbors.iter().sum::<Vec2>() / neighbors.len() as f32 * 0.8);
+            }
+        }
+    }
+
+    let unplaced = positions.iter().filter(|p| p.is_none()).count().max(1);
+    let mut k = 0;
+    positions
+        .into_iter()
+        .enumerate()
+        .map(|(i, position)| {
+            position.unwrap_or_else(|| {
+                let angle = std::f32::consts::TAU * k as f32 / unplaced as f32;
+                k += 1;
+                Vec2::from_angle(angle) * (parent_radius - radii[i]) * 0.4
+            })
+        })
+        .collect()
+}
+
+fn segments_cross(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let orientation = |p: Vec2, q: Vec2, r: Vec2| (q - p).perp_dot(r - p);
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    o1 * o2 < 0.0 && o3 * o4 < 0.0
+}
+
+/// Number of pairs of straight edges that cross each other.
+pub fn count_crossings(positions: &[Vec2], edges: &[(EdgeEnd, EdgeEnd)]) -> usize {
+    let segments = edges
+        .iter()
+        .map(|(a, b)| (a.position(positions), b.position(positions)))
+        .collect::<Vec<_>>();
+
+    let mut crossings = 0;
+    for (i, (a, b)) in segments.iter().enumerate() {
+        for (c, d) in &segments[i + 1..] {
+            if segments_cross(*a, *b, *c, *d) {
+                crossings += 1;
+            }
+        }
+    }
+    crossings
+}
+
+/// Places nodes with the given radii inside a circle with `parent_radius` around the origin.
+/// Edges pull their ends together, nodes push each other apart and anchors stay where they are.
+/// Afterwards nodes of similar size are swapped as long as that reduces the number of crossing
+/// edges.
+pub fn force_directed_layout(
+    parent_radius: f32,
+    radii: &[f32],
+    edges: &[(EdgeEnd, EdgeEnd)],
+) -> Vec<Vec2> {
+    let n = radii.len();
+    let mut positions = seed(parent_radius, radii, edges);
+    if n == 0 {
+        return positions;
+    }
+
+    let area = std::f32::consts::PI * parent_radius * parent_radius;
+    let k = (area / n as f32).sqrt() * 0.5;
+    let gap = parent_radius * GAP;
+
+    for iteration in 0..ITERATIONS {
+        let temperature = parent_radius * 0.1 * (1.0 - iteration as f32 / ITERATIONS as f32);
+        let mut forces = vec![Vec2::ZERO; n];
+
+        for i in 0..n {
+            for j in i + 1..n {
+                let mut delta = positions[i] - positions[j];
+                if delta.length_squared() < f32::EPSILON {
+                    // Separates nodes at the same position in a deterministic direction.
+                    delta = Vec2::from_angle(i as f32 + j as f32);
+                }
+                let distance = delta.length();
+                let min_distance = radii[i] + radii[j] + gap;
+                let mut repulsion = k * k / distance;
+                if distance < min_distance {
+                    repulsion += (min_distance - distance) * 10.0;
+                }
+                let force = delta / distance * repulsion;
+                forces[i] += force;
+                forces[j] -= force;
+            }
+        }
+
+        for (a, b) in edges {
+            let delta = b.position(&positions) - a.position(&positions);
+            let distance = delta.length();
+            if distance < f32::EPSILON {
+                continue;
+            }
+            let force = delta / distance * (distance * distance / k);
+            if let EdgeEnd::Node(i) = a {
+                forces[*i] += force;
+            }
+            if let EdgeEnd::Node(j) = b {
+                forces[*j] -= force;
+            }
+        }
+
+        for i in 0..n {
+            let step = forces[i].clamp_length_max(temperature);
+            positions[i] = clamp_inside(positions[i] + step, radii[i], parent_radius);
+        }
+    }
+
+    untangle(&mut positions, radii, edges);
+
+    positions
+}
+
+/// Finds a position for a circle with `radius` inside a circle with `parent_radius` around the
+/// origin that doesn't overlap the `occupied` circles. Positions closer to the center are
+/// preferred. If there's no free space, the position with the most clearance is returned.
+pub fn free_position(parent_radius: f32, radius: f32, occupied: &[(Vec2, f32)]) -> Vec2 {
+    let gap = parent_radius * GAP;
+    let max = (parent_radius * (1.0 - GAP) - radius).max(0.0);
+    let step = radius.max(max / 8.0).max(f32::EPSILON);
+
+    let clearance = |position: Vec2| {
+        occupied
+            .iter()
+            .map(|(center, r)| position.distance(*center) - r - radius - gap)
+            .fold(f32::INFINITY, f32::min)
+    };
+
+    let mut best = (Vec2::ZERO, clearance(Vec2::ZERO));
+    let mut ring = 0.0;
+    while best.1 < 0.0 && ring < max {
+        ring = (ring + step).min(max);
+        let count = (std::f32::consts::TAU * ring / step).ceil().max(1.0) as usize;
+        for k in 0..count {
+            let position = Vec2::from_angle(std::f32::consts::TAU * k as f32 / count as f32) * ring;
+            let candidate = clearance(position);
+            if candidate > best.1 {
+                best = (position, candidate);
+            }
+            if candidate >= 0.0 {
+                break;
+            }
+        }
+    }
+    best.0
+}
+
+/// Swaps pairs of nodes of similar size while that reduces the number of crossing edges and the
+/// [`UNTANGLE_BUDGET`] isn't used up.
+fn untangle(positions: &mut [Vec2], radii: &[f32], edges: &[(EdgeEnd, EdgeEnd)]) {
+    let tests_per_count = (edges.len() * edges.len().saturating_sub(1) / 2).max(1);
+    let mut remaining_counts = UNTANGLE_BUDGET / tests_per_count;
+    let mut crossings = count_crossings(positions, edges);
+
+    for _ in 0..UNTANGLE_PASSES {
+        let mut improved = false;
+        for i in 0..positions.len() {
+            for j in i + 1..positions.len() {
+                if crossings == 0 {
+                    return;
+                }
+                if (radii[i] - radii[j]).abs() > radii[i].min(radii[j]) * 0.5 {
+                    continue;
+                }
+                if remaining_counts == 0 {
+                    return;
+                }
+                remaining_counts -= 1;
+                positions.swap(i, j);
+                let swapped = count_crossings(positions, edges);
+                if swapped < crossings {
+                    crossings = swapped;
+                    improved = true;
+                } else {
+                    positions.swap(i, j);
+                }
+            }
+        }
+        if !improved {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossings_are_counted_per_pair_of_edges() {
+        let positions = [
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(-1.0, 1.0),
+            Vec2::new(1.0, -1.0),
+        ];
+        let crossing = [
+            (EdgeEnd::Node(0), EdgeEnd::Node(1)),
+            (EdgeEnd::Node(2), EdgeEnd::Node(3)),
+        ];
+        let parallel = [
+            (EdgeEnd::Node(0), EdgeEnd::Node(2)),
+            (EdgeEnd::Node(3), EdgeEnd::Node(1)),
+        ];
+
+        assert_eq!(count_crossings(&positions, &crossing), 1);
+        assert_eq!(count_crossings(&positions, &parallel), 0);
+        assert_eq!(count_crossings(&positions, &[crossing[0], parallel[0]]), 0);
+    }
+
+    #[test]
+    fn layout_keeps_nodes_inside_and_apart() {
+        let radii = [20.0, 20.0, 15.0, 10.0];
+        let edges = [
+            (EdgeEnd::Node(0), EdgeEnd::Node(1)),
+            (EdgeEnd::Node(1), EdgeEnd::Node(2)),
+            (EdgeEnd::Anchor(Vec2::new(100.0, 0.0)), EdgeEnd::Node(3)),
+        ];
+        let positions = force_directed_layout(100.0, &radii, &edges);
+
+        assert_eq!(positions.len(), radii.len());
+        for (i, position) in positions.iter().enumerate() {
+            assert!(position.length() + radii[i] <= 100.0 + 1e-3);
+            for j in i + 1..positions.len() {
+                assert!(position.distance(positions[j]) >= radii[i] + radii[j]);
+            }
+        }
+        // Nodes connected to anchors are pulled towards them.
+        assert!(positions[3].x > 0.0);
+    }
+
+    #[test]
+    fn layout_removes_crossings_of_anchored_nodes() {
+        let radii = [10.0; 4];
+        let anchors = [
+            Vec2::new(100.0, 0.0),
+            Vec2::new(0.0, 100.0),
+            Vec2::new(-100.0, 0.0),
+            Vec2::new(0.0, -100.0),
+        ];
+        let edges = anchors
+            .iter()
+            .enumerate()
+            .map(|(i, anchor)| (EdgeEnd::Anchor(*anchor), EdgeEnd::Node(i)))
+            .collect::<Vec<_>>();
+        let positions = force_directed_layout(100.0, &radii, &edges);
+
+        assert_eq!(count_crossings(&positions, &edges), 0);
+    }
+
+    #[test]
+    fn layout_of_nothing_is_empty() {
+        assert!(force_directed_layout(100.0, &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn free_position_prefers_the_center() {
+        assert_eq!(free_position(100.0, 10.0, &[]), Vec2::ZERO);
+    }
+
+    #[test]
+    fn free_position_avoids_occupied_space() {
+        let occupied = [(Vec2::ZERO, 30.0), (Vec2::new(50.0, 0.0), 15.0)];
+        let position = free_position(100.0, 10.0, &occupied);
+
+        for (center, radius) in occupied {
+            assert!(position.distance(center) >= radius + 10.0);
+        }
+        assert!(position.length() <= 100.0 - 10.0);
+    }
+}
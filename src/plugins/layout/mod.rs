@@ -0,0 +1,43 @@
+//! Automatic placement of system elements, routing of flows and snapping while dragging.
+mod auto_layout;
+mod force;
+mod routing;
+mod snap;
+
+pub use auto_layout::*;
+pub use force::*;
+pub use routing::*;
+pub use snap::*;
+
+use bevy::input::common_conditions::input_pressed;
+use bevy::prelude::*;
+
+pub struct LayoutPlugin;
+
+impl Plugin for LayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AutoLayout>()
+            .register_type::<FlowRoute>()
+            .insert_resource(SnapSettings::load())
+            .init_resource::<AlignmentGuides>()
+            .add_systems(
+                Update,
+                (
+                    auto_layout,
+                    snap_dragged_elements
+                        .after(crate::systems::drag_subsystem)
+                        .after(crate::systems::drag_external_entity),
+                    clear_alignment_guides.run_if(not(input_pressed(MouseButton::Left))),
+                    draw_alignment_guides,
+                    draw_grid,
+                    save_snap_settings,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                route_flows
+                    .after(crate::GeometryUpdateSet)
+                    .before(bevy::transform::TransformSystem::TransformPropagate),
+            );
+    }
+}
@@ -0,0 +1,366 @@
+//! Routing of flows around systems and external entities.
+use crate::components::*;
+use crate::constants::EXTERNAL_ENTITY_HEIGHT_HALF;
+use crate::plugins::label::NameLabel;
+use crate::plugins::preferences::Preferences;
+use crate::resources::Zoom;
+use bevy::prelude::*;
+use bevy::text::TextLayoutInfo;
+use bevy::utils::{HashMap, HashSet};
+
+/// Space between a routed flow and the systems and external entities it passes.
+const ROUTE_CLEARANCE: f32 = 20.0;
+
+/// Maximum number of detours around obstacles per flow.
+const MAX_DETOURS: usize = 12;
+
+/// Distance between the flows of a bundle.
+const BUNDLE_SPACING: f32 = 8.0;
+
+/// How a flow is drawn between its terminals.
+#[derive(Clone, Debug, Component, Reflect, PartialEq)]
+#[reflect(Component)]
+pub struct FlowRoute {
+    /// Whether the flow is routed around systems and external entities instead of being drawn
+    /// as a single curve.
+    pub routed: bool,
+    /// Points that a routed flow passes through between its terminals. They are in the same
+    /// coordinates as the `FlowCurve`.
+    pub waypoints: Vec<Vec2>,
+    /// Where the label sits along the drawn flow as a fraction of its length. Bundled flows
+    /// spread their labels along the route so they don't cover each other.
+    pub label_position: f32,
+}
+
+impl Default for FlowRoute {
+    fn default() -> Self {
+        Self {
+            routed: false,
+            waypoints: vec![],
+            label_position: 0.5,
+        }
+    }
+}
+
+/// Circle that a flow has to go around.
+#[derive(Copy, Clone, Debug)]
+struct Obstacle {
+    center: Vec2,
+    radius: f32,
+}
+
+fn closest_point_on_segment(a: Vec2, b: Vec2, point: Vec2) -> Vec2 {
+    let ab = b - a;
+    let t = ((point - a).dot(ab) / ab.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+/// Computes waypoints from `start` to `end` that keep `clearance` to all obstacles. Each detour
+/// pushes the point of a segment that is closest to an obstacle out of it. Obstacles that
+/// contain one end of a segment are ignored for that segment, e.g. the systems a flow connects.
+fn route(start: Vec2, end: Vec2, obstacles: &[Obstacle], clearance: f32) -> Vec<Vec2> {
+    let mut points = vec![start, end];
+
+    for _ in 0..MAX_DETOURS {
+        let detour = points.windows(2).enumerate().find_map(|(i, segment)| {
+            let (a, b) = (segment[0], segment[1]);
+            obstacles
+                .iter()
+                .filter(|o| {
+                    o.center.distance(a) > o.radius + clearance * 0.5
+                        && o.center.distance(b) > o.radius + clearance * 0.5
+                })
+                .map(|o| {
+                    let closest = closest_point_on_segment(a, b, o.center);
+                    (
+                        o,
+                        closest,
+                        o.radius + clearance - closest.distance(o.center),
+                    )
+                })
+                .filter(|(_, _, penetration)| *penetration > 1.0)
+                .max_by(|x, y| x.2.total_cmp(&y.2))
+                .map(|(o, closest, _)| {
+                    let normal = (closest - o.center)
+                        .try_normalize()
+                        .unwrap_or_else(|| (b - a).perp().normalize_or_zero());
+                    (i + 1, o.center + normal * (o.radius + clearance))
+                })
+        });
+
+        match detour {
+            Some((index, waypoint)) => points.insert(index, waypoint),
+            None => break,
+        }
+    }
+
+    points[1..points.len() - 1].to_vec()
+}
+
+/// Moves the waypoints of a bundled flow sideways along the route.
+fn offset_waypoints(start: Vec2, waypoints: &[Vec2], end: Vec2, offset: f32) -> Vec<Vec2> {
+    (0..waypoints.len())
+        .map(|i| {
+            let previous = if i == 0 { start } else { waypoints[i - 1] };
+            let next = waypoints.get(i + 1).copied().unwrap_or(end);
+            waypoints[i] + (next - previous).perp().normalize_or_zero() * offset
+        })
+        .collect()
+}
+
+/// Position of the label of the `k`-th of `count` bundled flows along the route.
+fn bundle_label_position(k: usize, count: usize) -> f32 {
+    (k + 1) as f32 / (count + 1) as f32
+}
+
+/// Recomputes the waypoints of the routed flows whose curves changed or whose siblings moved.
+/// Flows that connect the same two elements are bundled: they follow the route of the first
+/// of them with a small sideways offset. Routes avoid systems, external entities and the labels
+/// of other flows.
+///
+/// Runs after the flow curves are updated and works with local transforms so that curves and
+/// routes are drawn together in the next frame.
+pub fn route_flows(
+    changed_flow_query: Query<
+        Entity,
+        (
+            With<FlowRoute>,
+            Or<(Changed<FlowCurve>, Changed<FlowRoute>)>,
+        ),
+    >,
+    changed_obstacle_query: Query<
+        Option<&Parent>,
+        (
+            Or<(With<crate::components::System>, With<ExternalEntity>)>,
+            Changed<Transform>,
+        ),
+    >,
+    mut flow_query: Query<(
+        Entity,
+        &FlowCurve,
+        &mut FlowRoute,
+        &Transform,
+        &GlobalTransform,
+        Option<&Parent>,
+        &NestingLevel,
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&NameLabel>,
+    )>,
+    obstacle_query: Query<
+        (
+            &Transform,
+            Option<&Parent>,
+            Option<&crate::components::System>,
+            Option<&NestingLevel>,
+        ),
+        Or<(With<crate::components::System>, With<ExternalEntity>)>,
+    >,
+    label_query: Query<(&GlobalTransform, &TextLayoutInfo)>,
+    zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
+) {
+    if changed_flow_query.is_empty() && changed_obstacle_query.is_empty() {
+        return;
+    }
+
+    let changed_flows = changed_flow_query.iter().collect::<HashSet<_>>();
+    let changed_parents = changed_obstacle_query
+        .iter()
+        .map(|parent| parent.map(|p| p.get()))
+        .collect::<HashSet<_>>();
+
+    let mut bundles = HashMap::<(Option<Entity>, Entity, Entity), Vec<Entity>>::new();
+    for (entity, _, mut route, _, _, parent, _, start, end, _) in &mut flow_query {
+        if route.routed {
+            let (a, b) = if start.target < end.target {
+                (start.target, end.target)
+            } else {
+                (end.target, start.target)
+            };
+            bundles
+                .entry((parent.map(|p| p.get()), a, b))
+                .or_default()
+                .push(entity);
+        } else if !route.waypoints.is_empty() || route.label_position != 0.5 {
+            route.waypoints.clear();
+            route.label_position = 0.5;
+        }
+    }
+    bundles.retain(|(parent, ..), flows| {
+        changed_parents.contains(parent) || flows.iter().any(|f| changed_flows.contains(f))
+    });
+
+    // Labels of routed flows by parent. Routes of other bundles go around them.
+    let mut labels = HashMap::<Option<Entity>, Vec<(Entity, Vec3, f32)>>::new();
+    for (entity, _, route, .., parent, _, _, _, name_label) in &flow_query {
+        let Some((label_transform, layout)) =
+            name_label.and_then(|label| label_query.get(label.label).ok())
+        else {
+            continue;
+        };
+        if route.routed {
+            let (scale, _, translation) = label_transform.to_scale_rotation_translation();
+            labels.entry(parent.map(|p| p.get())).or_default().push((
+                entity,
+                translation,
+                layout.logical_size.length() * 0.5 * scale.x,
+            ));
+        }
+    }
+
+    // Start, waypoints and end in the coordinates of the parent of the first flow of each bundle.
+    let mut bundle_routes = HashMap::<Entity, (Vec2, Vec<Vec2>, Vec2)>::new();
+    for ((parent, ..), flows) in &mut bundles {
+        flows.sort();
+        let Ok((_, curve, _, transform, global_transform, _, nesting_level, ..)) =
+            flow_query.get(flows[0])
+        else {
+            continue;
+        };
+        let scale = preferences.nesting_scale(**nesting_level, **zoom);
+        let clearance = ROUTE_CLEARANCE * scale;
+        let to_local = transform.compute_affine().inverse();
+        let world_to_local = global_transform.affine().inverse();
+
+        let element_obstacles = obstacle_query
+            .iter()
+            .filter(|(_, obstacle_parent, ..)| obstacle_parent.map(|p| p.get()) == *parent)
+            .map(|(obstacle_transform, _, system, nesting_level)| Obstacle {
+                center: to_local
+                    .transform_point3(obstacle_transform.translation)
+                    .truncate(),
+                radius: match system {
+                    Some(system) => system.radius * **zoom,
+                    None => {
+                        EXTERNAL_ENTITY_HEIGHT_HALF
+                            * preferences.nesting_scale(nesting_level.map_or(0, |n| **n), **zoom)
+                    }
+                },
+            });
+        let label_obstacles = labels
+            .get(parent)
+            .into_iter()
+            .flatten()
+            .filter(|(flow, ..)| !flows.contains(flow))
+            .map(|(_, translation, radius)| Obstacle {
+                center: world_to_local.transform_point3(*translation).truncate(),
+                radius: *radius,
+            });
+        let obstacles = element_obstacles.chain(label_obstacles).collect::<Vec<_>>();
+
+        let start = curve.start + curve.start_direction * clearance;
+        let end = curve.end + curve.end_direction * clearance;
+        let to_parent = |p: Vec2| transform.transform_point(p.extend(0.0)).truncate();
+        let waypoints = route(start, end, &obstacles, clearance)
+            .into_iter()
+            .map(to_parent)
+            .collect();
+        bundle_routes.insert(flows[0], (to_parent(start), waypoints, to_parent(end)));
+    }
+
+    for flows in bundles.values() {
+        let Some((start, waypoints, end)) = bundle_routes.get(&flows[0]) else {
+            continue;
+        };
+        for (k, entity) in flows.iter().enumerate() {
+            let Ok((_, _, mut route, transform, _, _, nesting_level, ..)) =
+                flow_query.get_mut(*entity)
+            else {
+                continue;
+            };
+            let scale = preferences.nesting_scale(**nesting_level, **zoom);
+            let offset = (k as f32 - (flows.len() - 1) as f32 * 0.5) * BUNDLE_SPACING * scale;
+            let to_local = transform.compute_affine().inverse();
+            let waypoints = offset_waypoints(*start, waypoints, *end, offset)
+                .into_iter()
+                .map(|w| to_local.transform_point3(w.extend(0.0)).truncate())
+                .collect::<Vec<_>>();
+
+            route.set_if_neq(FlowRoute {
+                routed: true,
+                waypoints,
+                label_position: bundle_label_position(k, flows.len()),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn min_distance(start: Vec2, waypoints: &[Vec2], end: Vec2, point: Vec2) -> f32 {
+        let points = [vec![start], waypoints.to_vec(), vec![end]].concat();
+        points
+            .windows(2)
+            .map(|s| closest_point_on_segment(s[0], s[1], point).distance(point))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    #[test]
+    fn free_routes_are_straight() {
+        let obstacles = [Obstacle {
+            center: Vec2::new(0.0, 100.0),
+            radius: 20.0,
+        }];
+
+        assert!(route(
+            Vec2::new(-100.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            &obstacles,
+            10.0
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn routes_keep_clearance_to_obstacles() {
+        let (start, end) = (Vec2::new(-100.0, 0.0), Vec2::new(100.0, 0.0));
+        let obstacle = Obstacle {
+            center: Vec2::new(0.0, 5.0),
+            radius: 20.0,
+        };
+        let waypoints = route(start, end, &[obstacle], 10.0);
+
+        assert!(!waypoints.is_empty());
+        assert!(min_distance(start, &waypoints, end, obstacle.center) >= 29.0);
+    }
+
+    #[test]
+    fn obstacles_at_the_ends_are_ignored() {
+        let obstacles = [Obstacle {
+            center: Vec2::new(-100.0, 0.0),
+            radius: 30.0,
+        }];
+
+        assert!(route(
+            Vec2::new(-80.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            &obstacles,
+            10.0
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn bundled_waypoints_are_offset_sideways() {
+        let waypoints = offset_waypoints(
+            Vec2::new(-10.0, 0.0),
+            &[Vec2::ZERO],
+            Vec2::new(10.0, 0.0),
+            5.0,
+        );
+
+        assert_eq!(waypoints, vec![Vec2::new(0.0, 5.0)]);
+    }
+
+    #[test]
+    fn bundled_labels_are_spread_along_the_route() {
+        assert_eq!(bundle_label_position(0, 1), 0.5);
+        let positions = (0..3)
+            .map(|k| bundle_label_position(k, 3))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![0.25, 0.5, 0.75]);
+    }
+}
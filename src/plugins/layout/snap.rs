@@ -0,0 +1,297 @@
+//! Snapping of dragged elements to a grid, to their siblings and of interfaces to angles.
+use crate::components::*;
+use crate::events::{ExternalEntityDrag, SubsystemDrag};
+use crate::plugins::theme::{srgb, Themes};
+use crate::resources::Zoom;
+use crate::systems::{clamp_to_parent_system, external_entity_rotation};
+use crate::utils::{load_user_config, save_user_config};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+
+const SNAP_SETTINGS_FILE_NAME: &str = "snap.json";
+
+/// Distance in logical pixels within which an element snaps to a sibling's x or y coordinate.
+const GUIDE_SNAP_DISTANCE: f32 = 6.0;
+
+/// Grid lines closer than this in logical pixels aren't drawn.
+const MIN_GRID_LINE_SPACING: f32 = 8.0;
+
+/// How dragged elements snap. Stored in the user's configuration directory.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapSettings {
+    /// Snap dragged subsystems and external entities to the grid.
+    pub grid_snap: bool,
+    pub show_grid: bool,
+    /// Distance of the grid lines in pixels if zoom is at 100%.
+    pub grid_size: f32,
+    /// Snap dragged elements to the x or y coordinate of their siblings and show guides.
+    pub guides: bool,
+    /// Snap interfaces to multiples of `angle_step` on the boundary.
+    pub angle_snap: bool,
+    /// In degrees.
+    pub angle_step: f32,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            grid_snap: false,
+            show_grid: false,
+            grid_size: 25.0,
+            guides: true,
+            angle_snap: false,
+            angle_step: 15.0,
+        }
+    }
+}
+
+impl SnapSettings {
+    pub fn load() -> Self {
+        load_user_config(SNAP_SETTINGS_FILE_NAME)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_user_config(SNAP_SETTINGS_FILE_NAME, self)
+    }
+
+    /// Snaps an angle in radians if angle snapping is enabled.
+    pub fn snap_angle(&self, angle: f32) -> f32 {
+        if !self.angle_snap || self.angle_step <= 0.0 {
+            return angle;
+        }
+        let step = self.angle_step.to_radians();
+        (angle / step).round() * step
+    }
+}
+
+/// Snaps a local `position` to the nearest point of a world grid with spacing `grid`. `to_world`
+/// is the offset from local to world coordinates.
+fn snap_to_grid(position: Vec2, to_world: Vec2, grid: f32) -> Vec2 {
+    ((position + to_world) / grid).round() * grid - to_world
+}
+
+/// Lines in world coordinates that show the alignments of the dragged element.
+#[derive(Resource, Default, Debug)]
+pub struct AlignmentGuides(pub Vec<(Vec2, Vec2)>);
+
+/// Snaps subsystems and external entities that were dragged this frame to the grid and to the
+/// coordinates of their siblings.
+pub fn snap_dragged_elements(
+    mut subsystem_drag_reader: EventReader<SubsystemDrag>,
+    mut external_entity_drag_reader: EventReader<ExternalEntityDrag>,
+    mut element_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &GlobalTransform,
+            Option<&Parent>,
+            Has<ExternalEntity>,
+        ),
+        Or<(
+            (With<Subsystem>, Without<InterfaceSubsystem>),
+            With<ExternalEntity>,
+        )>,
+    >,
+    subsystem_query: Query<&Subsystem>,
+    system_query: Query<&crate::components::System>,
+    flow_query: Query<(&FlowStartConnection, &FlowEndConnection, &FlowCurve), With<FlowCurve>>,
+    snap_settings: Res<SnapSettings>,
+    zoom: Res<Zoom>,
+    mut alignment_guides: ResMut<AlignmentGuides>,
+) {
+    let dragged = subsystem_drag_reader
+        .read()
+        .filter(|event| !event.has_bubbled())
+        .map(|event| event.target)
+        .chain(
+            external_entity_drag_reader
+                .read()
+                .filter(|event| !event.has_bubbled())
+                .map(|event| event.target),
+        )
+        .collect::<Vec<_>>();
+
+    if dragged.is_empty() {
+        return;
+    }
+    alignment_guides.0.clear();
+
+    for target in dragged {
+        let Ok((_, transform, global_transform, parent, is_external_entity)) =
+            element_query.get(target)
+        else {
+            continue;
+        };
+        let parent = parent.map(|p| p.get());
+        let mut position = transform.translation.truncate();
+
+        // Offset from local to world coordinates. The grid is in world coordinates so that it
+        // matches the grid that is drawn at every nesting level.
+        let to_world = global_transform.translation().truncate() - transform.translation.truncate();
+
+        if snap_settings.grid_snap && snap_settings.grid_size > 0.0 {
+            let grid = snap_settings.grid_size * **zoom;
+            position = snap_to_grid(position, to_world, grid);
+        }
+
+        if snap_settings.guides {
+            let mut best_x: Option<(f32, f32)> = None;
+            let mut best_y: Option<(f32, f32)> = None;
+            for (entity, sibling_transform, _, sibling_parent, _) in &element_query {
+                if entity == target || sibling_parent.map(|p| p.get()) != parent {
+                    continue;
+                }
+                let sibling = sibling_transform.translation.truncate();
+                let dx = (sibling.x - position.x).abs();
+                if dx < GUIDE_SNAP_DISTANCE && !matches!(best_x, Some((d, _)) if d <= dx) {
+                    best_x = Some((dx, sibling.x));
+                }
+                let dy = (sibling.y - position.y).abs();
+                if dy < GUIDE_SNAP_DISTANCE && !matches!(best_y, Some((d, _)) if d <= dy) {
+                    best_y = Some((dy, sibling.y));
+                }
+            }
+
+            if let Some((_, x)) = best_x {
+                position.x = x;
+            }
+            if let Some((_, y)) = best_y {
+                position.y = y;
+            }
+
+            for (entity, sibling_transform, _, sibling_parent, _) in &element_query {
+                if entity == target || sibling_parent.map(|p| p.get()) != parent {
+                    continue;
+                }
+                let sibling = sibling_transform.translation.truncate();
+                if best_x.is_some() && sibling.x == position.x
+                    || best_y.is_some() && sibling.y == position.y
+                {
+                    alignment_guides
+                        .0
+                        .push((sibling + to_world, position + to_world));
+                }
+            }
+        }
+
+        // Keeps the elements inside the parent system like `drag_subsystem` and
+        // `drag_external_entity` do and turns external entities to face their flow again.
+        let mut rotation = None;
+        if is_external_entity {
+            let (system, _) = external_entity_rotation(target, position, &flow_query);
+            position =
+                clamp_to_parent_system(system, position, &subsystem_query, &system_query, **zoom);
+            rotation = Some(external_entity_rotation(target, position, &flow_query).1);
+        } else {
+            position =
+                clamp_to_parent_system(target, position, &subsystem_query, &system_query, **zoom);
+        }
+
+        if let Ok((_, mut transform, ..)) = element_query.get_mut(target) {
+            transform.translation = position.extend(transform.translation.z);
+            if let Some(rotation) = rotation {
+                transform.rotation = rotation;
+            }
+        }
+    }
+}
+
+/// Persists the snap settings when they are changed in the View menu.
+pub fn save_snap_settings(
+    snap_settings: Res<SnapSettings>,
+    mut saved: Local<Option<SnapSettings>>,
+) {
+    if !snap_settings.is_changed() || saved.as_ref() == Some(&*snap_settings) {
+        return;
+    }
+    if saved.is_some() {
+        if let Err(error) = snap_settings.save() {
+            warn!("Failed to save snap settings: {}", error);
+        }
+    }
+    *saved = Some(snap_settings.clone());
+}
+
+pub fn clear_alignment_guides(mut alignment_guides: ResMut<AlignmentGuides>) {
+    alignment_guides.0.clear();
+}
+
+pub fn draw_alignment_guides(
+    alignment_guides: Res<AlignmentGuides>,
+    themes: Res<Themes>,
+    mut gizmos: Gizmos,
+) {
+    let color = srgb(themes.current().selection);
+    for (start, end) in &alignment_guides.0 {
+        gizmos.line_2d(*start, *end, color);
+    }
+}
+
+/// Draws the grid that elements snap to in the visible part of the canvas.
+pub fn draw_grid(
+    snap_settings: Res<SnapSettings>,
+    themes: Res<Themes>,
+    zoom: Res<Zoom>,
+    camera_query: Query<&Transform, With<Camera>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut gizmos: Gizmos,
+) {
+    let grid = snap_settings.grid_size * **zoom;
+    if !snap_settings.show_grid || grid < MIN_GRID_LINE_SPACING {
+        return;
+    }
+    let (Ok(window), Ok(camera_transform)) = (window_query.get_single(), camera_query.get_single())
+    else {
+        return;
+    };
+
+    let center = camera_transform.translation.truncate();
+    let half_size = Vec2::new(window.width(), window.height()) * 0.5;
+    let min = ((center - half_size) / grid).floor() * grid;
+    let max = center + half_size;
+    let color = srgb(themes.current().stroke).with_a(0.1);
+
+    let mut x = min.x;
+    while x <= max.x {
+        gizmos.line_2d(Vec2::new(x, min.y), Vec2::new(x, max.y), color);
+        x += grid;
+    }
+    let mut y = min.y;
+    while y <= max.y {
+        gizmos.line_2d(Vec2::new(min.x, y), Vec2::new(max.x, y), color);
+        y += grid;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_is_in_world_coordinates() {
+        let to_world = Vec2::new(7.0, -3.0);
+        let snapped = snap_to_grid(Vec2::new(10.0, 12.0), to_world, 25.0);
+
+        assert_eq!(snapped + to_world, Vec2::new(25.0, 0.0));
+    }
+
+    #[test]
+    fn angles_snap_to_steps() {
+        let mut settings = SnapSettings::default();
+        assert_eq!(settings.snap_angle(0.3), 0.3);
+
+        settings.angle_snap = true;
+        settings.angle_step = 90.0;
+        assert_eq!(settings.snap_angle(1.0), std::f32::consts::FRAC_PI_2);
+    }
+
+    #[test]
+    fn missing_settings_use_the_defaults() {
+        let settings: SnapSettings = serde_json::from_str(r#"{"grid_snap": true}"#).unwrap();
+
+        assert!(settings.grid_snap);
+        assert_eq!(settings.grid_size, SnapSettings::default().grid_size);
+    }
+}
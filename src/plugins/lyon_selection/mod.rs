@@ -3,9 +3,7 @@ use bevy_prototype_lyon::prelude::*;
 
 mod highlight_bundles;
 
-use crate::plugins::lyon_selection::highlight_bundles::apply_highlight_bundles;
-
-pub use highlight_bundles::HighlightBundles;
+pub use highlight_bundles::{apply_highlight_bundles, HighlightBundles};
 
 pub struct LyonSelectionPlugin;
 
@@ -1,4 +1,18 @@
+pub mod analysis;
+pub mod annotations;
+pub mod attributes;
+pub mod command_palette;
+pub mod descriptions;
 pub mod file_dialog;
+pub mod formula;
+pub mod keybindings;
 pub mod label;
+pub mod layout;
 pub mod lyon_selection;
 pub mod mouse_interaction;
+pub mod navigation;
+pub mod outline;
+pub mod preferences;
+pub mod protocols;
+pub mod scenarios;
+pub mod theme;
@@ -80,6 +80,13 @@ pub struct Dragging {
     start_pos: Vec2,
 }
 
+impl Dragging {
+    /// Whether an element was pressed and may be dragged, or is being dragged.
+    pub fn is_pending(&self) -> bool {
+        self.started || self.hovered_entity.is_some()
+    }
+}
+
 #[derive(Resource, Clone, Deref, DerefMut, PartialEq, Eq, Reflect, Debug, Default)]
 pub struct Selection(HashSet<Entity>);
 
@@ -0,0 +1,185 @@
+//! Smooth camera flights between areas of the canvas.
+use crate::plugins::mouse_interaction::{DragPosition, Dragging};
+use crate::resources::{FocusedSystem, Zoom, ZoomLimits};
+use crate::systems::zoom_factor_to_fit;
+use bevy::input::mouse::MouseWheel;
+use bevy::input::touchpad::TouchpadMagnify;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+/// How long a camera flight takes in seconds.
+const FLIGHT_DURATION: f32 = 0.4;
+
+/// Fraction of the smaller window dimension that a focused system fills after diving into it.
+const DIVE_FILL: f32 = 0.9;
+
+/// A camera flight that is in progress. Centers are unzoomed world coordinates so that they
+/// stay valid while the zoom changes.
+#[derive(Copy, Clone, Debug)]
+pub struct CameraFlight {
+    /// The system that was focused when the flight started.
+    pub focus: Entity,
+    from_center: Vec2,
+    from_zoom: f32,
+    to_center: Vec2,
+    to_zoom: f32,
+    elapsed: f32,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct CameraAnimation(Option<CameraFlight>);
+
+impl CameraAnimation {
+    /// Starts a flight from the current view to an area so that it fills `fill` of the smaller
+    /// window dimension. `center` and `half_size` are in the current zoomed world coordinates.
+    pub fn start(
+        &mut self,
+        focus: Entity,
+        camera_transform: &Transform,
+        zoom: &Zoom,
+        window_size: Vec2,
+        center: Vec2,
+        half_size: Vec2,
+        fill: f32,
+    ) {
+        self.fly_to(
+            focus,
+            camera_transform,
+            zoom,
+            center / **zoom,
+            **zoom * zoom_factor_to_fit(window_size, half_size, fill),
+        );
+    }
+
+    /// Starts a flight from the current view to an unzoomed world position and zoom.
+    pub fn fly_to(
+        &mut self,
+        focus: Entity,
+        camera_transform: &Transform,
+        zoom: &Zoom,
+        to_center: Vec2,
+        to_zoom: f32,
+    ) {
+        let zoom = **zoom;
+        self.0 = Some(CameraFlight {
+            focus,
+            from_center: camera_transform.translation.truncate() / zoom,
+            from_zoom: zoom,
+            to_center,
+            to_zoom,
+            elapsed: 0.0,
+        });
+    }
+
+    pub fn flight(&self) -> Option<&CameraFlight> {
+        self.0.as_ref()
+    }
+
+    pub fn cancel(&mut self) {
+        self.0 = None;
+    }
+}
+
+/// Starts a flight into the focused system whenever the focus changes. Flights that were
+/// started for the same focus, e.g. by jumping to an element, are kept. Elements that are pressed
+/// to be dragged are focused too, so there's no flight while that's the case.
+pub fn dive_into_focused_system(
+    focused_system: Res<FocusedSystem>,
+    dragging: Res<Dragging>,
+    mut previous_focused_system: Local<Option<Entity>>,
+    system_query: Query<(&GlobalTransform, &crate::components::System)>,
+    camera_query: Query<&Transform, With<Camera>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    zoom: Res<Zoom>,
+    mut camera_animation: ResMut<CameraAnimation>,
+) {
+    if !focused_system.is_changed() || Some(**focused_system) == *previous_focused_system {
+        return;
+    }
+
+    let previous = previous_focused_system.replace(**focused_system);
+    if previous.is_none()
+        || dragging.is_pending()
+        || camera_animation
+            .flight()
+            .is_some_and(|flight| flight.focus == **focused_system)
+    {
+        return;
+    }
+
+    let (Ok((global_transform, system)), Ok(window)) = (
+        system_query.get(**focused_system),
+        window_query.get_single(),
+    ) else {
+        return;
+    };
+
+    let Ok(camera_transform) = camera_query.get_single() else {
+        return;
+    };
+
+    camera_animation.start(
+        **focused_system,
+        camera_transform,
+        &zoom,
+        Vec2::new(window.width(), window.height()),
+        global_transform.translation().truncate(),
+        Vec2::splat(system.radius * **zoom),
+        DIVE_FILL,
+    );
+}
+
+/// Moves the camera and zoom along the current flight. The zoom is interpolated
+/// logarithmically so that zooming in and out feels equally fast.
+pub fn animate_camera(
+    time: Res<Time>,
+    mut camera_animation: ResMut<CameraAnimation>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+    mut zoom: ResMut<Zoom>,
+    zoom_limits: Res<ZoomLimits>,
+) {
+    let Some(flight) = &mut camera_animation.0 else {
+        return;
+    };
+
+    flight.elapsed += time.delta_seconds();
+    let t = (flight.elapsed / FLIGHT_DURATION).min(1.0);
+    let s = t * t * (3.0 - 2.0 * t);
+
+    let center = flight.from_center.lerp(flight.to_center, s);
+    let new_zoom = zoom_limits
+        .clamp((flight.from_zoom.ln() + (flight.to_zoom.ln() - flight.from_zoom.ln()) * s).exp());
+
+    // The camera position is given for the current zoom. It's scaled along with the new zoom by
+    // `apply_zoom_to_camera_position`.
+    let current_zoom = **zoom;
+    let mut camera_transform = camera_query.single_mut();
+    camera_transform.translation = (center * current_zoom).extend(camera_transform.translation.z);
+    zoom.mul(new_zoom / current_zoom);
+
+    if t >= 1.0 {
+        camera_animation.cancel();
+    }
+}
+
+/// Stops the current flight when the user takes over the camera: panning, zooming with the wheel,
+/// the keyboard or a pinch, or dragging an element.
+pub fn cancel_camera_animation(
+    mut camera_animation: ResMut<CameraAnimation>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut wheel_reader: EventReader<MouseWheel>,
+    mut magnify_reader: EventReader<TouchpadMagnify>,
+    mut drag_reader: EventReader<DragPosition>,
+) {
+    // All readers are drained so that old input doesn't cancel the next flight.
+    let wheel = wheel_reader.read().count() > 0;
+    let magnify = magnify_reader.read().count() > 0;
+    let drag = drag_reader.read().count() > 0;
+    let keyboard_zoom = key_input.any_just_pressed([KeyCode::Minus, KeyCode::Equal]);
+    let pan = mouse_input.pressed(MouseButton::Right);
+
+    if camera_animation.flight().is_some() && (wheel || magnify || drag || keyboard_zoom || pan) {
+        camera_animation.cancel();
+    }
+}
@@ -0,0 +1,103 @@
+//! Named camera positions that are stored with the model.
+use super::CameraAnimation;
+use crate::resources::{FocusedSystem, Zoom};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+/// A saved camera position. `center` is in unzoomed world coordinates.
+#[derive(Clone, Debug)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub center: Vec2,
+    pub zoom: f32,
+    /// The system that was focused when the bookmark was created.
+    pub focus: Option<Entity>,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct CameraBookmarks(pub Vec<CameraBookmark>);
+
+/// State of the bookmark list window.
+#[derive(Resource, Default)]
+pub struct BookmarkList {
+    pub open: bool,
+    /// Name for the next bookmark.
+    name: String,
+}
+
+pub fn bookmarks_egui(
+    mut egui_contexts: EguiContexts,
+    mut bookmark_list: ResMut<BookmarkList>,
+    mut camera_bookmarks: ResMut<CameraBookmarks>,
+    mut focused_system: ResMut<FocusedSystem>,
+    system_query: Query<(), With<crate::components::System>>,
+    camera_query: Query<&Transform, With<Camera>>,
+    zoom: Res<Zoom>,
+    mut camera_animation: ResMut<CameraAnimation>,
+) {
+    if !bookmark_list.open {
+        return;
+    }
+
+    let mut open = bookmark_list.open;
+    let mut recall = None;
+    let mut remove = None;
+
+    egui::Window::new("Camera Bookmarks")
+        .open(&mut open)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut bookmark_list.name);
+                if ui
+                    .add_enabled(
+                        !bookmark_list.name.trim().is_empty(),
+                        egui::Button::new("Add Current View"),
+                    )
+                    .clicked()
+                {
+                    camera_bookmarks.0.push(CameraBookmark {
+                        name: std::mem::take(&mut bookmark_list.name).trim().to_string(),
+                        center: camera_query.single().translation.truncate() / **zoom,
+                        zoom: **zoom,
+                        focus: Some(**focused_system),
+                    });
+                }
+            });
+            ui.separator();
+
+            if camera_bookmarks.0.is_empty() {
+                ui.label("No bookmarks yet.");
+            }
+            for (i, bookmark) in camera_bookmarks.0.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.button("Go").clicked() {
+                        recall = Some(i);
+                    }
+                    ui.text_edit_singleline(&mut bookmark.name);
+                    if ui.button("🗑").on_hover_text("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+        });
+
+    bookmark_list.open = open;
+
+    if let Some(i) = recall {
+        let bookmark = &camera_bookmarks.0[i];
+        if let Some(focus) = bookmark.focus.filter(|e| system_query.contains(*e)) {
+            **focused_system = focus;
+        }
+        camera_animation.fly_to(
+            **focused_system,
+            camera_query.single(),
+            &zoom,
+            bookmark.center,
+            bookmark.zoom,
+        );
+    }
+
+    if let Some(i) = remove {
+        camera_bookmarks.0.remove(i);
+    }
+}
@@ -0,0 +1,74 @@
+//! Bar below the menu that shows the path from the root to the focused system.
+use super::FocusHistory;
+use crate::plugins::keybindings::{KeyAction, Keybindings};
+use crate::resources::FocusedSystem;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+pub fn breadcrumbs_egui(
+    mut egui_contexts: EguiContexts,
+    mut focused_system: ResMut<FocusedSystem>,
+    mut focus_history: ResMut<FocusHistory>,
+    system_query: Query<(), With<crate::components::System>>,
+    parent_query: Query<&Parent>,
+    name_query: Query<&Name>,
+    keybindings: Res<Keybindings>,
+) {
+    if !system_query.contains(**focused_system) {
+        return;
+    }
+
+    let mut path = std::iter::once(**focused_system)
+        .chain(parent_query.iter_ancestors(**focused_system))
+        .filter(|e| system_query.contains(*e))
+        .collect::<Vec<_>>();
+    path.reverse();
+
+    let mut new_focus = None;
+
+    egui::TopBottomPanel::top("Breadcrumbs").show(egui_contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(focus_history.can_go_back(), egui::Button::new("⏴"))
+                .on_hover_text(format!(
+                    "Back ({})",
+                    keybindings.get(KeyAction::NavigateBack).label()
+                ))
+                .clicked()
+            {
+                new_focus = focus_history.go_back(|e| system_query.contains(e));
+            }
+            if ui
+                .add_enabled(focus_history.can_go_forward(), egui::Button::new("⏵"))
+                .on_hover_text(format!(
+                    "Forward ({})",
+                    keybindings.get(KeyAction::NavigateForward).label()
+                ))
+                .clicked()
+            {
+                new_focus = focus_history.go_forward(|e| system_query.contains(e));
+            }
+            ui.separator();
+
+            for (i, &entity) in path.iter().enumerate() {
+                if i > 0 {
+                    ui.label("›");
+                }
+                let name = name_query.get(entity).map(|n| n.as_str()).unwrap_or("");
+                let name = if name.trim().is_empty() {
+                    "Unnamed"
+                } else {
+                    name
+                };
+                let is_focused = i == path.len() - 1;
+                if ui.selectable_label(is_focused, name).clicked() && !is_focused {
+                    new_focus = Some(entity);
+                }
+            }
+        });
+    });
+
+    if let Some(entity) = new_focus {
+        **focused_system = entity;
+    }
+}
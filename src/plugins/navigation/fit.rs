@@ -0,0 +1,98 @@
+//! Framing the whole model, the focused system or the selection with the camera.
+use super::CameraAnimation;
+use crate::components::*;
+use crate::plugins::mouse_interaction::PickSelection;
+use crate::resources::{FocusedSystem, Zoom};
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::window::PrimaryWindow;
+
+/// Fraction of the smaller window dimension that the framed elements fill.
+const FIT_FILL: f32 = 0.9;
+
+/// What to frame with the camera.
+#[derive(Event, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FitView {
+    /// All elements of the model.
+    Model,
+    /// The focused system with everything inside it.
+    FocusedSystem,
+    /// The selected elements.
+    Selection,
+}
+
+pub fn fit_view(
+    mut fit_view_reader: EventReader<FitView>,
+    element_query: Query<(Entity, &GlobalTransform, &Aabb), With<SystemElement>>,
+    pick_selection_query: Query<&PickSelection>,
+    parent_query: Query<&Parent>,
+    focused_system: Res<FocusedSystem>,
+    camera_query: Query<&Transform, With<Camera>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    zoom: Res<Zoom>,
+    mut camera_animation: ResMut<CameraAnimation>,
+) {
+    let Some(fit) = fit_view_reader.read().last().copied() else {
+        return;
+    };
+
+    let is_included = |entity: Entity| match fit {
+        FitView::Model => true,
+        FitView::FocusedSystem => {
+            entity == **focused_system
+                || parent_query
+                    .iter_ancestors(entity)
+                    .any(|e| e == **focused_system)
+        }
+        FitView::Selection => pick_selection_query
+            .get(entity)
+            .is_ok_and(|selection| selection.is_selected),
+    };
+
+    let (mut min, mut max) = (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN));
+    for (entity, global_transform, aabb) in &element_query {
+        if !is_included(entity) {
+            continue;
+        }
+        let center = global_transform
+            .transform_point(aabb.center.into())
+            .truncate();
+        let half_size = (Vec3::from(aabb.half_extents).truncate()
+            * global_transform.compute_transform().scale.truncate())
+        .abs();
+        min = min.min(center - half_size);
+        max = max.max(center + half_size);
+    }
+
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    if min.x > max.x {
+        return;
+    }
+
+    camera_animation.start(
+        **focused_system,
+        camera_query.single(),
+        &zoom,
+        Vec2::new(window.width(), window.height()),
+        (min + max) * 0.5,
+        (max - min) * 0.5,
+        FIT_FILL,
+    );
+}
+
+/// Frames the model, Shift+1 by default.
+pub fn fit_model_from_keyboard(mut fit_view_writer: EventWriter<FitView>) {
+    fit_view_writer.send(FitView::Model);
+}
+
+/// Frames the selection, Shift+2 by default.
+pub fn fit_selection_from_keyboard(mut fit_view_writer: EventWriter<FitView>) {
+    fit_view_writer.send(FitView::Selection);
+}
+
+/// Frames the focused system, Shift+3 by default.
+pub fn fit_focused_system_from_keyboard(mut fit_view_writer: EventWriter<FitView>) {
+    fit_view_writer.send(FitView::FocusedSystem);
+}
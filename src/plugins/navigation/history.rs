@@ -0,0 +1,158 @@
+//! Back/forward history of the focused system.
+use crate::resources::FocusedSystem;
+use bevy::prelude::*;
+
+/// Maximum number of systems that are remembered in each direction.
+const MAX_HISTORY_LEN: usize = 100;
+
+#[derive(Resource, Default, Debug)]
+pub struct FocusHistory {
+    back: Vec<Entity>,
+    forward: Vec<Entity>,
+    current: Option<Entity>,
+}
+
+impl FocusHistory {
+    /// Remembers a newly focused system. Forgets the forward history like a web browser.
+    fn visit(&mut self, entity: Entity) {
+        if self.current == Some(entity) {
+            return;
+        }
+        if let Some(current) = self.current.replace(entity) {
+            self.back.push(current);
+            if self.back.len() > MAX_HISTORY_LEN {
+                self.back.remove(0);
+            }
+        }
+        self.forward.clear();
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Steps back to the last system that still exists and returns it.
+    pub fn go_back(&mut self, exists: impl Fn(Entity) -> bool) -> Option<Entity> {
+        Self::step(&mut self.back, &mut self.forward, &mut self.current, exists)
+    }
+
+    /// Steps forward to the next system that still exists and returns it.
+    pub fn go_forward(&mut self, exists: impl Fn(Entity) -> bool) -> Option<Entity> {
+        Self::step(&mut self.forward, &mut self.back, &mut self.current, exists)
+    }
+
+    fn step(
+        from: &mut Vec<Entity>,
+        to: &mut Vec<Entity>,
+        current: &mut Option<Entity>,
+        exists: impl Fn(Entity) -> bool,
+    ) -> Option<Entity> {
+        while let Some(entity) = from.pop() {
+            if exists(entity) {
+                if let Some(current) = current.replace(entity) {
+                    to.push(current);
+                }
+                return Some(entity);
+            }
+        }
+        None
+    }
+}
+
+pub fn record_focus_history(
+    focused_system: Res<FocusedSystem>,
+    mut focus_history: ResMut<FocusHistory>,
+    system_query: Query<(), With<crate::components::System>>,
+) {
+    if focused_system.is_changed() && system_query.contains(**focused_system) {
+        focus_history.visit(**focused_system);
+    }
+}
+
+pub fn navigate_back(
+    mut focus_history: ResMut<FocusHistory>,
+    mut focused_system: ResMut<FocusedSystem>,
+    system_query: Query<(), With<crate::components::System>>,
+) {
+    if let Some(entity) = focus_history.go_back(|e| system_query.contains(e)) {
+        **focused_system = entity;
+    }
+}
+
+pub fn navigate_forward(
+    mut focus_history: ResMut<FocusHistory>,
+    mut focused_system: ResMut<FocusedSystem>,
+    system_query: Query<(), With<crate::components::System>>,
+) {
+    if let Some(entity) = focus_history.go_forward(|e| system_query.contains(e)) {
+        **focused_system = entity;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn e(index: u32) -> Entity {
+        Entity::from_raw(index)
+    }
+
+    #[test]
+    fn back_and_forward() {
+        let mut history = FocusHistory::default();
+        history.visit(e(1));
+        history.visit(e(2));
+        history.visit(e(2));
+        history.visit(e(3));
+
+        assert!(!history.can_go_forward());
+        assert_eq!(history.go_back(|_| true), Some(e(2)));
+        assert_eq!(history.go_back(|_| true), Some(e(1)));
+        assert_eq!(history.go_back(|_| true), None);
+        assert_eq!(history.go_forward(|_| true), Some(e(2)));
+        assert_eq!(history.go_forward(|_| true), Some(e(3)));
+        assert!(!history.can_go_forward());
+    }
+
+    #[test]
+    fn visiting_forgets_the_forward_history() {
+        let mut history = FocusHistory::default();
+        history.visit(e(1));
+        history.visit(e(2));
+        history.go_back(|_| true);
+        history.visit(e(3));
+
+        assert!(!history.can_go_forward());
+        assert_eq!(history.go_back(|_| true), Some(e(1)));
+    }
+
+    #[test]
+    fn removed_systems_are_skipped() {
+        let mut history = FocusHistory::default();
+        for index in 1..=3 {
+            history.visit(e(index));
+        }
+
+        assert_eq!(history.go_back(|entity| entity != e(2)), Some(e(1)));
+        assert!(!history.can_go_back());
+        assert_eq!(history.go_forward(|_| true), Some(e(3)));
+    }
+
+    #[test]
+    fn the_back_history_is_limited() {
+        let mut history = FocusHistory::default();
+        for index in 0..MAX_HISTORY_LEN as u32 + 10 {
+            history.visit(e(index));
+        }
+
+        let mut steps = 0;
+        while history.go_back(|_| true).is_some() {
+            steps += 1;
+        }
+        assert_eq!(steps, MAX_HISTORY_LEN);
+    }
+}
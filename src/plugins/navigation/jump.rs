@@ -0,0 +1,100 @@
+//! Jumping to an element: focuses its system, flies the camera to it and selects it.
+use super::CameraAnimation;
+use crate::components::*;
+use crate::plugins::mouse_interaction::{do_deselect_all, PickSelection};
+use crate::resources::{FocusedSystem, Zoom};
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::window::PrimaryWindow;
+
+/// Size that is framed for elements without a bounding box.
+const DEFAULT_FRAME_HALF_SIZE: f32 = 50.0;
+
+/// Fraction of the smaller window dimension that the element fills after jumping to it.
+const JUMP_FILL: f32 = 0.5;
+
+#[derive(Event, Copy, Clone, Debug)]
+pub struct JumpToElement(pub Entity);
+
+/// The system that contains the element or the element itself if it's a system.
+pub fn containing_system(
+    entity: Entity,
+    system_query: &Query<(), With<crate::components::System>>,
+    parent_query: &Query<&Parent>,
+) -> Option<Entity> {
+    std::iter::once(entity)
+        .chain(parent_query.iter_ancestors(entity))
+        .find(|e| system_query.contains(*e))
+}
+
+/// Names of the systems that contain the element from the outermost to the innermost, e.g.
+/// "Factory › Boiler".
+pub fn hierarchy_path(
+    entity: Entity,
+    system_query: &Query<(), With<crate::components::System>>,
+    parent_query: &Query<&Parent>,
+    name_query: &Query<&Name>,
+) -> String {
+    let mut path = parent_query
+        .iter_ancestors(entity)
+        .filter(|e| system_query.contains(*e))
+        .filter_map(|e| name_query.get(e).ok())
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    path.reverse();
+    path.join(" › ")
+}
+
+pub fn jump_to_element(
+    mut jump_reader: EventReader<JumpToElement>,
+    system_query: Query<(), With<crate::components::System>>,
+    parent_query: Query<&Parent>,
+    geometry_query: Query<(&GlobalTransform, Option<&Aabb>), With<SystemElement>>,
+    mut pick_selection_query: Query<&mut PickSelection>,
+    mut focused_system: ResMut<FocusedSystem>,
+    camera_query: Query<&Transform, With<Camera>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    zoom: Res<Zoom>,
+    mut camera_animation: ResMut<CameraAnimation>,
+) {
+    let Some(JumpToElement(entity)) = jump_reader.read().last().copied() else {
+        return;
+    };
+
+    if let Some(system) = containing_system(entity, &system_query, &parent_query) {
+        **focused_system = system;
+    }
+
+    if let (Ok((global_transform, aabb)), Ok(window)) =
+        (geometry_query.get(entity), window_query.get_single())
+    {
+        let (center, half_size) = match aabb {
+            Some(aabb) => (
+                global_transform
+                    .transform_point(aabb.center.into())
+                    .truncate(),
+                Vec3::from(aabb.half_extents).truncate()
+                    * global_transform.compute_transform().scale.truncate(),
+            ),
+            None => (
+                global_transform.translation().truncate(),
+                Vec2::splat(DEFAULT_FRAME_HALF_SIZE * **zoom),
+            ),
+        };
+
+        camera_animation.start(
+            **focused_system,
+            camera_query.single(),
+            &zoom,
+            Vec2::new(window.width(), window.height()),
+            center,
+            half_size,
+            JUMP_FILL,
+        );
+    }
+
+    do_deselect_all(&mut pick_selection_query);
+    if let Ok(mut pick_selection) = pick_selection_query.get_mut(entity) {
+        pick_selection.is_selected = true;
+    }
+}
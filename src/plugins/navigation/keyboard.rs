@@ -0,0 +1,154 @@
+//! Moving the selection with the keyboard: Tab cycles through the siblings of the selected
+//! element, ←/→ follow its flows upstream and downstream and ↑/↓ go to the parent system and into
+//! the selected system.
+use crate::components::*;
+use crate::plugins::mouse_interaction::PickSelection;
+use crate::resources::FocusedSystem;
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+/// The flow's end points: the interface if there is one, otherwise the connected element.
+fn flow_ends(
+    flow: Entity,
+    flow_query: &Query<(
+        Entity,
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>,
+) -> Option<(Entity, Entity)> {
+    let (_, start, end, start_interface, end_interface) = flow_query.get(flow).ok()?;
+    Some((
+        start_interface.map_or(start.target, |i| i.target),
+        end_interface.map_or(end.target, |i| i.target),
+    ))
+}
+
+pub fn navigate_selection_with_keyboard(
+    mut egui_contexts: EguiContexts,
+    input: Res<ButtonInput<KeyCode>>,
+    element_query: Query<(Entity, &Transform, Option<&Parent>), With<SystemElement>>,
+    children_query: Query<&Children>,
+    system_query: Query<(), With<crate::components::System>>,
+    flow_query: Query<(
+        Entity,
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>,
+    mut pick_selection_query: Query<(Entity, &mut PickSelection)>,
+    focused_system: Res<FocusedSystem>,
+) {
+    if egui_contexts.ctx_mut().wants_keyboard_input()
+        || input.any_pressed([
+            KeyCode::AltLeft,
+            KeyCode::AltRight,
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ])
+    {
+        return;
+    }
+
+    let selected = pick_selection_query
+        .iter()
+        .find(|(entity, selection)| selection.is_selected && element_query.contains(*entity))
+        .map(|(entity, _)| entity);
+
+    let children_of = |system: Entity| {
+        let mut children = children_query
+            .get(system)
+            .map(|children| {
+                children
+                    .iter()
+                    .filter_map(|child| element_query.get(*child).ok())
+                    .map(|(entity, transform, _)| (entity, transform.translation.truncate()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        // Counterclockwise from the right so that Tab goes around the parent.
+        children.sort_by(|(_, a), (_, b)| a.to_angle().total_cmp(&b.to_angle()));
+        children
+            .into_iter()
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>()
+    };
+
+    let target = if input.just_pressed(KeyCode::Tab) {
+        let backwards = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+        match selected {
+            Some(selected) => {
+                let parent = element_query
+                    .get(selected)
+                    .ok()
+                    .and_then(|(_, _, parent)| parent.map(|p| p.get()));
+                let siblings = match parent {
+                    Some(parent) => children_of(parent),
+                    None => element_query
+                        .iter()
+                        .filter(|(_, _, parent)| parent.is_none())
+                        .map(|(entity, ..)| entity)
+                        .collect(),
+                };
+                siblings.iter().position(|e| *e == selected).map(|index| {
+                    let len = siblings.len();
+                    let next = if backwards {
+                        (index + len - 1) % len
+                    } else {
+                        (index + 1) % len
+                    };
+                    siblings[next]
+                })
+            }
+            None => children_of(**focused_system).first().copied(),
+        }
+    } else if input.just_pressed(KeyCode::ArrowRight) || input.just_pressed(KeyCode::ArrowLeft) {
+        let downstream = input.just_pressed(KeyCode::ArrowRight);
+        selected.and_then(|selected| {
+            if let Some((start, end)) = flow_ends(selected, &flow_query) {
+                Some(if downstream { end } else { start })
+            } else {
+                flow_query
+                    .iter()
+                    .filter(|(flow, ..)| {
+                        flow_ends(*flow, &flow_query).is_some_and(|(start, end)| {
+                            if downstream {
+                                start == selected
+                            } else {
+                                end == selected
+                            }
+                        })
+                    })
+                    .map(|(flow, ..)| flow)
+                    .min()
+            }
+        })
+    } else if input.just_pressed(KeyCode::ArrowUp) {
+        selected
+            .and_then(|selected| element_query.get(selected).ok())
+            .and_then(|(_, _, parent)| parent.map(|p| p.get()))
+            .filter(|parent| system_query.contains(*parent))
+    } else if input.just_pressed(KeyCode::ArrowDown) {
+        selected
+            .filter(|selected| system_query.contains(*selected))
+            .and_then(|selected| children_of(selected).first().copied())
+    } else {
+        None
+    };
+
+    let Some(target) = target else {
+        return;
+    };
+
+    for (entity, mut selection) in &mut pick_selection_query {
+        let is_selected = entity == target;
+        if selection.is_selected != is_selected {
+            selection.is_selected = is_selected;
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Overview of the whole model in a corner of the canvas.
+use super::CameraAnimation;
+use crate::components::*;
+use crate::plugins::theme::Themes;
+use crate::resources::{FocusedSystem, Zoom};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContexts};
+
+/// Size of the minimap in logical pixels.
+const MINIMAP_SIZE: egui::Vec2 = egui::vec2(220.0, 160.0);
+
+/// Distance of the minimap from the corner of the canvas.
+const MINIMAP_MARGIN: f32 = 10.0;
+
+/// Width of the lines of systems and flows in the minimap.
+const MINIMAP_LINE_WIDTH: f32 = 1.0;
+
+/// Width of the rectangle that shows the camera viewport in the minimap.
+const MINIMAP_VIEWPORT_LINE_WIDTH: f32 = 1.5;
+
+/// Elements that are nested deeper than this below the focused system are not drawn.
+const MINIMAP_DEPTH_BELOW_FOCUS: u16 = 1;
+
+#[derive(Resource)]
+pub struct Minimap {
+    pub open: bool,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self { open: true }
+    }
+}
+
+/// Maps between unzoomed world coordinates and the minimap.
+struct MinimapTransform {
+    world_center: Vec2,
+    scale: f32,
+    rect: egui::Rect,
+}
+
+impl MinimapTransform {
+    fn new(world_min: Vec2, world_max: Vec2, rect: egui::Rect) -> Self {
+        let world_size = (world_max - world_min).max(Vec2::ONE);
+        Self {
+            world_center: (world_min + world_max) * 0.5,
+            scale: (rect.width() / world_size.x).min(rect.height() / world_size.y),
+            rect,
+        }
+    }
+
+    fn to_map(&self, world: Vec2) -> egui::Pos2 {
+        let offset = (world - self.world_center) * self.scale;
+        self.rect.center() + egui::vec2(offset.x, -offset.y)
+    }
+
+    fn to_world(&self, pos: egui::Pos2) -> Vec2 {
+        let offset = pos - self.rect.center();
+        self.world_center + Vec2::new(offset.x, -offset.y) / self.scale
+    }
+}
+
+fn color32(color: Color) -> egui::Color32 {
+    let [r, g, b, _] = color.as_rgba_u8();
+    egui::Color32::from_rgb(r, g, b)
+}
+
+pub fn minimap_egui(
+    mut egui_contexts: EguiContexts,
+    minimap: Res<Minimap>,
+    themes: Res<Themes>,
+    focused_system: Res<FocusedSystem>,
+    zoom: Res<Zoom>,
+    system_query: Query<(
+        Entity,
+        &GlobalTransform,
+        &crate::components::System,
+        Option<&NestingLevel>,
+    )>,
+    external_entity_query: Query<(Entity, &GlobalTransform, &NestingLevel), With<ExternalEntity>>,
+    flow_query: Query<(Entity, &GlobalTransform, &FlowCurve, &Flow, &NestingLevel)>,
+    nesting_level_query: Query<&NestingLevel>,
+    parent_query: Query<&Parent>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_animation: ResMut<CameraAnimation>,
+) {
+    if !minimap.open || system_query.is_empty() {
+        return;
+    }
+
+    let theme = themes.current();
+    let zoom = **zoom;
+    let max_nesting_level = NestingLevel::current(**focused_system, &nesting_level_query)
+        .saturating_add(MINIMAP_DEPTH_BELOW_FOCUS);
+    let is_visible = |nesting_level: Option<&NestingLevel>| {
+        nesting_level.map_or(0, |n| **n) <= max_nesting_level
+    };
+    let is_in_focus = |entity: Entity| {
+        entity == **focused_system
+            || parent_query
+                .iter_ancestors(entity)
+                .any(|e| e == **focused_system)
+    };
+
+    let systems = system_query
+        .iter()
+        .filter(|(_, _, _, nesting_level)| is_visible(*nesting_level))
+        .map(|(entity, transform, system, _)| {
+            (
+                entity,
+                transform.translation().truncate() / zoom,
+                system.radius,
+            )
+        })
+        .collect::<Vec<_>>();
+    let external_entities = external_entity_query
+        .iter()
+        .filter(|(_, _, nesting_level)| is_visible(Some(nesting_level)))
+        .map(|(entity, transform, _)| (entity, transform.translation().truncate() / zoom))
+        .collect::<Vec<_>>();
+    let flows = flow_query
+        .iter()
+        .filter(|(.., nesting_level)| is_visible(Some(nesting_level)))
+        .map(|(entity, transform, curve, flow, _)| {
+            let point = |p: Vec2| transform.transform_point(p.extend(0.0)).truncate() / zoom;
+            (
+                entity,
+                point(curve.start),
+                point(curve.end),
+                flow.substance_type,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let (mut world_min, mut world_max) = (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN));
+    for (_, center, radius) in &systems {
+        world_min = world_min.min(*center - *radius);
+        world_max = world_max.max(*center + *radius);
+    }
+    for (_, position) in &external_entities {
+        world_min = world_min.min(*position);
+        world_max = world_max.max(*position);
+    }
+    let padding = (world_max - world_min).max_element() * 0.05;
+    world_min -= padding;
+    world_max += padding;
+
+    let ctx = egui_contexts.ctx_mut();
+    let canvas = ctx.available_rect();
+    let position = canvas.right_bottom() - MINIMAP_SIZE - egui::Vec2::splat(MINIMAP_MARGIN);
+
+    let rgb = |[r, g, b]: [u8; 3]| egui::Color32::from_rgb(r, g, b);
+    let background = rgb(theme.background);
+    let stroke_color = rgb(theme.stroke);
+    let selection_color = rgb(theme.selection);
+    let dimmed = |color: egui::Color32, entity: Entity| {
+        if is_in_focus(entity) {
+            color
+        } else {
+            color.gamma_multiply(0.3)
+        }
+    };
+
+    let mut pan_to = None;
+
+    egui::Area::new(egui::Id::new("Minimap"))
+        .fixed_pos(position)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let (response, painter) =
+                    ui.allocate_painter(MINIMAP_SIZE, egui::Sense::click_and_drag());
+                let rect = response.rect;
+                painter.rect_filled(rect, 2.0, background);
+                let painter = painter.with_clip_rect(rect);
+                let map = MinimapTransform::new(world_min, world_max, rect);
+
+                for (entity, center, radius) in &systems {
+                    let color = if *entity == **focused_system {
+                        selection_color
+                    } else {
+                        dimmed(stroke_color, *entity)
+                    };
+                    painter.circle_stroke(
+                        map.to_map(*center),
+                        (radius * map.scale).max(1.0),
+                        egui::Stroke::new(MINIMAP_LINE_WIDTH, color),
+                    );
+                }
+                for (entity, start, end, substance_type) in &flows {
+                    let color = dimmed(color32(theme.flow_color(*substance_type)), *entity);
+                    painter.line_segment(
+                        [map.to_map(*start), map.to_map(*end)],
+                        egui::Stroke::new(MINIMAP_LINE_WIDTH, color),
+                    );
+                }
+                for (entity, position) in &external_entities {
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(map.to_map(*position), egui::vec2(3.0, 6.0)),
+                        0.0,
+                        dimmed(stroke_color, *entity),
+                    );
+                }
+
+                if let Ok(window) = window_query.get_single() {
+                    let camera = camera_query.single().translation.truncate() / zoom;
+                    let half_size = Vec2::new(window.width(), window.height()) * 0.5 / zoom;
+                    painter.rect_stroke(
+                        egui::Rect::from_two_pos(
+                            map.to_map(camera - half_size),
+                            map.to_map(camera + half_size),
+                        ),
+                        0.0,
+                        egui::Stroke::new(MINIMAP_VIEWPORT_LINE_WIDTH, selection_color),
+                    );
+                }
+
+                if response.clicked() || response.dragged() {
+                    if let Some(pointer) = response.interact_pointer_pos() {
+                        pan_to = Some(map.to_world(pointer));
+                    }
+                }
+            });
+        });
+
+    if let Some(world) = pan_to {
+        camera_animation.cancel();
+        let mut camera_transform = camera_query.single_mut();
+        camera_transform.translation = (world * zoom).extend(camera_transform.translation.z);
+    }
+}
@@ -0,0 +1,90 @@
+//! Finding elements and moving the view to them.
+mod animation;
+mod bookmarks;
+mod breadcrumbs;
+mod fit;
+mod history;
+mod jump;
+mod keyboard;
+mod minimap;
+mod search;
+
+pub use animation::*;
+pub use bookmarks::*;
+pub use breadcrumbs::*;
+pub use fit::*;
+pub use history::*;
+pub use jump::*;
+pub use keyboard::*;
+pub use minimap::*;
+pub use search::*;
+
+use crate::plugins::keybindings::{key_action_just_pressed, KeyAction};
+use crate::plugins::outline::outline_panel_egui;
+use crate::states::AppState;
+use crate::systems::{egui_menu_bar, egui_selected_context};
+use bevy::input::common_conditions::input_just_pressed;
+use bevy::prelude::*;
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<JumpToElement>()
+            .add_event::<FitView>()
+            .init_resource::<CameraBookmarks>()
+            .init_resource::<BookmarkList>()
+            .init_resource::<SearchPanel>()
+            .init_resource::<FocusHistory>()
+            .init_resource::<CameraAnimation>()
+            .init_resource::<Minimap>()
+            .add_systems(
+                Update,
+                (
+                    toggle_search_panel.run_if(key_action_just_pressed(KeyAction::Search)),
+                    search_panel_egui.after(bevy_egui::EguiSet::InitContexts),
+                    breadcrumbs_egui.after(egui_menu_bar),
+                    jump_to_element.after(search_panel_egui),
+                    bookmarks_egui.after(bevy_egui::EguiSet::InitContexts),
+                    fit_model_from_keyboard.run_if(key_action_just_pressed(KeyAction::FitModel)),
+                    fit_selection_from_keyboard
+                        .run_if(key_action_just_pressed(KeyAction::FitSelection)),
+                    fit_focused_system_from_keyboard
+                        .run_if(key_action_just_pressed(KeyAction::FitFocusedSystem)),
+                    navigate_selection_with_keyboard
+                        .run_if(in_state(AppState::Normal))
+                        .after(bevy_egui::EguiSet::InitContexts),
+                    fit_view
+                        .after(fit_model_from_keyboard)
+                        .after(fit_selection_from_keyboard)
+                        .after(fit_focused_system_from_keyboard)
+                        .after(egui_menu_bar),
+                    minimap_egui
+                        .after(egui_selected_context)
+                        .after(outline_panel_egui)
+                        .before(animate_camera),
+                    navigate_back.run_if(
+                        input_just_pressed(MouseButton::Back)
+                            .or_else(key_action_just_pressed(KeyAction::NavigateBack)),
+                    ),
+                    navigate_forward.run_if(
+                        input_just_pressed(MouseButton::Forward)
+                            .or_else(key_action_just_pressed(KeyAction::NavigateForward)),
+                    ),
+                    (record_focus_history, dive_into_focused_system)
+                        .after(jump_to_element)
+                        .after(bookmarks_egui)
+                        .after(fit_view)
+                        .after(breadcrumbs_egui)
+                        .after(navigate_back)
+                        .after(navigate_forward),
+                    animate_camera
+                        .after(dive_into_focused_system)
+                        .before(crate::ZoomSet),
+                    cancel_camera_animation
+                        .after(dive_into_focused_system)
+                        .before(animate_camera),
+                ),
+            );
+    }
+}
@@ -0,0 +1,187 @@
+//! Search panel that finds elements by their name, description, protocol, unit, parameters and
+//! attributes across all nesting levels.
+use super::{hierarchy_path, JumpToElement};
+use crate::components::*;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+/// State of the search panel.
+#[derive(Resource, Default)]
+pub struct SearchPanel {
+    pub open: bool,
+    pub query: String,
+    /// Moves the keyboard focus to the search field in the next frame.
+    request_focus: bool,
+}
+
+/// An element that matches the search query.
+#[derive(Clone, Debug)]
+pub struct SearchMatch {
+    pub entity: Entity,
+    pub name: String,
+    pub element: SystemElement,
+    pub path: String,
+    /// What matched, e.g. "Protocol: HTTP".
+    pub matched: String,
+}
+
+/// Toggles the search panel and focuses its search field.
+pub fn toggle_search_panel(mut search_panel: ResMut<SearchPanel>) {
+    search_panel.open = !search_panel.open;
+    search_panel.request_focus = search_panel.open;
+}
+
+fn matching_parameter(parameters: &[Parameter], query: &str) -> Option<String> {
+    parameters
+        .iter()
+        .find(|p| {
+            p.name.to_lowercase().contains(query)
+                || p.value.to_string().to_lowercase().contains(query)
+        })
+        .map(|p| format!("{}: {}", p.name, p.value))
+}
+
+pub fn search_elements(
+    query: &str,
+    element_query: &Query<(Entity, &Name, &ElementDescription, &SystemElement)>,
+    interface_query: &Query<&Interface>,
+    flow_query: &Query<&Flow>,
+    system_query: &Query<&crate::components::System>,
+    external_entity_query: &Query<&ExternalEntity>,
+) -> Vec<(Entity, String)> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return vec![];
+    }
+
+    let mut matches = element_query
+        .iter()
+        .filter_map(|(entity, name, description, _)| {
+            if name.to_lowercase().contains(&query) {
+                return Some((entity, String::new()));
+            }
+            if description.text.to_lowercase().contains(&query) {
+                return Some((entity, format!("Description: {}", description.text)));
+            }
+
+            let matched = if let Ok(interface) = interface_query.get(entity) {
+                Some(&interface.protocol)
+                    .filter(|p| p.to_lowercase().contains(&query))
+                    .map(|p| format!("Protocol: {}", p))
+                    .or_else(|| matching_parameter(&interface.attributes, &query))
+            } else if let Ok(flow) = flow_query.get(entity) {
+                Some(&flow.unit)
+                    .filter(|u| u.to_lowercase().contains(&query))
+                    .map(|u| format!("Unit: {}", u))
+                    .or_else(|| {
+                        Some(&flow.substance_sub_type)
+                            .filter(|s| s.to_lowercase().contains(&query))
+                            .map(|s| format!("Substance: {}", s))
+                    })
+                    .or_else(|| matching_parameter(&flow.parameters, &query))
+            } else if let Ok(system) = system_query.get(entity) {
+                matching_parameter(&system.attributes, &query)
+            } else if let Ok(external_entity) = external_entity_query.get(entity) {
+                matching_parameter(&external_entity.attributes, &query)
+            } else {
+                None
+            };
+
+            matched.map(|matched| (entity, matched))
+        })
+        .collect::<Vec<_>>();
+
+    matches.sort_by_key(|(_, matched)| !matched.is_empty());
+    matches
+}
+
+/// Shows the search panel. Selecting a result jumps to the element.
+pub fn search_panel_egui(
+    mut egui_contexts: EguiContexts,
+    mut search_panel: ResMut<SearchPanel>,
+    element_query: Query<(Entity, &Name, &ElementDescription, &SystemElement)>,
+    interface_query: Query<&Interface>,
+    flow_query: Query<&Flow>,
+    system_query: Query<&crate::components::System>,
+    external_entity_query: Query<&ExternalEntity>,
+    is_system_query: Query<(), With<crate::components::System>>,
+    parent_query: Query<&Parent>,
+    name_query: Query<&Name>,
+    mut jump_writer: EventWriter<JumpToElement>,
+) {
+    if !search_panel.open {
+        return;
+    }
+
+    let results = search_elements(
+        &search_panel.query,
+        &element_query,
+        &interface_query,
+        &flow_query,
+        &system_query,
+        &external_entity_query,
+    )
+    .into_iter()
+    .filter_map(|(entity, matched)| {
+        let (_, name, _, element) = element_query.get(entity).ok()?;
+        Some(SearchMatch {
+            entity,
+            name: name.to_string(),
+            element: *element,
+            path: hierarchy_path(entity, &is_system_query, &parent_query, &name_query),
+            matched,
+        })
+    })
+    .collect::<Vec<_>>();
+
+    let mut open = search_panel.open;
+    let mut selected = None;
+
+    egui::Window::new("Search")
+        .open(&mut open)
+        .default_width(350.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut search_panel.query)
+                    .hint_text("Name, description, protocol, unit, parameter...")
+                    .desired_width(f32::INFINITY),
+            );
+            if search_panel.request_focus {
+                response.request_focus();
+                search_panel.request_focus = false;
+            }
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                selected = results.first().map(|r| r.entity);
+            }
+
+            if !search_panel.query.trim().is_empty() {
+                ui.label(format!("{} matches", results.len()));
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    for result in &results {
+                        ui.separator();
+                        if ui
+                            .link(format!("{} ({})", result.name, result.element))
+                            .clicked()
+                        {
+                            selected = Some(result.entity);
+                        }
+                        if !result.path.is_empty() {
+                            ui.weak(&result.path);
+                        }
+                        if !result.matched.is_empty() {
+                            ui.label(&result.matched);
+                        }
+                    }
+                });
+        });
+
+    search_panel.open = open;
+
+    if let Some(entity) = selected {
+        jump_writer.send(JumpToElement(entity));
+    }
+}
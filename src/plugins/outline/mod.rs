@@ -0,0 +1,19 @@
+//! Collapsible tree of the model hierarchy next to the canvas.
+mod panel;
+mod tree;
+
+pub use panel::*;
+pub use tree::*;
+
+use bevy::prelude::*;
+
+pub struct OutlinePlugin;
+
+impl Plugin for OutlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OutlinePanel>().add_systems(
+            Update,
+            outline_panel_egui.after(crate::plugins::navigation::breadcrumbs_egui),
+        );
+    }
+}
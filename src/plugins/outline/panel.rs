@@ -0,0 +1,411 @@
+//! Side panel with the collapsible outline of the model.
+use super::{build_system_node, icon, OutlineNode};
+use crate::components::*;
+use crate::plugins::layout::free_position;
+use crate::plugins::mouse_interaction::PickSelection;
+use crate::plugins::navigation::JumpToElement;
+use crate::resources::Zoom;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_egui::egui::collapsing_header::CollapsingState;
+use bevy_egui::{egui, EguiContexts};
+
+/// State of the outline panel.
+#[derive(Resource, Default)]
+pub struct OutlinePanel {
+    pub open: bool,
+    /// The element that is renamed in place.
+    renaming: Option<Renaming>,
+    /// The element that was last selected. Its ancestors are expanded when it changes.
+    last_selected: Option<Entity>,
+}
+
+/// An element that is renamed in place.
+struct Renaming {
+    entity: Entity,
+    name: String,
+    /// Whether the text field still has to take the keyboard focus.
+    is_started: bool,
+}
+
+impl Renaming {
+    fn new(node: &OutlineNode) -> Self {
+        Self {
+            entity: node.entity,
+            name: node.name.clone(),
+            is_started: true,
+        }
+    }
+}
+
+/// Payload of dragging a node in the outline.
+struct OutlineDrag(Entity);
+
+/// Actions that the user triggered in the outline this frame.
+#[derive(Default)]
+struct OutlineActions {
+    select: Option<Entity>,
+    jump: Option<Entity>,
+    rename: Option<(Entity, String)>,
+    reparent: Option<(Entity, Entity)>,
+}
+
+/// Decides which nodes can be dragged onto which systems so that the outline explains the limits
+/// while dragging instead of after the drop.
+struct ReparentRules<'a> {
+    /// Whether a node can be dragged at all.
+    movable: &'a dyn Fn(Entity) -> Result<(), String>,
+    /// Whether the dragged node can be dropped onto a system.
+    target: &'a dyn Fn(Entity, Entity) -> Result<(), String>,
+}
+
+fn node_egui(
+    ui: &mut egui::Ui,
+    node: &OutlineNode,
+    selected: &HashSet<Entity>,
+    reveal: &[Entity],
+    renaming: &mut Option<Renaming>,
+    rules: &ReparentRules,
+    actions: &mut OutlineActions,
+) {
+    let mut header = |ui: &mut egui::Ui, renaming: &mut Option<Renaming>| {
+        if let Some(rename) = renaming {
+            if rename.entity == node.entity {
+                let response = ui.text_edit_singleline(&mut rename.name);
+                if rename.is_started {
+                    response.request_focus();
+                    rename.is_started = false;
+                }
+                if response.lost_focus() {
+                    if !ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        actions.rename = Some((rename.entity, rename.name.clone()));
+                    }
+                    *renaming = None;
+                }
+                return;
+            }
+        }
+
+        let is_selected = selected.contains(&node.entity);
+        let mut response = ui
+            .selectable_label(is_selected, format!("{} {}", icon(node.element), node.name))
+            .interact(egui::Sense::drag());
+
+        if is_selected && reveal.last() == Some(&node.entity) {
+            response.scroll_to_me(Some(egui::Align::Center));
+        }
+
+        if node.element == SystemElement::System {
+            match (rules.movable)(node.entity) {
+                Ok(()) => response.dnd_set_drag_payload(OutlineDrag(node.entity)),
+                Err(reason) => response = response.on_hover_text(reason),
+            }
+
+            if let Some(dragged) = response.dnd_hover_payload::<OutlineDrag>() {
+                if let Err(reason) = (rules.target)(dragged.0, node.entity) {
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::NoDrop);
+                    egui::show_tooltip_text(ui.ctx(), egui::Id::new("Outline Drop"), reason);
+                }
+            }
+            if let Some(dragged) = response.dnd_release_payload::<OutlineDrag>() {
+                if (rules.target)(dragged.0, node.entity).is_ok() {
+                    actions.reparent = Some((dragged.0, node.entity));
+                }
+            }
+        }
+
+        if response.double_clicked() {
+            *renaming = Some(Renaming::new(node));
+        } else if response.clicked() {
+            actions.select = Some(node.entity);
+        }
+
+        response.context_menu(|ui| {
+            if ui.button("Rename").clicked() {
+                *renaming = Some(Renaming::new(node));
+                ui.close_menu();
+            }
+            if ui.button("Show on Canvas").clicked() {
+                actions.jump = Some(node.entity);
+                ui.close_menu();
+            }
+        });
+    };
+
+    if node.children.is_empty() {
+        ui.horizontal(|ui| {
+            // Aligns leaves with the headers of collapsible nodes.
+            ui.add_space(ui.spacing().indent);
+            header(ui, renaming);
+        });
+        return;
+    }
+
+    let id = ui.make_persistent_id(("Outline", node.entity));
+    let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, false);
+    if reveal.contains(&node.entity) && reveal.last() != Some(&node.entity) {
+        state.set_open(true);
+    }
+    state.show_header(ui, |ui| header(ui, renaming)).body(|ui| {
+        for child in &node.children {
+            node_egui(ui, child, selected, reveal, renaming, rules, actions);
+        }
+    });
+}
+
+/// What's needed to place a moved subsystem inside its new parent.
+#[derive(SystemParam)]
+pub struct SubsystemPlacement<'w, 's> {
+    zoom: Res<'w, Zoom>,
+    system_query: Query<'w, 's, &'static crate::components::System>,
+    position_query: Query<'w, 's, (&'static mut Transform, &'static mut InitialPosition)>,
+}
+
+impl SubsystemPlacement<'_, '_> {
+    /// Moves `subsystem` to free space inside `target` next to the `siblings` that are already
+    /// there.
+    fn place(&mut self, subsystem: Entity, target: Entity, siblings: &[Entity]) {
+        let radius_of = |entity: Entity| self.system_query.get(entity).map_or(0.0, |s| s.radius);
+        let occupied = siblings
+            .iter()
+            .filter_map(|sibling| {
+                self.position_query
+                    .get(*sibling)
+                    .ok()
+                    .map(|(_, position)| (**position, radius_of(*sibling)))
+            })
+            .collect::<Vec<_>>();
+        let position = free_position(radius_of(target), radius_of(subsystem), &occupied);
+
+        let zoom = **self.zoom;
+        if let Ok((mut transform, mut initial_position)) = self.position_query.get_mut(subsystem) {
+            **initial_position = position;
+            transform.translation = (position * zoom).extend(transform.translation.z);
+        }
+    }
+}
+
+/// Subsystems that are connected to an interaction themselves or through their interface.
+fn interacting_subsystems(
+    parent_query: &Query<&Parent>,
+    flow_connection_query: &Query<(
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>,
+) -> HashSet<Entity> {
+    let mut interacting = HashSet::new();
+    for (start, end, start_interface, end_interface) in flow_connection_query {
+        let targets = [
+            Some(start.target),
+            Some(end.target),
+            start_interface.map(|c| c.target),
+            end_interface.map(|c| c.target),
+        ];
+        for target in targets.into_iter().flatten() {
+            interacting.insert(target);
+            if let Ok(parent) = parent_query.get(target) {
+                interacting.insert(parent.get());
+            }
+        }
+    }
+    interacting
+}
+
+/// Checks that a node can be moved at all. Only subsystems without interactions can be moved so
+/// that the interactions don't have to change.
+fn validate_movable(
+    dragged: Entity,
+    subsystem_query: &Query<(Entity, &Subsystem)>,
+    interface_subsystem_query: &Query<(), With<InterfaceSubsystem>>,
+    interacting: &HashSet<Entity>,
+) -> Result<(), String> {
+    if !subsystem_query.contains(dragged) {
+        return Err("Only subsystems can be moved".to_string());
+    }
+    if interface_subsystem_query.contains(dragged) {
+        return Err("Interface subsystems belong to their interface".to_string());
+    }
+    if interacting.contains(&dragged) {
+        return Err("Only subsystems without interactions can be moved".to_string());
+    }
+    Ok(())
+}
+
+/// Checks that a movable subsystem can be moved into `target`. It has to stay at the nesting
+/// level of its current parent so that the geometry of the nesting level doesn't change.
+fn validate_target(
+    dragged: Entity,
+    target: Entity,
+    subsystem_query: &Query<(Entity, &Subsystem)>,
+    nesting_level_query: &Query<&NestingLevel>,
+    parent_query: &Query<&Parent>,
+) -> Result<(), String> {
+    let Ok((_, subsystem)) = subsystem_query.get(dragged) else {
+        return Err("Only subsystems can be moved".to_string());
+    };
+    if target == dragged
+        || target == subsystem.parent_system
+        || parent_query.iter_ancestors(target).any(|a| a == dragged)
+    {
+        return Err("The subsystem can't be moved there".to_string());
+    }
+    if NestingLevel::current(target, nesting_level_query)
+        != NestingLevel::current(subsystem.parent_system, nesting_level_query)
+    {
+        return Err(
+            "Subsystems can only be moved into systems at the same nesting level".to_string(),
+        );
+    }
+    Ok(())
+}
+
+pub fn outline_panel_egui(
+    mut commands: Commands,
+    mut egui_contexts: EguiContexts,
+    mut outline_panel: ResMut<OutlinePanel>,
+    root_query: Query<(Entity, &SystemEnvironment), Without<Subsystem>>,
+    element_query: Query<(Entity, &Name, &SystemElement)>,
+    subsystem_query: Query<(Entity, &Subsystem)>,
+    interface_subsystem_query: Query<(), With<InterfaceSubsystem>>,
+    children_query: Query<&Children>,
+    parent_query: Query<&Parent>,
+    nesting_level_query: Query<&NestingLevel>,
+    flow_connection_query: Query<(
+        &FlowStartConnection,
+        &FlowEndConnection,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>,
+    mut pick_selection_query: Query<(Entity, &mut PickSelection)>,
+    mut name_query: Query<&mut Name>,
+    mut placement: SubsystemPlacement,
+    mut jump_writer: EventWriter<JumpToElement>,
+) {
+    if !outline_panel.open {
+        return;
+    }
+
+    let Ok((root_entity, environment)) = root_query.get_single() else {
+        return;
+    };
+
+    let root = build_system_node(
+        root_entity,
+        &element_query,
+        &subsystem_query,
+        &children_query,
+    );
+
+    let selected = pick_selection_query
+        .iter()
+        .filter(|(_, selection)| selection.is_selected)
+        .map(|(entity, _)| entity)
+        .collect::<HashSet<_>>();
+
+    // Expands the ancestors of an element that was selected on the canvas.
+    let single_selected = (selected.len() == 1)
+        .then(|| selected.iter().next().copied())
+        .flatten();
+    let reveal = if single_selected != outline_panel.last_selected {
+        outline_panel.last_selected = single_selected;
+        single_selected
+            .map(|entity| root.path_to(entity))
+            .unwrap_or_default()
+    } else {
+        vec![]
+    };
+
+    let interacting = interacting_subsystems(&parent_query, &flow_connection_query);
+    let movable = |dragged: Entity| {
+        validate_movable(
+            dragged,
+            &subsystem_query,
+            &interface_subsystem_query,
+            &interacting,
+        )
+    };
+    let droppable = |dragged: Entity, target: Entity| {
+        movable(dragged)?;
+        validate_target(
+            dragged,
+            target,
+            &subsystem_query,
+            &nesting_level_query,
+            &parent_query,
+        )
+    };
+    let rules = ReparentRules {
+        movable: &movable,
+        target: &droppable,
+    };
+
+    let mut actions = OutlineActions::default();
+    let panel = &mut *outline_panel;
+
+    egui::SidePanel::left("Outline")
+        .default_width(250.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.heading("Outline");
+            egui::ScrollArea::both()
+                .auto_shrink([false; 2])
+                .show(ui, |ui| {
+                    let environment_name = if environment.name.trim().is_empty() {
+                        "Environment"
+                    } else {
+                        environment.name.as_str()
+                    };
+                    ui.label(format!("🌍 {}", environment_name));
+                    ui.indent("Outline Environment", |ui| {
+                        node_egui(
+                            ui,
+                            &root,
+                            &selected,
+                            &reveal,
+                            &mut panel.renaming,
+                            &rules,
+                            &mut actions,
+                        );
+                    });
+                });
+        });
+
+    if let Some(entity) = actions.select {
+        for (_, mut selection) in &mut pick_selection_query {
+            if selection.is_selected {
+                selection.is_selected = false;
+            }
+        }
+        if let Ok((_, mut selection)) = pick_selection_query.get_mut(entity) {
+            selection.is_selected = true;
+        }
+        outline_panel.last_selected = Some(entity);
+    }
+
+    if let Some(entity) = actions.jump {
+        jump_writer.send(JumpToElement(entity));
+    }
+
+    if let Some((entity, new_name)) = actions.rename {
+        if let Ok(mut name) = name_query.get_mut(entity) {
+            if !new_name.trim().is_empty() {
+                name.set(new_name);
+            }
+        }
+    }
+
+    if let Some((dragged, target)) = actions.reparent {
+        commands.entity(dragged).set_parent(target);
+        commands.entity(dragged).insert(Subsystem {
+            parent_system: target,
+        });
+        let siblings = subsystem_query
+            .iter()
+            .filter(|(_, subsystem)| subsystem.parent_system == target)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        placement.place(dragged, target, &siblings);
+    }
+}
@@ -0,0 +1,79 @@
+//! Builds the tree of the model from `Subsystem::parent_system` and the Bevy hierarchy.
+use crate::components::*;
+use bevy::prelude::*;
+
+#[derive(Clone, Debug)]
+pub struct OutlineNode {
+    pub entity: Entity,
+    pub name: String,
+    pub element: SystemElement,
+    pub children: Vec<OutlineNode>,
+}
+
+impl OutlineNode {
+    /// The path of nodes from this node to the node of `entity`. Empty if it isn't in the tree.
+    pub fn path_to(&self, entity: Entity) -> Vec<Entity> {
+        if self.entity == entity {
+            return vec![entity];
+        }
+        for child in &self.children {
+            let mut path = child.path_to(entity);
+            if !path.is_empty() {
+                path.insert(0, self.entity);
+                return path;
+            }
+        }
+        vec![]
+    }
+}
+
+pub fn icon(element: SystemElement) -> &'static str {
+    match element {
+        SystemElement::System => "○",
+        SystemElement::Interface => "▮",
+        SystemElement::Interaction => "→",
+        SystemElement::ExternalEntity => "□",
+    }
+}
+
+/// Builds the node of a system with its subsystems, interfaces, external entities and flows.
+pub fn build_system_node(
+    system_entity: Entity,
+    element_query: &Query<(Entity, &Name, &SystemElement)>,
+    subsystem_query: &Query<(Entity, &Subsystem)>,
+    children_query: &Query<&Children>,
+) -> OutlineNode {
+    let node = |entity: Entity| {
+        element_query
+            .get(entity)
+            .ok()
+            .map(|(entity, name, element)| OutlineNode {
+                entity,
+                name: name.to_string(),
+                element: *element,
+                children: vec![],
+            })
+    };
+
+    let mut subsystems = subsystem_query
+        .iter()
+        .filter(|(_, subsystem)| subsystem.parent_system == system_entity)
+        .map(|(entity, _)| {
+            build_system_node(entity, element_query, subsystem_query, children_query)
+        })
+        .collect::<Vec<_>>();
+    subsystems.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut others = children_query
+        .get(system_entity)
+        .map(|children| children.iter().filter_map(|c| node(*c)).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|n| n.element != SystemElement::System)
+        .collect::<Vec<_>>();
+    others.sort_by(|a, b| (a.element as u8, &a.name).cmp(&(b.element as u8, &b.name)));
+
+    let mut system_node = node(system_entity).expect("System should have a name");
+    system_node.children = subsystems.into_iter().chain(others).collect();
+    system_node
+}
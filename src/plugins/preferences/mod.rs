@@ -0,0 +1,27 @@
+//! User preferences that replace the compile-time defaults and apply while the app is running.
+mod user_preferences;
+mod window;
+
+pub use user_preferences::*;
+pub use window::*;
+
+use bevy::prelude::*;
+
+pub struct PreferencesPlugin;
+
+impl Plugin for PreferencesPlugin {
+    fn build(&self, app: &mut App) {
+        let preferences = Preferences::load();
+        app.insert_resource(preferences.zoom_limits)
+            .insert_resource(preferences)
+            .init_resource::<PreferencesWindow>()
+            .add_systems(
+                Update,
+                (
+                    preferences_egui.after(bevy_egui::EguiSet::InitContexts),
+                    sync_zoom_limits.before(save_preferences),
+                    save_preferences,
+                ),
+            );
+    }
+}
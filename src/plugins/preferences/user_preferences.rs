@@ -0,0 +1,104 @@
+use crate::components::NestingLevel;
+use crate::constants::{
+    DEFAULT_LINE_WIDTH, FLOW_LENGTH, LABEL_SCALE_VISIBILITY_THRESHOLD, SCALE_VISIBILITY_THRESHOLD,
+    SUBSYSTEM_FULL_SIZE_INTERFACE_COUNT, SUBSYSTEM_SCALING_FACTOR, WHEEL_ZOOM_SPEED,
+};
+use crate::resources::ZoomLimits;
+use crate::utils::{load_user_config, save_user_config};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const PREFERENCES_FILE_NAME: &str = "preferences.json";
+
+/// Values that tune the look and behavior of the canvas. Stored in the user's configuration
+/// directory. The constants of the same names are the defaults.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Width of the lines of system elements at zoom level 1.
+    pub line_width: f32,
+    /// Length of newly created flows at zoom level 1.
+    pub flow_length: f32,
+    /// Size of a subsystem relative to its parent once it has enough interfaces.
+    pub subsystem_scaling_factor: f32,
+    /// Number of interfaces at which a subsystem reaches its full size.
+    pub subsystem_full_size_interface_count: f32,
+    /// Minimum scale of a system element before it's hidden.
+    pub scale_visibility_threshold: f32,
+    /// Minimum scale of a label before it's hidden.
+    pub label_scale_visibility_threshold: f32,
+    /// Zoom factor per pixel scrolled with the mouse wheel.
+    pub wheel_zoom_speed: f32,
+    /// Range that the user can zoom in. Edited in the View menu.
+    pub zoom_limits: ZoomLimits,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            line_width: DEFAULT_LINE_WIDTH,
+            flow_length: FLOW_LENGTH,
+            subsystem_scaling_factor: SUBSYSTEM_SCALING_FACTOR,
+            subsystem_full_size_interface_count: SUBSYSTEM_FULL_SIZE_INTERFACE_COUNT,
+            scale_visibility_threshold: SCALE_VISIBILITY_THRESHOLD,
+            label_scale_visibility_threshold: LABEL_SCALE_VISIBILITY_THRESHOLD,
+            wheel_zoom_speed: WHEEL_ZOOM_SPEED,
+            zoom_limits: ZoomLimits::default(),
+        }
+    }
+}
+
+impl Preferences {
+    pub fn load() -> Self {
+        load_user_config(PREFERENCES_FILE_NAME)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_user_config(PREFERENCES_FILE_NAME, self)
+    }
+
+    /// Scale of elements at the nesting level with the subsystem scaling factor of the
+    /// preferences.
+    pub fn nesting_scale(&self, nesting_level: u16, zoom: f32) -> f32 {
+        NestingLevel::compute_scale(nesting_level, zoom, self.subsystem_scaling_factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_without_zoom_limits_use_the_defaults() {
+        let preferences: Preferences = serde_json::from_str(r#"{"flow_length": 100.0}"#).unwrap();
+
+        assert_eq!(preferences.flow_length, 100.0);
+        assert_eq!(preferences.zoom_limits, ZoomLimits::default());
+    }
+
+    #[test]
+    fn zoom_limits_round_trip() {
+        let preferences = Preferences {
+            zoom_limits: ZoomLimits { min: 0.5, max: 4.0 },
+            ..default()
+        };
+        let json = serde_json::to_string(&preferences).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<Preferences>(&json).unwrap(),
+            preferences
+        );
+    }
+
+    #[test]
+    fn nesting_scale_uses_the_subsystem_scaling_factor() {
+        let preferences = Preferences {
+            subsystem_scaling_factor: 0.5,
+            ..default()
+        };
+
+        assert_eq!(preferences.nesting_scale(0, 0.5), 0.5);
+        assert_eq!(preferences.nesting_scale(2, 2.0), 0.5);
+        assert_eq!(preferences.nesting_scale(1, 4.0), 1.0);
+    }
+}
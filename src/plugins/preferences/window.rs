@@ -0,0 +1,131 @@
+//! Window to change the preferences.
+use super::Preferences;
+use crate::constants::SUBSYSTEM_MIN_SCALING_FACTOR;
+use crate::plugins::theme::ThemeEditor;
+use crate::resources::ZoomLimits;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+#[derive(Resource, Default)]
+pub struct PreferencesWindow {
+    pub open: bool,
+}
+
+fn drag_row(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut f32,
+    speed: f64,
+    range: std::ops::RangeInclusive<f32>,
+) {
+    ui.label(label);
+    ui.add(egui::DragValue::new(value).speed(speed).clamp_range(range));
+    ui.end_row();
+}
+
+pub fn preferences_egui(
+    mut egui_contexts: EguiContexts,
+    mut preferences_window: ResMut<PreferencesWindow>,
+    mut preferences: ResMut<Preferences>,
+    mut theme_editor: ResMut<ThemeEditor>,
+) {
+    if !preferences_window.open {
+        return;
+    }
+
+    // Only mutate the resource on actual changes so everything that depends on it only updates
+    // when needed.
+    let mut edited = preferences.clone();
+
+    egui::Window::new("Preferences")
+        .open(&mut preferences_window.open)
+        .default_width(320.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.heading("Canvas");
+            egui::Grid::new("Canvas Preferences")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    drag_row(ui, "Line Width", &mut edited.line_width, 0.1, 0.5..=10.0);
+                    drag_row(
+                        ui,
+                        "Flow Length",
+                        &mut edited.flow_length,
+                        1.0,
+                        50.0..=1000.0,
+                    );
+                    drag_row(
+                        ui,
+                        "Subsystem Size",
+                        &mut edited.subsystem_scaling_factor,
+                        0.01,
+                        SUBSYSTEM_MIN_SCALING_FACTOR..=0.9,
+                    );
+                    drag_row(
+                        ui,
+                        "Interfaces for Full Size",
+                        &mut edited.subsystem_full_size_interface_count,
+                        1.0,
+                        1.0..=32.0,
+                    );
+                });
+
+            ui.heading("Zoom");
+            egui::Grid::new("Zoom Preferences")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    drag_row(
+                        ui,
+                        "Hide Elements Below Scale",
+                        &mut edited.scale_visibility_threshold,
+                        0.01,
+                        0.0..=1.0,
+                    );
+                    drag_row(
+                        ui,
+                        "Hide Labels Below Scale",
+                        &mut edited.label_scale_visibility_threshold,
+                        0.01,
+                        0.0..=1.0,
+                    );
+                    drag_row(
+                        ui,
+                        "Wheel Zoom Speed",
+                        &mut edited.wheel_zoom_speed,
+                        0.0001,
+                        0.0001..=0.02,
+                    );
+                });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Reset to Defaults").clicked() {
+                    edited = Preferences::default();
+                }
+                if ui.button("Colors...").clicked() {
+                    theme_editor.open = true;
+                }
+            });
+        });
+
+    if edited != *preferences {
+        *preferences = edited;
+    }
+}
+
+/// Keeps the zoom limits that are edited in the View menu and the stored ones in sync.
+pub fn sync_zoom_limits(mut preferences: ResMut<Preferences>, mut zoom_limits: ResMut<ZoomLimits>) {
+    if zoom_limits.is_changed() && preferences.zoom_limits != *zoom_limits {
+        preferences.zoom_limits = *zoom_limits;
+    } else if preferences.is_changed() && preferences.zoom_limits != *zoom_limits {
+        *zoom_limits = preferences.zoom_limits;
+    }
+}
+
+/// Persists the preferences whenever they are changed in the UI.
+pub fn save_preferences(preferences: Res<Preferences>) {
+    if preferences.is_changed() && !preferences.is_added() {
+        if let Err(error) = preferences.save() {
+            warn!("Failed to save preferences: {}", error);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::components::*;
+use crate::resources::{Protocol, Protocols};
+use bevy::prelude::*;
+
+/// A protocol mismatch of an interaction or an interface.
+#[derive(Clone, Debug)]
+pub struct ProtocolProblem {
+    /// The interaction or interface the problem is reported at.
+    pub element: Entity,
+    /// The other elements that are involved, e.g. the interfaces at the ends of an interaction.
+    pub involved: Vec<Entity>,
+    pub message: String,
+}
+
+/// All protocol problems of the model. Updated whenever interfaces, interactions or the protocol
+/// registry change.
+#[derive(Resource, Default, Deref)]
+pub struct ProtocolProblems(Vec<ProtocolProblem>);
+
+impl ProtocolProblems {
+    /// Problems that the element is reported at or involved in.
+    pub fn for_element(&self, entity: Entity) -> impl Iterator<Item = &ProtocolProblem> {
+        self.iter()
+            .filter(move |p| p.element == entity || p.involved.contains(&entity))
+    }
+}
+
+pub fn check_protocols(
+    changed_query: Query<
+        (),
+        Or<(
+            Changed<Flow>,
+            Changed<Interface>,
+            Changed<Name>,
+            Changed<FlowStartInterfaceConnection>,
+            Changed<FlowEndInterfaceConnection>,
+        )>,
+    >,
+    mut removed_flows: RemovedComponents<Flow>,
+    mut removed_interfaces: RemovedComponents<Interface>,
+    mut removed_start_connections: RemovedComponents<FlowStartInterfaceConnection>,
+    mut removed_end_connections: RemovedComponents<FlowEndInterfaceConnection>,
+    protocols: Res<Protocols>,
+    flow_query: Query<(
+        Entity,
+        &Flow,
+        Option<&FlowStartInterfaceConnection>,
+        Option<&FlowEndInterfaceConnection>,
+    )>,
+    interface_query: Query<(Entity, &Interface)>,
+    name_query: Query<&Name>,
+    mut protocol_problems: ResMut<ProtocolProblems>,
+) {
+    let removed = removed_flows.read().count()
+        + removed_interfaces.read().count()
+        + removed_start_connections.read().count()
+        + removed_end_connections.read().count();
+    if changed_query.is_empty() && removed == 0 && !protocols.is_changed() {
+        return;
+    }
+
+    let name = |entity: Entity| {
+        name_query
+            .get(entity)
+            .map(|name| name.to_string())
+            .unwrap_or_default()
+    };
+
+    let mut problems = vec![];
+
+    for (entity, interface) in &interface_query {
+        if !interface.protocol.trim().is_empty()
+            && protocols
+                .find(&interface.protocol, &interface.protocol_version)
+                .is_none()
+        {
+            problems.push(ProtocolProblem {
+                element: entity,
+                involved: vec![],
+                message: format!(
+                    "Interface \"{}\" uses the unknown protocol \"{}\"",
+                    name(entity),
+                    interface.protocol.trim()
+                ),
+            });
+        }
+    }
+
+    let interface_protocol = |connection: Option<Entity>| {
+        let entity = connection?;
+        let (_, interface) = interface_query.get(entity).ok()?;
+        let protocol = protocols.find(&interface.protocol, &interface.protocol_version)?;
+        Some((entity, protocol))
+    };
+
+    for (entity, flow, start_connection, end_connection) in &flow_query {
+        let start = interface_protocol(start_connection.map(|c| c.target));
+        let end = interface_protocol(end_connection.map(|c| c.target));
+
+        for (interface, protocol) in start.iter().chain(end.iter()) {
+            if protocol.substance_type != flow.substance_type {
+                problems.push(ProtocolProblem {
+                    element: entity,
+                    involved: vec![*interface],
+                    message: format!(
+                        "Interaction \"{}\" carries {:?} but protocol \"{}\" of interface \"{}\" carries {:?}",
+                        name(entity),
+                        flow.substance_type,
+                        protocol.label(),
+                        name(*interface),
+                        protocol.substance_type
+                    ),
+                });
+            }
+        }
+
+        if let (Some((start_interface, start)), Some((end_interface, end))) = (start, end) {
+            if !Protocol::is_compatible_with(start, end) {
+                problems.push(ProtocolProblem {
+                    element: entity,
+                    involved: vec![start_interface, end_interface],
+                    message: format!(
+                        "Interaction \"{}\" connects the incompatible protocols \"{}\" and \"{}\"",
+                        name(entity),
+                        start.label(),
+                        end.label()
+                    ),
+                });
+            }
+        }
+    }
+
+    problems.sort_by(|a, b| a.message.cmp(&b.message));
+    protocol_problems.0 = problems;
+}
@@ -0,0 +1,24 @@
+//! Checks that the interfaces at both ends of an interaction use compatible protocols.
+mod compatibility;
+mod problems;
+
+pub use compatibility::*;
+pub use problems::*;
+
+use bevy::prelude::*;
+
+pub struct ProtocolsPlugin;
+
+impl Plugin for ProtocolsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProtocolProblems>()
+            .init_resource::<ProblemsList>()
+            .add_systems(
+                Update,
+                (
+                    check_protocols,
+                    problems_egui.after(bevy_egui::EguiSet::InitContexts),
+                ),
+            );
+    }
+}
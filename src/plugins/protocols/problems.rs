@@ -0,0 +1,54 @@
+//! Window that lists all problems of the model.
+use super::ProtocolProblems;
+use crate::plugins::mouse_interaction::{do_deselect_all, PickSelection};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+#[derive(Resource, Default)]
+pub struct ProblemsList {
+    pub open: bool,
+}
+
+/// Lists the protocol problems. Clicking a problem selects the element it is reported at.
+pub fn problems_egui(
+    mut egui_contexts: EguiContexts,
+    mut problems_list: ResMut<ProblemsList>,
+    protocol_problems: Res<ProtocolProblems>,
+    mut pick_selection_query: Query<&mut PickSelection>,
+) {
+    if !problems_list.open {
+        return;
+    }
+
+    let mut selected = None;
+
+    egui::Window::new("Problems")
+        .open(&mut problems_list.open)
+        .default_width(400.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            if protocol_problems.is_empty() {
+                ui.label("No problems found.");
+                return;
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    for problem in protocol_problems.iter() {
+                        if ui
+                            .link(egui::RichText::new(&problem.message).color(egui::Color32::RED))
+                            .clicked()
+                        {
+                            selected = Some(problem.element);
+                        }
+                    }
+                });
+        });
+
+    if let Some(entity) = selected {
+        do_deselect_all(&mut pick_selection_query);
+        if let Ok(mut pick_selection) = pick_selection_query.get_mut(entity) {
+            pick_selection.is_selected = true;
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! Named scenarios that override amounts, parameters & attributes of the base model.
+mod scenario;
+mod window;
+
+pub use scenario::*;
+pub use window::*;
+
+use crate::data_model::save::save_world;
+use bevy::prelude::*;
+
+pub struct ScenariosPlugin;
+
+impl Plugin for ScenariosPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Scenarios>()
+            .init_resource::<ScenarioEditor>()
+            .add_systems(
+                Update,
+                (
+                    scenarios_egui.after(bevy_egui::EguiSet::InitContexts),
+                    deactivate_scenario_before_save.before(save_world),
+                    resume_scenario_after_save.after(save_world),
+                ),
+            );
+    }
+}
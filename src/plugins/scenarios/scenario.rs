@@ -0,0 +1,404 @@
+//! While a scenario is active the components hold the scenario values and the base values are
+//! kept aside. Switching scenarios stores the differences to the base as the overrides of the
+//! previously active scenario.
+use crate::components::*;
+use crate::plugins::file_dialog::ExportFileEvent;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rust_decimal::Decimal;
+
+/// The values of an element that a scenario can override. As an override only the values that
+/// differ from the base are set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ElementValues {
+    /// Amount of a flow.
+    pub amount: Option<Decimal>,
+    /// Parameters of a flow.
+    pub parameters: Vec<Parameter>,
+    /// Attributes of a system, interface or external entity.
+    pub attributes: Vec<Parameter>,
+    /// Names of the parameters of a flow that the scenario removes.
+    pub removed_parameters: Vec<String>,
+    /// Names of the attributes that the scenario removes.
+    pub removed_attributes: Vec<String>,
+}
+
+impl ElementValues {
+    fn is_empty(&self) -> bool {
+        self.amount.is_none()
+            && self.parameters.is_empty()
+            && self.attributes.is_empty()
+            && self.removed_parameters.is_empty()
+            && self.removed_attributes.is_empty()
+    }
+
+    /// Values of `self` that are different from `base`.
+    fn difference(&self, base: &ElementValues) -> ElementValues {
+        let changed = |current: &[Parameter], base: &[Parameter]| {
+            current
+                .iter()
+                .filter(|p| !base.contains(p))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let removed = |current: &[Parameter], base: &[Parameter]| {
+            base.iter()
+                .filter(|b| !current.iter().any(|p| p.name.trim() == b.name.trim()))
+                .map(|b| b.name.trim().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        ElementValues {
+            amount: self.amount.filter(|amount| Some(*amount) != base.amount),
+            parameters: changed(&self.parameters, &base.parameters),
+            attributes: changed(&self.attributes, &base.attributes),
+            removed_parameters: removed(&self.parameters, &base.parameters),
+            removed_attributes: removed(&self.attributes, &base.attributes),
+        }
+    }
+}
+
+/// Removes the parameters with the removed names, then replaces the parameters with the same name
+/// or adds them.
+fn merge_parameters(target: &mut Vec<Parameter>, overrides: &[Parameter], removed: &[String]) {
+    target.retain(|p| !removed.iter().any(|name| p.name.trim() == name.trim()));
+
+    for parameter in overrides {
+        match target
+            .iter_mut()
+            .find(|p| p.name.trim() == parameter.name.trim())
+        {
+            Some(existing) => *existing = parameter.clone(),
+            None => target.push(parameter.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Scenario {
+    pub name: String,
+    pub overrides: HashMap<Entity, ElementValues>,
+}
+
+/// All scenarios of the model. Stored in the model file.
+#[derive(Resource, Default)]
+pub struct Scenarios {
+    pub scenarios: Vec<Scenario>,
+    /// Index of the active scenario. `None` if the base model is shown.
+    active: Option<usize>,
+    /// Values of the base model while a scenario is active.
+    base: HashMap<Entity, ElementValues>,
+    /// Scenario that is re-activated after the base model was saved.
+    resume: Option<usize>,
+}
+
+impl Scenarios {
+    pub fn new(scenarios: Vec<Scenario>) -> Self {
+        Self {
+            scenarios,
+            ..default()
+        }
+    }
+
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    /// Name of the active scenario. `None` if the base model is shown.
+    pub fn active_name(&self) -> Option<&str> {
+        self.active
+            .map(|active| self.scenarios[active].name.as_str())
+    }
+
+    /// Values of the base model. If no scenario is active these are the `current` values.
+    pub fn base_values(
+        &self,
+        current: &HashMap<Entity, ElementValues>,
+    ) -> HashMap<Entity, ElementValues> {
+        match self.active {
+            Some(_) => self.base.clone(),
+            None => current.clone(),
+        }
+    }
+
+    /// Overrides of the scenario. For the active scenario these are the differences of the
+    /// `current` values to the base, i.e. they include the unsaved edits.
+    pub fn overrides(
+        &self,
+        index: usize,
+        current: &HashMap<Entity, ElementValues>,
+    ) -> HashMap<Entity, ElementValues> {
+        if self.active == Some(index) {
+            difference(current, &self.base)
+        } else {
+            self.scenarios[index].overrides.clone()
+        }
+    }
+
+    /// Stores the edits of the active scenario, restores the base model and applies the overrides
+    /// of the new scenario.
+    pub fn switch_to(&mut self, target: Option<usize>, elements: &mut ScenarioElements) {
+        if target == self.active {
+            return;
+        }
+
+        if let Some(active) = self.active.take() {
+            self.scenarios[active].overrides = difference(&elements.snapshot(), &self.base);
+            elements.restore(&self.base);
+            self.base.clear();
+        }
+
+        if let Some(target) = target {
+            self.base = elements.snapshot();
+            elements.apply(&self.scenarios[target].overrides);
+            self.active = Some(target);
+        }
+    }
+
+    pub fn remove(&mut self, index: usize, elements: &mut ScenarioElements) {
+        if self.active == Some(index) {
+            self.switch_to(None, elements);
+        }
+        self.scenarios.remove(index);
+        self.active = self.active.map(|a| if a > index { a - 1 } else { a });
+    }
+}
+
+fn difference(
+    current: &HashMap<Entity, ElementValues>,
+    base: &HashMap<Entity, ElementValues>,
+) -> HashMap<Entity, ElementValues> {
+    current
+        .iter()
+        .filter_map(|(entity, values)| {
+            let difference = values.difference(base.get(entity)?);
+            (!difference.is_empty()).then_some((*entity, difference))
+        })
+        .collect()
+}
+
+fn flow_values(flow: &Flow) -> ElementValues {
+    ElementValues {
+        // Amounts of formulas are always computed from the other values.
+        amount: flow.formula.is_none().then_some(flow.amount),
+        parameters: flow.parameters.clone(),
+        ..default()
+    }
+}
+
+fn attribute_values(attributes: &[Parameter]) -> ElementValues {
+    ElementValues {
+        attributes: attributes.to_vec(),
+        ..default()
+    }
+}
+
+/// The components of all elements whose values scenarios can override.
+#[derive(SystemParam)]
+pub struct ScenarioElements<'w, 's> {
+    flow_query: Query<'w, 's, (Entity, &'static mut Flow)>,
+    system_query: Query<'w, 's, (Entity, &'static mut crate::components::System)>,
+    interface_query: Query<'w, 's, (Entity, &'static mut Interface)>,
+    external_entity_query: Query<'w, 's, (Entity, &'static mut ExternalEntity)>,
+}
+
+impl ScenarioElements<'_, '_> {
+    pub fn snapshot(&self) -> HashMap<Entity, ElementValues> {
+        let flows = self
+            .flow_query
+            .iter()
+            .map(|(entity, flow)| (entity, flow_values(flow)));
+        let systems = self
+            .system_query
+            .iter()
+            .map(|(entity, system)| (entity, attribute_values(&system.attributes)));
+        let interfaces = self
+            .interface_query
+            .iter()
+            .map(|(entity, interface)| (entity, attribute_values(&interface.attributes)));
+        let external_entities =
+            self.external_entity_query
+                .iter()
+                .map(|(entity, external_entity)| {
+                    (entity, attribute_values(&external_entity.attributes))
+                });
+
+        flows
+            .chain(systems)
+            .chain(interfaces)
+            .chain(external_entities)
+            .collect()
+    }
+
+    /// Snapshots the elements that changed since the last time the system ran and drops the
+    /// removed elements. Returns whether any value in the snapshot changed.
+    pub fn update_snapshot(&mut self, snapshot: &mut HashMap<Entity, ElementValues>) -> bool {
+        let mut changed = false;
+        let mut update = |entity: Entity, values: ElementValues| {
+            if snapshot.get(&entity) != Some(&values) {
+                snapshot.insert(entity, values);
+                changed = true;
+            }
+        };
+
+        for (entity, flow) in self.flow_query.iter_mut() {
+            if flow.is_changed() {
+                update(entity, flow_values(&flow));
+            }
+        }
+        for (entity, system) in self.system_query.iter_mut() {
+            if system.is_changed() {
+                update(entity, attribute_values(&system.attributes));
+            }
+        }
+        for (entity, interface) in self.interface_query.iter_mut() {
+            if interface.is_changed() {
+                update(entity, attribute_values(&interface.attributes));
+            }
+        }
+        for (entity, external_entity) in self.external_entity_query.iter_mut() {
+            if external_entity.is_changed() {
+                update(entity, attribute_values(&external_entity.attributes));
+            }
+        }
+
+        let count = snapshot.len();
+        snapshot.retain(|entity, _| {
+            self.flow_query.contains(*entity)
+                || self.system_query.contains(*entity)
+                || self.interface_query.contains(*entity)
+                || self.external_entity_query.contains(*entity)
+        });
+
+        changed || snapshot.len() != count
+    }
+
+    /// Applies overrides on top of the current values.
+    pub fn apply(&mut self, overrides: &HashMap<Entity, ElementValues>) {
+        for (entity, values) in overrides {
+            if let Ok((_, mut flow)) = self.flow_query.get_mut(*entity) {
+                if let Some(amount) = values.amount {
+                    flow.amount = amount;
+                }
+                merge_parameters(
+                    &mut flow.parameters,
+                    &values.parameters,
+                    &values.removed_parameters,
+                );
+            }
+            if let Some(attributes) = self.attributes_mut(*entity) {
+                merge_parameters(attributes, &values.attributes, &values.removed_attributes);
+            }
+        }
+    }
+
+    /// Replaces the current values with a snapshot.
+    pub fn restore(&mut self, snapshot: &HashMap<Entity, ElementValues>) {
+        for (entity, values) in snapshot {
+            if let Ok((_, mut flow)) = self.flow_query.get_mut(*entity) {
+                if let Some(amount) = values.amount {
+                    flow.amount = amount;
+                }
+                flow.parameters.clone_from(&values.parameters);
+            }
+            if let Some(attributes) = self.attributes_mut(*entity) {
+                attributes.clone_from(&values.attributes);
+            }
+        }
+    }
+
+    fn attributes_mut(&mut self, entity: Entity) -> Option<&mut Vec<Parameter>> {
+        if let Ok((_, system)) = self.system_query.get_mut(entity) {
+            return Some(&mut system.into_inner().attributes);
+        }
+        if let Ok((_, interface)) = self.interface_query.get_mut(entity) {
+            return Some(&mut interface.into_inner().attributes);
+        }
+        if let Ok((_, external_entity)) = self.external_entity_query.get_mut(entity) {
+            return Some(&mut external_entity.into_inner().attributes);
+        }
+        None
+    }
+}
+
+/// Only the base model is saved. The active scenario is deactivated so its edits are stored as
+/// overrides and re-activated after saving.
+pub fn deactivate_scenario_before_save(
+    mut save_file_event_reader: EventReader<ExportFileEvent>,
+    mut scenarios: ResMut<Scenarios>,
+    mut elements: ScenarioElements,
+) {
+    if save_file_event_reader.read().count() == 0 {
+        return;
+    }
+
+    if let Some(active) = scenarios.active {
+        scenarios.switch_to(None, &mut elements);
+        scenarios.resume = Some(active);
+    }
+}
+
+pub fn resume_scenario_after_save(
+    mut scenarios: ResMut<Scenarios>,
+    mut elements: ScenarioElements,
+) {
+    if let Some(resume) = scenarios.resume.take() {
+        scenarios.switch_to(Some(resume), &mut elements);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(name: &str, value: &str) -> Parameter {
+        Parameter {
+            name: name.to_string(),
+            value: ParameterValue::Text {
+                value: value.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn difference_contains_changed_added_and_removed_values() {
+        let base = ElementValues {
+            parameters: vec![text("a", "1"), text("b", "2"), text("c", "3")],
+            ..default()
+        };
+        let current = ElementValues {
+            parameters: vec![text("a", "1"), text("b", "20"), text("d", "4")],
+            ..default()
+        };
+
+        let difference = current.difference(&base);
+
+        assert_eq!(difference.parameters, vec![text("b", "20"), text("d", "4")]);
+        assert_eq!(difference.removed_parameters, vec!["c".to_string()]);
+        assert!(base.difference(&base).is_empty());
+    }
+
+    #[test]
+    fn merging_the_difference_restores_the_values() {
+        let base = vec![text("a", "1"), text("b", "2"), text("c", "3")];
+        let current = vec![text("a", "1"), text("b", "20"), text("d", "4")];
+        let difference = ElementValues {
+            parameters: current.clone(),
+            ..default()
+        }
+        .difference(&ElementValues {
+            parameters: base.clone(),
+            ..default()
+        });
+
+        let mut merged = base;
+        merge_parameters(
+            &mut merged,
+            &difference.parameters,
+            &difference.removed_parameters,
+        );
+
+        assert_eq!(merged, current);
+    }
+}
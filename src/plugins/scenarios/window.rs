@@ -0,0 +1,273 @@
+//! Window to switch between scenarios, manage them and compare their values.
+use super::{ElementValues, Scenario, ScenarioElements, Scenarios};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::egui::ComboBox;
+use bevy_egui::{egui, EguiContexts};
+
+#[derive(Resource, Default)]
+pub struct ScenarioEditor {
+    pub open: bool,
+}
+
+/// One row of the comparison table: a single value of an element across all scenarios.
+struct ComparisonRow {
+    element: String,
+    quantity: String,
+    base: String,
+    /// Value in each scenario. `None` if the scenario doesn't override the value.
+    scenarios: Vec<Option<String>>,
+}
+
+/// Lists every value that is overridden in at least one scenario.
+fn comparison_rows(
+    base: &HashMap<Entity, ElementValues>,
+    overrides: &[HashMap<Entity, ElementValues>],
+    name_query: &Query<&Name>,
+) -> Vec<ComparisonRow> {
+    let mut rows = Vec::<ComparisonRow>::new();
+
+    let element_name = |entity: Entity| {
+        name_query
+            .get(entity)
+            .map(|name| name.to_string())
+            .unwrap_or_default()
+    };
+
+    for (index, scenario) in overrides.iter().enumerate() {
+        for (entity, values) in scenario {
+            let base_values = base.get(entity).cloned().unwrap_or_default();
+
+            let base_value = |name: &str| {
+                base_values
+                    .parameters
+                    .iter()
+                    .chain(&base_values.attributes)
+                    .find(|p| p.name.trim() == name)
+                    .map(|p| p.value.to_string())
+                    .unwrap_or_default()
+            };
+
+            let mut changed = vec![];
+            if let Some(amount) = values.amount {
+                changed.push((
+                    "Amount".to_string(),
+                    base_values
+                        .amount
+                        .map(|a| a.to_string())
+                        .unwrap_or_default(),
+                    amount.to_string(),
+                ));
+            }
+            for parameter in values.parameters.iter().chain(&values.attributes) {
+                let name = parameter.name.trim();
+                changed.push((
+                    name.to_string(),
+                    base_value(name),
+                    parameter.value.to_string(),
+                ));
+            }
+            for name in values
+                .removed_parameters
+                .iter()
+                .chain(&values.removed_attributes)
+            {
+                changed.push((name.clone(), base_value(name), "(removed)".to_string()));
+            }
+
+            let element = element_name(*entity);
+            for (quantity, base_value, value) in changed {
+                let row_index = match rows
+                    .iter()
+                    .position(|r| r.element == element && r.quantity == quantity)
+                {
+                    Some(row_index) => row_index,
+                    None => {
+                        rows.push(ComparisonRow {
+                            element: element.clone(),
+                            quantity,
+                            base: base_value,
+                            scenarios: vec![None; overrides.len()],
+                        });
+                        rows.len() - 1
+                    }
+                };
+                rows[row_index].scenarios[index] = Some(value);
+            }
+        }
+    }
+
+    rows.sort_by(|a, b| (&a.element, &a.quantity).cmp(&(&b.element, &b.quantity)));
+    rows
+}
+
+/// The current values of the elements and the comparison that was last computed from them.
+#[derive(Default)]
+pub struct ScenarioComparison {
+    current: HashMap<Entity, ElementValues>,
+    overrides: Vec<HashMap<Entity, ElementValues>>,
+    rows: Vec<ComparisonRow>,
+    is_stale: bool,
+}
+
+/// Shows the scenario window with the switcher, the scenario management and the comparison of
+/// all overridden values. The comparison is only recomputed when a value or a scenario changed.
+pub fn scenarios_egui(
+    mut egui_contexts: EguiContexts,
+    mut scenario_editor: ResMut<ScenarioEditor>,
+    mut scenarios: ResMut<Scenarios>,
+    mut elements: ScenarioElements,
+    name_query: Query<&Name>,
+    mut comparison: Local<Option<ScenarioComparison>>,
+) {
+    if !scenario_editor.open {
+        *comparison = None;
+        return;
+    }
+
+    let comparison = match &mut *comparison {
+        Some(comparison) => {
+            if elements.update_snapshot(&mut comparison.current) || scenarios.is_changed() {
+                comparison.is_stale = true;
+            }
+            comparison
+        }
+        None => comparison.insert(ScenarioComparison {
+            current: elements.snapshot(),
+            is_stale: true,
+            ..default()
+        }),
+    };
+
+    if comparison.is_stale {
+        comparison.is_stale = false;
+        comparison.overrides = (0..scenarios.scenarios.len())
+            .map(|index| scenarios.overrides(index, &comparison.current))
+            .collect();
+        comparison.rows = comparison_rows(
+            &scenarios.base_values(&comparison.current),
+            &comparison.overrides,
+            &name_query,
+        );
+    }
+
+    let mut open = scenario_editor.open;
+    let mut target = scenarios.active();
+    let mut names = scenarios
+        .scenarios
+        .iter()
+        .map(|scenario| scenario.name.clone())
+        .collect::<Vec<_>>();
+    let mut added = false;
+    let mut duplicated = None;
+    let mut removed = None;
+
+    egui::Window::new("Scenarios")
+        .open(&mut open)
+        .default_width(500.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            let selected_text = target.map_or("Base".to_string(), |i| names[i].clone());
+
+            ui.horizontal(|ui| {
+                ui.label("Active Scenario");
+                ComboBox::from_id_source("Active Scenario")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut target, None, "Base");
+                        for (index, name) in names.iter().enumerate() {
+                            ui.selectable_value(&mut target, Some(index), name);
+                        }
+                    });
+            });
+            ui.label("Edits in the inspector change the active scenario.");
+
+            ui.separator();
+
+            for (index, name) in names.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(name);
+                    if ui.button("Duplicate").clicked() {
+                        duplicated = Some(index);
+                    }
+                    if ui.button("Delete").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+
+            added = ui.button("New Scenario").clicked();
+
+            ui.separator();
+
+            if comparison.rows.is_empty() {
+                ui.label("No scenario overrides any value.");
+                return;
+            }
+
+            egui::ScrollArea::both().max_height(400.0).show(ui, |ui| {
+                egui::Grid::new("Scenario Comparison")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Element");
+                        ui.strong("Value");
+                        ui.strong("Base");
+                        for name in &names {
+                            ui.strong(name);
+                        }
+                        ui.end_row();
+
+                        for row in &comparison.rows {
+                            ui.label(&row.element);
+                            ui.label(&row.quantity);
+                            ui.label(&row.base);
+                            for value in &row.scenarios {
+                                match value {
+                                    Some(value) => ui.strong(value),
+                                    None => ui.weak(&row.base),
+                                };
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+    if scenario_editor.open != open {
+        scenario_editor.open = open;
+    }
+
+    for (index, name) in names.into_iter().enumerate() {
+        if scenarios.scenarios[index].name != name {
+            scenarios.scenarios[index].name = name;
+        }
+    }
+
+    if added {
+        let name = format!("Scenario {}", scenarios.scenarios.len() + 1);
+        scenarios.scenarios.push(Scenario {
+            name,
+            overrides: HashMap::new(),
+        });
+    }
+
+    if let Some(index) = duplicated {
+        let scenario = Scenario {
+            name: format!("{} (Copy)", scenarios.scenarios[index].name),
+            overrides: comparison.overrides[index].clone(),
+        };
+        scenarios.scenarios.push(scenario);
+    }
+
+    if let Some(index) = removed {
+        scenarios.remove(index, &mut elements);
+        if target == Some(index) {
+            target = None;
+        } else {
+            target = target.map(|t| if t > index { t - 1 } else { t });
+        }
+    }
+
+    if target != scenarios.active() {
+        scenarios.switch_to(target, &mut elements);
+    }
+}
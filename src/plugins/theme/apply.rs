@@ -0,0 +1,122 @@
+//! Systems that apply the colors of the active theme to the canvas and the UI.
+use super::{srgb, Themes};
+use crate::components::*;
+use crate::plugins::label::{AutoContrastTextColor, Background, NameLabel};
+use crate::plugins::lyon_selection::HighlightBundles;
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+use bevy_prototype_lyon::prelude::*;
+
+pub fn apply_theme_clear_color(themes: Res<Themes>, mut clear_color: ResMut<ClearColor>) {
+    if themes.is_changed() {
+        clear_color.0 = srgb(themes.current().background);
+    }
+}
+
+/// Outlines of systems and interfaces. Flows and external entities use their substance colors.
+pub fn apply_theme_strokes(
+    themes: Res<Themes>,
+    mut query: Query<
+        (Ref<SystemElement>, &mut HighlightBundles<Stroke, Stroke>),
+        Or<(With<crate::components::System>, With<Interface>)>,
+    >,
+) {
+    let color = srgb(themes.current().stroke);
+
+    for (element, mut highlight) in &mut query {
+        if themes.is_changed() || element.is_added() {
+            highlight.idle.color = color;
+            highlight.selected.color = color;
+        }
+    }
+}
+
+/// Lines that are overlaid on selected elements.
+pub fn apply_theme_selection_helpers(
+    themes: Res<Themes>,
+    helper_query: Query<&SelectedHighlightHelperAdded>,
+    mut stroke_query: Query<&mut Stroke>,
+) {
+    if !themes.is_changed() {
+        return;
+    }
+
+    let color = srgb(themes.current().selection);
+    for helper in &helper_query {
+        if let Ok(mut stroke) = stroke_query.get_mut(helper.helper_entity) {
+            stroke.color = color;
+        }
+    }
+}
+
+/// Name labels use the label text color of the theme, except for the labels of systems which
+/// contrast with the system's fill. Labels with a background have the canvas behind them in the
+/// environment and the label background inside of systems.
+pub fn apply_theme_labels(
+    themes: Res<Themes>,
+    label_query: Query<(Ref<NameLabel>, Option<&NestingLevel>)>,
+    mut text_query: Query<(&mut Text, &Parent, Has<Background>), Without<AutoContrastTextColor>>,
+    mut sprite_query: Query<&mut Sprite>,
+) {
+    let theme = themes.current();
+
+    for (label, nesting_level) in &label_query {
+        if !themes.is_changed() && !label.is_added() {
+            continue;
+        }
+
+        let Ok((mut text, parent, has_background)) = text_query.get_mut(label.label) else {
+            continue;
+        };
+        text.sections[0].style.color = srgb(theme.label_text);
+
+        if has_background {
+            if let Ok(mut sprite) = sprite_query.get_mut(parent.get()) {
+                sprite.color = label_background(&theme, nesting_level.map_or(0, |l| **l));
+            }
+        }
+    }
+}
+
+pub fn label_background(theme: &super::Theme, nesting_level: u16) -> Color {
+    if nesting_level == 0 {
+        srgb(theme.background)
+    } else {
+        srgb(theme.label_background)
+    }
+}
+
+pub fn apply_egui_theme(mut egui_contexts: EguiContexts, themes: Res<Themes>) {
+    egui_contexts
+        .ctx_mut()
+        .set_visuals(themes.current().egui_visuals());
+}
+
+/// Seconds without changes after which changed themes are saved. Dragging a color picker changes
+/// the themes every frame.
+const SAVE_DELAY: f32 = 1.0;
+
+/// Persists the themes once they haven't been changed in the UI for a moment, or on exit.
+pub fn save_themes(
+    themes: Res<Themes>,
+    time: Res<Time>,
+    mut exit_reader: EventReader<AppExit>,
+    mut changed_at: Local<Option<f32>>,
+) {
+    if themes.is_changed() && !themes.is_added() {
+        *changed_at = Some(time.elapsed_seconds());
+    }
+
+    let is_exiting = exit_reader.read().count() > 0;
+    let Some(changed) = *changed_at else {
+        return;
+    };
+
+    if is_exiting || time.elapsed_seconds() - changed >= SAVE_DELAY {
+        *changed_at = None;
+        if let Err(error) = themes.save() {
+            warn!("Failed to save themes: {}", error);
+        }
+    }
+}
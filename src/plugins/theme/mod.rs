@@ -0,0 +1,31 @@
+//! Light, dark, high-contrast and user-defined color themes for the canvas and the UI.
+mod apply;
+mod themes;
+mod window;
+
+pub use apply::*;
+pub use themes::*;
+pub use window::*;
+
+use bevy::prelude::*;
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Themes::load())
+            .init_resource::<ThemeEditor>()
+            .add_systems(
+                Update,
+                (
+                    apply_theme_clear_color,
+                    apply_theme_strokes,
+                    apply_theme_selection_helpers,
+                    apply_theme_labels,
+                    apply_egui_theme.after(bevy_egui::EguiSet::InitContexts),
+                    themes_egui.after(bevy_egui::EguiSet::InitContexts),
+                    save_themes,
+                ),
+            );
+    }
+}
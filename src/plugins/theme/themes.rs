@@ -0,0 +1,207 @@
+use crate::components::SubstanceType;
+use crate::utils::{load_user_config, save_user_config};
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+const THEMES_FILE_NAME: &str = "themes.json";
+
+/// Colors of flows and interfaces of a base substance type in sRGB.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubstanceColors {
+    pub flow: [u8; 3],
+    pub interface: [u8; 3],
+}
+
+/// All colors of the canvas and the UI. Colors are in sRGB.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    /// Whether the UI panels use dark visuals.
+    pub dark: bool,
+    /// Background of the canvas.
+    pub background: [u8; 3],
+    /// Outline of systems and interfaces.
+    pub stroke: [u8; 3],
+    /// Line overlaid on selected elements.
+    pub selection: [u8; 3],
+    pub energy: SubstanceColors,
+    pub material: SubstanceColors,
+    pub message: SubstanceColors,
+    /// Background of flow labels inside of systems.
+    pub label_background: [u8; 3],
+    pub label_text: [u8; 3],
+}
+
+pub fn srgb([r, g, b]: [u8; 3]) -> Color {
+    Color::rgb_u8(r, g, b)
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Self {
+            name: "Light".to_string(),
+            dark: false,
+            background: [250, 235, 215],
+            stroke: [0, 0, 0],
+            selection: [255, 255, 255],
+            energy: SubstanceColors {
+                flow: [181, 27, 27],
+                interface: [233, 182, 178],
+            },
+            material: SubstanceColors {
+                flow: [128, 128, 128],
+                interface: [128, 128, 128],
+            },
+            message: SubstanceColors {
+                flow: [192, 192, 192],
+                interface: [192, 192, 192],
+            },
+            label_background: [255, 255, 255],
+            label_text: [0, 0, 0],
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            name: "Dark".to_string(),
+            dark: true,
+            background: [30, 32, 36],
+            stroke: [200, 200, 200],
+            selection: [90, 160, 255],
+            energy: SubstanceColors {
+                flow: [230, 80, 70],
+                interface: [120, 50, 48],
+            },
+            material: SubstanceColors {
+                flow: [150, 150, 150],
+                interface: [90, 90, 90],
+            },
+            message: SubstanceColors {
+                flow: [100, 170, 220],
+                interface: [50, 85, 110],
+            },
+            label_background: [50, 53, 58],
+            label_text: [230, 230, 230],
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "High Contrast".to_string(),
+            dark: true,
+            background: [0, 0, 0],
+            stroke: [255, 255, 255],
+            selection: [255, 220, 0],
+            energy: SubstanceColors {
+                flow: [255, 60, 60],
+                interface: [255, 150, 150],
+            },
+            material: SubstanceColors {
+                flow: [255, 255, 255],
+                interface: [200, 200, 200],
+            },
+            message: SubstanceColors {
+                flow: [0, 200, 255],
+                interface: [150, 230, 255],
+            },
+            label_background: [0, 0, 0],
+            label_text: [255, 255, 255],
+        }
+    }
+
+    pub fn built_in() -> [Theme; 3] {
+        [Theme::light(), Theme::dark(), Theme::high_contrast()]
+    }
+
+    pub fn substance_colors(&self, substance_type: SubstanceType) -> &SubstanceColors {
+        match substance_type {
+            SubstanceType::Energy => &self.energy,
+            SubstanceType::Material => &self.material,
+            SubstanceType::Message => &self.message,
+        }
+    }
+
+    pub fn substance_colors_mut(&mut self, substance_type: SubstanceType) -> &mut SubstanceColors {
+        match substance_type {
+            SubstanceType::Energy => &mut self.energy,
+            SubstanceType::Material => &mut self.material,
+            SubstanceType::Message => &mut self.message,
+        }
+    }
+
+    pub fn flow_color(&self, substance_type: SubstanceType) -> Color {
+        srgb(self.substance_colors(substance_type).flow)
+    }
+
+    pub fn interface_color(&self, substance_type: SubstanceType) -> Color {
+        srgb(self.substance_colors(substance_type).interface)
+    }
+
+    /// Interfaces of catalog substances use the flow color blended into the background.
+    pub fn interface_shade(&self, flow_color: Color) -> Color {
+        let [r, g, b, _] = flow_color.as_rgba_f32();
+        let [br, bg, bb, _] = srgb(self.background).as_rgba_f32();
+        Color::rgb(r + (br - r) * 0.6, g + (bg - g) * 0.6, b + (bb - b) * 0.6)
+    }
+
+    pub fn egui_visuals(&self) -> egui::Visuals {
+        let color32 = |[r, g, b]: [u8; 3]| egui::Color32::from_rgb(r, g, b);
+
+        let mut visuals = if self.dark {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        };
+        visuals.override_text_color = Some(color32(self.label_text));
+        visuals.selection.bg_fill = color32(self.selection).gamma_multiply(0.6);
+        visuals.selection.stroke.color = color32(self.label_text);
+        visuals
+    }
+}
+
+/// The built-in and user-defined themes. Stored in the user's configuration directory.
+#[derive(Resource, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Themes {
+    /// Name of the active theme.
+    pub active: String,
+    pub custom: Vec<Theme>,
+}
+
+impl Default for Themes {
+    fn default() -> Self {
+        Self {
+            active: Theme::light().name,
+            custom: vec![],
+        }
+    }
+}
+
+impl Themes {
+    pub fn load() -> Self {
+        load_user_config(THEMES_FILE_NAME)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_user_config(THEMES_FILE_NAME, self)
+    }
+
+    pub fn all(&self) -> Vec<Theme> {
+        Theme::built_in()
+            .into_iter()
+            .chain(self.custom.iter().cloned())
+            .collect()
+    }
+
+    /// The active theme. Falls back to the light theme if it doesn't exist anymore.
+    pub fn current(&self) -> Theme {
+        self.all()
+            .into_iter()
+            .find(|theme| theme.name == self.active)
+            .unwrap_or_else(Theme::light)
+    }
+
+    pub fn is_built_in(name: &str) -> bool {
+        Theme::built_in().iter().any(|theme| theme.name == name)
+    }
+}
@@ -0,0 +1,130 @@
+//! Window to pick the active theme and to define custom themes.
+use super::{Theme, Themes};
+use crate::components::SubstanceType;
+use bevy::prelude::*;
+use bevy_egui::egui::ComboBox;
+use bevy_egui::{egui, EguiContexts};
+
+#[derive(Resource, Default)]
+pub struct ThemeEditor {
+    pub open: bool,
+    /// Name of the active custom theme while it's being edited. Only applied if it's unique.
+    name: String,
+    /// The theme that `name` belongs to.
+    name_of: String,
+}
+
+fn color_row(ui: &mut egui::Ui, label: &str, color: &mut [u8; 3]) {
+    ui.label(label);
+    ui.color_edit_button_srgb(color);
+    ui.end_row();
+}
+
+pub fn themes_egui(
+    mut egui_contexts: EguiContexts,
+    mut theme_editor: ResMut<ThemeEditor>,
+    mut themes: ResMut<Themes>,
+) {
+    if !theme_editor.open {
+        return;
+    }
+
+    // Only mutate the resource on actual changes so the themes are only saved when needed.
+    let mut edited = themes.clone();
+    let theme_editor = &mut *theme_editor;
+
+    if theme_editor.name_of != edited.active {
+        theme_editor.name.clone_from(&edited.active);
+        theme_editor.name_of.clone_from(&edited.active);
+    }
+
+    egui::Window::new("Themes")
+        .open(&mut theme_editor.open)
+        .default_width(320.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Theme");
+                ComboBox::from_id_source("Active Theme")
+                    .selected_text(&edited.active)
+                    .show_ui(ui, |ui| {
+                        for theme in edited.all() {
+                            ui.selectable_value(&mut edited.active, theme.name.clone(), &theme.name);
+                        }
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("New Custom Theme").clicked() {
+                    let mut theme = edited.current();
+                    let mut count = edited.custom.len() + 1;
+                    while edited.all().iter().any(|t| t.name == format!("Custom {}", count)) {
+                        count += 1;
+                    }
+                    theme.name = format!("Custom {}", count);
+                    edited.active.clone_from(&theme.name);
+                    edited.custom.push(theme);
+                }
+
+                let custom_idx = edited.custom.iter().position(|t| t.name == edited.active);
+                if let Some(idx) = custom_idx {
+                    if ui.button("Delete Theme").clicked() {
+                        edited.custom.remove(idx);
+                        edited.active = Theme::light().name;
+                    }
+                }
+            });
+
+            let active = edited.active.clone();
+            let Some(theme) = edited.custom.iter_mut().find(|t| t.name == active) else {
+                ui.label("Built-in themes can't be changed. Create a custom theme to pick your own colors.");
+                return;
+            };
+
+            ui.separator();
+
+            let name = &mut theme_editor.name;
+            ui.horizontal(|ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(name);
+            });
+            ui.checkbox(&mut theme.dark, "Dark Panels");
+
+            egui::Grid::new("Theme Colors").show(ui, |ui| {
+                color_row(ui, "Background", &mut theme.background);
+                color_row(ui, "Outlines", &mut theme.stroke);
+                color_row(ui, "Selection", &mut theme.selection);
+                color_row(ui, "Label Background", &mut theme.label_background);
+                color_row(ui, "Label Text", &mut theme.label_text);
+                for substance_type in [
+                    SubstanceType::Energy,
+                    SubstanceType::Material,
+                    SubstanceType::Message,
+                ] {
+                    let colors = theme.substance_colors_mut(substance_type);
+                    color_row(ui, &format!("{:?} Flow", substance_type), &mut colors.flow);
+                    color_row(
+                        ui,
+                        &format!("{:?} Interface", substance_type),
+                        &mut colors.interface,
+                    );
+                }
+            });
+
+            if *name == theme.name {
+                return;
+            }
+            if name.trim().is_empty() {
+                ui.colored_label(egui::Color32::RED, "The theme needs a name");
+            } else if Themes::is_built_in(name) || themes.custom.iter().any(|t| t.name == *name) {
+                ui.colored_label(egui::Color32::RED, "A theme with this name already exists");
+            } else {
+                theme.name.clone_from(name);
+                edited.active.clone_from(name);
+                theme_editor.name_of.clone_from(name);
+            }
+        });
+
+    if edited != *themes {
+        *themes = edited;
+    }
+}
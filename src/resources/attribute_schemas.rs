@@ -0,0 +1,95 @@
+use crate::components::{Parameter, ParameterValue};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The element types that can have user-defined attributes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AttributeElementType {
+    System,
+    Interface,
+    ExternalEntity,
+}
+
+impl AttributeElementType {
+    pub const ALL: [AttributeElementType; 3] = [
+        AttributeElementType::System,
+        AttributeElementType::Interface,
+        AttributeElementType::ExternalEntity,
+    ];
+}
+
+impl std::fmt::Display for AttributeElementType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttributeElementType::System => write!(f, "System"),
+            AttributeElementType::Interface => write!(f, "Interface"),
+            AttributeElementType::ExternalEntity => write!(f, "External Entity"),
+        }
+    }
+}
+
+/// The attributes that every element of a type has. The values of the attributes are the defaults
+/// of elements that haven't set the attribute yet. Stored in the model file.
+#[derive(Resource, Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AttributeSchemas {
+    pub system: Vec<Parameter>,
+    pub interface: Vec<Parameter>,
+    pub external_entity: Vec<Parameter>,
+}
+
+impl AttributeSchemas {
+    pub fn schema(&self, element_type: AttributeElementType) -> &Vec<Parameter> {
+        match element_type {
+            AttributeElementType::System => &self.system,
+            AttributeElementType::Interface => &self.interface,
+            AttributeElementType::ExternalEntity => &self.external_entity,
+        }
+    }
+
+    pub fn schema_mut(&mut self, element_type: AttributeElementType) -> &mut Vec<Parameter> {
+        match element_type {
+            AttributeElementType::System => &mut self.system,
+            AttributeElementType::Interface => &mut self.interface,
+            AttributeElementType::ExternalEntity => &mut self.external_entity,
+        }
+    }
+
+    /// Returns the values of all schema attributes of an element. Attributes the element hasn't
+    /// set yet have the default value of the schema.
+    pub fn resolve(
+        &self,
+        element_type: AttributeElementType,
+        attributes: &[Parameter],
+    ) -> Vec<Parameter> {
+        self.schema(element_type)
+            .iter()
+            .map(|expected| {
+                attributes
+                    .iter()
+                    .find(|a| a.name.trim() == expected.name.trim())
+                    .filter(|a| a.value.kind() == expected.value.kind())
+                    .map(|a| match (&a.value, &expected.value) {
+                        // The options of enumerations are defined by the schema.
+                        (
+                            ParameterValue::Enum { value, .. },
+                            ParameterValue::Enum { options, .. },
+                        ) => Parameter {
+                            name: expected.name.clone(),
+                            value: ParameterValue::Enum {
+                                value: value.clone(),
+                                options: options.clone(),
+                            },
+                        },
+                        _ => a.clone(),
+                    })
+                    .unwrap_or_else(|| expected.clone())
+            })
+            .collect()
+    }
+}
+
+/// State of the attribute schema editor window.
+#[derive(Resource, Default)]
+pub struct AttributeSchemaEditor {
+    pub open: bool,
+}
@@ -1,7 +1,16 @@
+mod attribute_schemas;
+mod parameter_schemas;
+mod protocols;
+mod substance_catalog;
 mod system_element_geometry;
 
+pub use attribute_schemas::*;
+pub use parameter_schemas::*;
+pub use protocols::*;
+pub use substance_catalog::*;
 pub use system_element_geometry::*;
 
+use crate::constants::{MAX_ZOOM, MIN_ZOOM};
 use bevy::prelude::*;
 
 #[derive(Debug, Resource, Deref, DerefMut, Copy, Clone, Reflect)]
@@ -29,6 +38,15 @@ impl Zoom {
         debug_assert!(fac > 0.0);
         self.0 *= fac;
     }
+
+    /// Multiplies the zoom by `fac` without leaving the limits and returns the factor that was
+    /// actually applied.
+    pub fn mul_within(&mut self, fac: f32, limits: &ZoomLimits) -> f32 {
+        let zoom = limits.clamp(self.0 * fac);
+        let applied = zoom / self.0;
+        self.0 = zoom;
+        applied
+    }
 }
 
 impl Default for Zoom {
@@ -37,6 +55,31 @@ impl Default for Zoom {
     }
 }
 
+/// Range that the user can zoom in. Stored with the preferences.
+#[derive(
+    Debug, Resource, Copy, Clone, PartialEq, Reflect, serde::Serialize, serde::Deserialize,
+)]
+#[reflect(Resource)]
+pub struct ZoomLimits {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ZoomLimits {
+    pub fn clamp(&self, zoom: f32) -> f32 {
+        zoom.clamp(self.min, self.max.max(self.min))
+    }
+}
+
+impl Default for ZoomLimits {
+    fn default() -> Self {
+        Self {
+            min: MIN_ZOOM,
+            max: MAX_ZOOM,
+        }
+    }
+}
+
 #[derive(Resource, Deref, DerefMut)]
 pub struct StrokeTessellator(bevy_prototype_lyon::prelude::tess::StrokeTessellator);
 
@@ -0,0 +1,72 @@
+use crate::components::{Flow, Parameter, SubstanceType};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The parameters that flows of a substance are expected to have. The values of the parameters
+/// are the defaults that new flows are pre-populated with.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ParameterSchema {
+    pub substance_type: SubstanceType,
+    /// If empty the schema applies to all flows of the substance type.
+    pub substance_sub_type: String,
+    pub parameters: Vec<Parameter>,
+}
+
+impl ParameterSchema {
+    fn applies_to(&self, flow: &Flow) -> bool {
+        self.substance_type == flow.substance_type
+            && (self.substance_sub_type.trim().is_empty()
+                || self
+                    .substance_sub_type
+                    .trim()
+                    .eq_ignore_ascii_case(flow.substance_sub_type.trim()))
+    }
+}
+
+/// All parameter schemas of the model. Stored in the model file.
+#[derive(Resource, Clone, Debug, Default, Deref, DerefMut)]
+pub struct ParameterSchemas(pub Vec<ParameterSchema>);
+
+impl ParameterSchemas {
+    /// Returns the expected parameters of the flow. Parameters of a schema for the sub type
+    /// override parameters with the same name of a schema for the whole substance type.
+    pub fn parameters_for(&self, flow: &Flow) -> Vec<Parameter> {
+        let mut schemas = self
+            .iter()
+            .filter(|schema| schema.applies_to(flow))
+            .collect::<Vec<_>>();
+        schemas.sort_by_key(|schema| !schema.substance_sub_type.trim().is_empty());
+
+        let mut parameters = Vec::<Parameter>::new();
+        for parameter in schemas.into_iter().flat_map(|schema| &schema.parameters) {
+            if let Some(existing) = parameters
+                .iter_mut()
+                .find(|p| p.name.trim() == parameter.name.trim())
+            {
+                *existing = parameter.clone();
+            } else {
+                parameters.push(parameter.clone());
+            }
+        }
+        parameters
+    }
+
+    /// Adds the expected parameters that the flow doesn't have yet with their default values.
+    pub fn add_missing_parameters(&self, flow: &mut Flow) {
+        for parameter in self.parameters_for(flow) {
+            if !flow
+                .parameters
+                .iter()
+                .any(|p| p.name.trim() == parameter.name.trim())
+            {
+                flow.parameters.push(parameter);
+            }
+        }
+    }
+}
+
+/// State of the parameter schema editor window.
+#[derive(Resource, Default)]
+pub struct ParameterSchemaEditor {
+    pub open: bool,
+}
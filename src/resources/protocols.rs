@@ -0,0 +1,53 @@
+use crate::components::SubstanceType;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A protocol that interfaces can use to exchange a substance.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Protocol {
+    pub name: String,
+    /// Version in the format `major.minor`. Only versions with the same major version are
+    /// compatible.
+    pub version: String,
+    /// The substance that is exchanged through the protocol.
+    pub substance_type: SubstanceType,
+    /// Message schema or physical specification.
+    pub specification: String,
+}
+
+impl Protocol {
+    fn major_version(&self) -> &str {
+        self.version.trim().split('.').next().unwrap_or_default()
+    }
+
+    pub fn is_compatible_with(&self, other: &Protocol) -> bool {
+        self.name.trim() == other.name.trim() && self.major_version() == other.major_version()
+    }
+
+    /// Name and version as shown in the UI.
+    pub fn label(&self) -> String {
+        if self.version.trim().is_empty() {
+            self.name.trim().to_string()
+        } else {
+            format!("{} v{}", self.name.trim(), self.version.trim())
+        }
+    }
+}
+
+/// Registry of all protocols of the model. Stored in the model file.
+#[derive(Resource, Clone, Debug, Default, Deref, DerefMut)]
+pub struct Protocols(pub Vec<Protocol>);
+
+impl Protocols {
+    /// Finds the protocol an interface references by name and version.
+    pub fn find(&self, name: &str, version: &str) -> Option<&Protocol> {
+        self.iter()
+            .find(|p| p.name.trim() == name.trim() && p.version.trim() == version.trim())
+    }
+}
+
+/// State of the protocol registry window.
+#[derive(Resource, Default)]
+pub struct ProtocolEditor {
+    pub open: bool,
+}
@@ -0,0 +1,216 @@
+use crate::components::SubstanceType;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A substance of the catalog. The three base substance types are the implicit roots of the
+/// hierarchy, e.g. Material → Water → Cooling water.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SubstanceDefinition {
+    pub name: String,
+    pub substance_type: SubstanceType,
+    /// Name of the parent substance of the same type. `None` for direct children of the base
+    /// type.
+    pub parent: Option<String>,
+    /// Color of the flows in sRGB. Inherited from the parent if `None`.
+    pub color: Option<[u8; 3]>,
+    /// Unit that new flows of this substance are given.
+    pub default_unit: String,
+    /// Short symbol, e.g. an emoji, shown next to the name.
+    pub icon: String,
+}
+
+impl SubstanceDefinition {
+    pub fn label(&self) -> String {
+        if self.icon.trim().is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} {}", self.icon.trim(), self.name)
+        }
+    }
+}
+
+/// The hierarchical catalog of substances of the model. Stored in the model file.
+#[derive(Resource, Clone, Debug, Default, Deref, DerefMut)]
+pub struct SubstanceCatalog(pub Vec<SubstanceDefinition>);
+
+impl SubstanceCatalog {
+    pub fn find(&self, substance_type: SubstanceType, name: &str) -> Option<&SubstanceDefinition> {
+        self.iter().find(|s| {
+            s.substance_type == substance_type && s.name.trim().eq_ignore_ascii_case(name.trim())
+        })
+    }
+
+    /// Direct children of a substance or of the base type if `parent` is `None`.
+    pub fn children<'a>(
+        &'a self,
+        substance_type: SubstanceType,
+        parent: Option<&'a str>,
+    ) -> impl Iterator<Item = (usize, &'a SubstanceDefinition)> {
+        self.iter().enumerate().filter(move |(_, s)| {
+            s.substance_type == substance_type && s.parent.as_deref() == parent
+        })
+    }
+
+    /// The substance and all its ancestors, starting with the substance.
+    fn ancestors(&self, substance_type: SubstanceType, name: &str) -> Vec<&SubstanceDefinition> {
+        let mut ancestors = vec![];
+        let mut current = self.find(substance_type, name);
+        while let Some(substance) = current {
+            // Guards against cycles in hand-edited files.
+            if ancestors.contains(&substance) {
+                break;
+            }
+            ancestors.push(substance);
+            current = substance
+                .parent
+                .as_ref()
+                .and_then(|parent| self.find(substance_type, parent));
+        }
+        ancestors
+    }
+
+    /// Path from the base type to the substance, e.g. "Material → Water → Cooling water".
+    pub fn path(&self, substance_type: SubstanceType, name: &str) -> String {
+        let mut path = vec![format!("{:?}", substance_type)];
+        path.extend(
+            self.ancestors(substance_type, name)
+                .into_iter()
+                .rev()
+                .map(|s| s.name.clone()),
+        );
+        path.join(" → ")
+    }
+
+    /// Color of the substance or its closest ancestor that has one. `None` if the color of the
+    /// base type should be used.
+    pub fn color(&self, substance_type: SubstanceType, name: &str) -> Option<Color> {
+        self.ancestors(substance_type, name)
+            .into_iter()
+            .find_map(|s| s.color)
+            .map(|[r, g, b]| Color::rgb_u8(r, g, b))
+    }
+
+    /// Renames a substance and updates the parent of its children.
+    pub fn rename(&mut self, index: usize, new_name: String) {
+        let substance_type = self[index].substance_type;
+        let old_name = std::mem::replace(&mut self[index].name, new_name.clone());
+
+        for substance in self.iter_mut() {
+            if substance.substance_type == substance_type
+                && substance.parent.as_deref() == Some(old_name.as_str())
+            {
+                substance.parent = Some(new_name.clone());
+            }
+        }
+    }
+
+    /// Removes a substance. Its children move up to its parent.
+    pub fn remove(&mut self, index: usize) {
+        let removed = self.0.remove(index);
+
+        for substance in self.iter_mut() {
+            if substance.substance_type == removed.substance_type
+                && substance.parent.as_deref() == Some(removed.name.as_str())
+            {
+                substance.parent.clone_from(&removed.parent);
+            }
+        }
+    }
+
+    /// Checks a new name for the substance. Names have to be unique within a substance type
+    /// because flows reference substances by name.
+    pub fn validate_name(&self, index: usize, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("The name can't be empty".to_string());
+        }
+
+        let substance_type = self[index].substance_type;
+        let is_duplicate = self.iter().enumerate().any(|(other, s)| {
+            other != index
+                && s.substance_type == substance_type
+                && s.name.trim().eq_ignore_ascii_case(name)
+        });
+        if is_duplicate {
+            return Err(format!("There already is a substance \"{}\"", name));
+        }
+
+        Ok(())
+    }
+}
+
+/// State of the substance catalog window.
+#[derive(Resource, Default)]
+pub struct SubstanceCatalogEditor {
+    pub open: bool,
+    /// The substance whose name is being edited and the name typed so far. The substance is only
+    /// renamed once the edit is committed.
+    pub renaming: Option<(usize, String)>,
+    /// Why the last rename was rejected.
+    pub rename_error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn substance(name: &str, parent: Option<&str>) -> SubstanceDefinition {
+        SubstanceDefinition {
+            name: name.to_string(),
+            substance_type: SubstanceType::Material,
+            parent: parent.map(str::to_string),
+            ..default()
+        }
+    }
+
+    fn catalog() -> SubstanceCatalog {
+        SubstanceCatalog(vec![
+            SubstanceDefinition {
+                color: Some([0, 0, 255]),
+                ..substance("Water", None)
+            },
+            substance("Cooling water", Some("Water")),
+            SubstanceDefinition {
+                substance_type: SubstanceType::Energy,
+                ..substance("Heat", None)
+            },
+        ])
+    }
+
+    #[test]
+    fn children_inherit_the_color_and_path() {
+        let catalog = catalog();
+
+        assert_eq!(
+            catalog.color(SubstanceType::Material, "cooling water"),
+            Some(Color::rgb_u8(0, 0, 255))
+        );
+        assert_eq!(
+            catalog.path(SubstanceType::Material, "Cooling water"),
+            "Material → Water → Cooling water"
+        );
+        assert_eq!(catalog.color(SubstanceType::Energy, "Heat"), None);
+    }
+
+    #[test]
+    fn names_must_be_unique_and_not_empty() {
+        let catalog = catalog();
+
+        assert!(catalog.validate_name(1, "  ").is_err());
+        assert!(catalog.validate_name(1, "water").is_err());
+        assert!(catalog.validate_name(1, "Heat").is_ok());
+        assert!(catalog.validate_name(0, "Water").is_ok());
+    }
+
+    #[test]
+    fn renaming_and_removing_keeps_the_hierarchy() {
+        let mut catalog = catalog();
+
+        catalog.rename(0, "Fresh water".to_string());
+        assert_eq!(catalog[1].parent.as_deref(), Some("Fresh water"));
+
+        catalog.remove(0);
+        assert_eq!(catalog[0].name, "Cooling water");
+        assert_eq!(catalog[0].parent, None);
+    }
+}
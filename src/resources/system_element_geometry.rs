@@ -27,10 +27,11 @@ impl FixedSystemElementGeometriesByNestingLevel {
         &mut self,
         nesting_level: u16,
         zoom: f32,
+        scaling_factor: f32,
         meshes: &mut ResMut<Assets<Mesh>>,
         tess: &mut ResMut<StrokeTessellator>,
     ) -> FixedSystemElementGeometries {
-        let scale = NestingLevel::compute_scale(nesting_level, zoom);
+        let scale = NestingLevel::compute_scale(nesting_level, zoom, scaling_factor);
 
         self.0
             .entry(nesting_level)
@@ -34,3 +34,14 @@ pub fn pan_camera_with_mouse_wheel(
 pub fn reset_camera_position(mut camera_query: Query<&mut Transform, With<Camera>>) {
     camera_query.single_mut().translation = Vec3::new(0.0, 0.0, 1000.);
 }
+
+/// Factor by which the zoom has to be multiplied so that an area fills the given fraction of the
+/// smaller window dimension. `half_size` is in the current zoomed world coordinates.
+pub fn zoom_factor_to_fit(window_size: Vec2, half_size: Vec2, fill: f32) -> f32 {
+    let extent = half_size.max_element() * 2.0;
+    if extent > f32::EPSILON {
+        window_size.min_element() * fill / extent
+    } else {
+        1.0
+    }
+}
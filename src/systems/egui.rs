@@ -6,11 +6,35 @@
 //! This feature heavily uses "system piping".
 use crate::components::*;
 use crate::data_model::Complexity;
+use crate::plugins::analysis::{
+    monte_carlo_element_egui, AmountPropagation, BottleneckAnalysis, DisruptionAnalysis,
+    MonteCarloAnalysis,
+};
+use crate::plugins::annotations::{AnnotationKind, CreateAnnotation};
+use crate::plugins::attributes::AttributeTable;
+use crate::plugins::descriptions::{description_egui, DescriptionLinks, LinkIndex};
+use crate::plugins::formula::FormulaErrors;
+use crate::plugins::keybindings::{KeyAction, Keybindings, KeybindingsWindow};
+use crate::plugins::layout::{AutoLayout, FlowRoute, SnapSettings};
 use crate::plugins::mouse_interaction::PickSelection;
+use crate::plugins::navigation::{BookmarkList, FitView, Minimap, SearchPanel};
+use crate::plugins::outline::OutlinePanel;
+use crate::plugins::preferences::PreferencesWindow;
+use crate::plugins::protocols::{ProblemsList, ProtocolProblems};
+use crate::plugins::scenarios::{ScenarioEditor, Scenarios};
+use crate::plugins::theme::{ThemeEditor, Themes};
+use crate::resources::{
+    AttributeElementType, AttributeSchemaEditor, AttributeSchemas, ParameterSchema,
+    ParameterSchemaEditor, ParameterSchemas, Protocol, ProtocolEditor, Protocols, SubstanceCatalog,
+    SubstanceCatalogEditor, SubstanceDefinition, Zoom, ZoomLimits,
+};
+use crate::systems::external_entity_rotation;
+use bevy::ecs::system::SystemParam;
 use bevy::input::mouse::MouseWheel;
+use bevy::input::touchpad::TouchpadMagnify;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
-use bevy_egui::egui::{Checkbox, ComboBox, DragValue, Margin, Slider, Ui, Visuals};
+use bevy_egui::egui::{Checkbox, ComboBox, DragValue, Margin, Slider, Ui};
 use bevy_egui::{egui, EguiContexts};
 use rust_decimal::Decimal;
 
@@ -62,12 +86,90 @@ macro_rules! vcj_text_edit {
     };
 }
 
-fn interface_egui(ui: &mut Ui, interface: &mut Interface) {
+fn interface_egui(ui: &mut Ui, interface: &mut Interface, protocols: &Protocols) {
     h_label!(ui, "Protocol φ");
-    vcj_text_edit!(ui, &mut interface.protocol, true);
+
+    let selected_text = match protocols.find(&interface.protocol, &interface.protocol_version) {
+        Some(protocol) => protocol.label(),
+        None if interface.protocol.trim().is_empty() => "None".to_string(),
+        None => format!("{} (unknown)", interface.protocol.trim()),
+    };
+
+    ui.vertical_centered_justified(|ui| {
+        ComboBox::from_id_source("Interface Protocol")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(interface.protocol.is_empty(), "None")
+                    .clicked()
+                {
+                    interface.protocol.clear();
+                    interface.protocol_version.clear();
+                }
+                for protocol in protocols.iter() {
+                    let selected = protocol.name == interface.protocol
+                        && protocol.version == interface.protocol_version;
+                    if ui.selectable_label(selected, protocol.label()).clicked() {
+                        interface.protocol.clone_from(&protocol.name);
+                        interface.protocol_version.clone_from(&protocol.version);
+                    }
+                }
+            });
+    });
+
+    if let Some(protocol) = protocols.find(&interface.protocol, &interface.protocol_version) {
+        h_label!(ui, format!("Carries {:?}", protocol.substance_type));
+        if !protocol.specification.trim().is_empty() {
+            vcj_label!(ui, &protocol.specification);
+        }
+    }
+}
+
+const MAX_SUBSTANCE_DEPTH: usize = 16;
+
+/// Lists the substances below `parent` indented by their depth in the catalog.
+fn substance_tree_egui(
+    ui: &mut Ui,
+    substance_catalog: &SubstanceCatalog,
+    substance_type: SubstanceType,
+    parent: Option<&str>,
+    depth: usize,
+    current: &str,
+    picked: &mut Option<usize>,
+) {
+    // Substances with the same name as their parent would recurse forever.
+    if depth > MAX_SUBSTANCE_DEPTH {
+        return;
+    }
+
+    for (idx, substance) in substance_catalog.children(substance_type, parent) {
+        let label = format!("{}{}", "    ".repeat(depth), substance.label());
+        if ui
+            .selectable_label(substance.name.eq_ignore_ascii_case(current), label)
+            .clicked()
+        {
+            *picked = Some(idx);
+        }
+        substance_tree_egui(
+            ui,
+            substance_catalog,
+            substance_type,
+            Some(&substance.name),
+            depth + 1,
+            current,
+            picked,
+        );
+    }
 }
 
-fn interaction_egui(ui: &mut Ui, flow: &mut Flow) {
+fn interaction_egui(
+    ui: &mut Ui,
+    flow: &mut Flow,
+    formula_error: Option<&String>,
+    parameter_schemas: &ParameterSchemas,
+    substance_catalog: &SubstanceCatalog,
+    element_names: &[String],
+) {
     h_label!(ui, "Interaction Usability");
     ui.horizontal(|ui| {
         ComboBox::from_label("")
@@ -94,10 +196,24 @@ fn interaction_egui(ui: &mut Ui, flow: &mut Flow) {
             });
     });
 
-    flow_egui(ui, flow);
+    flow_egui(
+        ui,
+        flow,
+        formula_error,
+        parameter_schemas,
+        substance_catalog,
+        element_names,
+    );
 }
 
-fn flow_egui(ui: &mut Ui, flow: &mut Flow) {
+fn flow_egui(
+    ui: &mut Ui,
+    flow: &mut Flow,
+    formula_error: Option<&String>,
+    parameter_schemas: &ParameterSchemas,
+    substance_catalog: &SubstanceCatalog,
+    element_names: &[String],
+) {
     h_label!(ui, "Interaction Type");
     h_wrap!(ui, |ui| {
         ComboBox::from_label("       ")
@@ -128,59 +244,399 @@ fn flow_egui(ui: &mut Ui, flow: &mut Flow) {
     });
 
     h_label!(ui, "Substance Sub Type");
-    vcj_text_edit!(ui, &mut flow.substance_sub_type, false);
+    let selected_text = match substance_catalog.find(flow.substance_type, &flow.substance_sub_type)
+    {
+        Some(substance) => substance.label(),
+        None if flow.substance_sub_type.trim().is_empty() => "None".to_string(),
+        None => format!("{} (not in catalog)", flow.substance_sub_type),
+    };
+    let mut picked = None;
+    ui.vertical_centered_justified(|ui| {
+        ComboBox::from_id_source("Substance Sub Type")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(flow.substance_sub_type.is_empty(), "None")
+                    .clicked()
+                {
+                    flow.substance_sub_type.clear();
+                }
+                substance_tree_egui(
+                    ui,
+                    substance_catalog,
+                    flow.substance_type,
+                    None,
+                    0,
+                    &flow.substance_sub_type,
+                    &mut picked,
+                );
+            });
+    });
+    if let Some(idx) = picked {
+        let substance = &substance_catalog[idx];
+        flow.substance_sub_type.clone_from(&substance.name);
+        if !substance.default_unit.trim().is_empty() {
+            flow.unit.clone_from(&substance.default_unit);
+        }
+    }
+    if substance_catalog
+        .find(flow.substance_type, &flow.substance_sub_type)
+        .is_some()
+    {
+        h_label!(
+            ui,
+            substance_catalog.path(flow.substance_type, &flow.substance_sub_type)
+        );
+    }
 
     h_label!(ui, "Substance Unit");
     vcj_text_edit!(ui, &mut flow.unit, false);
 
     // TODO : allow empty strings
-    let mut amount_string = flow.amount.to_string();
+    let mut amount_string = match &flow.formula {
+        Some(formula) => format!("={}", formula),
+        None => flow.amount.to_string(),
+    };
     h_label!(ui, "Substance Amount");
     ui.horizontal(|ui| {
-        ui.text_edit_singleline(&mut amount_string);
-        only_valid_positive_decimal(&mut amount_string, &mut flow.amount);
+        if ui
+            .add(egui::TextEdit::singleline(&mut amount_string).hint_text("Amount or =formula"))
+            .changed()
+        {
+            flow.amount_origin = AmountOrigin::Entered;
+            flow.formula = amount_string.strip_prefix('=').map(str::to_string);
+        }
+        if flow.formula.is_none() {
+            only_valid_positive_decimal(&mut amount_string, &mut flow.amount);
+        }
+        if matches!(flow.amount_origin, AmountOrigin::Computed) {
+            ui.label("(computed)");
+        }
     });
+    if flow.formula.is_some() {
+        match formula_error {
+            Some(error) => ui.colored_label(egui::Color32::RED, error),
+            None => ui.label(format!("= {}", flow.amount)),
+        };
+    }
+
+    uncertainty_egui(ui, flow);
 
     ui.separator();
     vcj_label!(ui, "Parameters");
-    parameters_list_egui(ui, flow);
+    parameters_list_egui(ui, flow, parameter_schemas, element_names);
 }
 
-fn parameters_list_egui(ui: &mut Ui, flow: &mut Flow) {
-    egui::Grid::new("Parameters List")
-        .striped(true)
-        .show(ui, |ui| {
-            if flow.parameters.is_empty() {
-                if ui.button("Add Parameter").clicked() {
-                    flow.parameters.push(Parameter::default());
+fn uncertainty_egui(ui: &mut Ui, flow: &mut Flow) {
+    h_label!(ui, "Amount Uncertainty");
+    let amount = flow.amount;
+    h_wrap!(ui, |ui| {
+        ComboBox::from_label("   ")
+            .selected_text(flow.uncertainty.map_or("None", |u| u.name()))
+            .show_ui(ui, |ui| {
+                ui.style_mut().wrap = Some(false);
+                ui.set_min_width(60.0);
+                ui.selectable_value(&mut flow.uncertainty, None, "None");
+                for uncertainty in [
+                    AmountUncertainty::Range {
+                        min: amount,
+                        max: amount,
+                    },
+                    AmountUncertainty::Uniform {
+                        min: amount,
+                        max: amount,
+                    },
+                    AmountUncertainty::Normal {
+                        std_dev: Decimal::ZERO,
+                    },
+                ] {
+                    let selected = flow
+                        .uncertainty
+                        .is_some_and(|u| u.name() == uncertainty.name());
+                    if ui.selectable_label(selected, uncertainty.name()).clicked() && !selected {
+                        flow.uncertainty = Some(uncertainty);
+                    }
                 }
+            });
+    });
+
+    let Some(uncertainty) = &mut flow.uncertainty else {
+        return;
+    };
+
+    egui::Grid::new("Amount Uncertainty").show(ui, |ui| {
+        let decimal_field = |ui: &mut Ui, label: &str, decimal: &mut Decimal| {
+            ui.label(label);
+            let mut decimal_string = decimal.to_string();
+            ui.add(egui::TextEdit::singleline(&mut decimal_string).desired_width(80.0));
+            only_valid_positive_decimal(&mut decimal_string, decimal);
+            ui.end_row();
+        };
+
+        match uncertainty {
+            AmountUncertainty::Range { min, max } | AmountUncertainty::Uniform { min, max } => {
+                decimal_field(ui, "Min", min);
+                decimal_field(ui, "Max", max);
+            }
+            AmountUncertainty::Normal { std_dev } => {
+                decimal_field(ui, "Std. Deviation", std_dev);
+            }
+        }
+    });
+}
+
+fn parameters_list_egui(
+    ui: &mut Ui,
+    flow: &mut Flow,
+    parameter_schemas: &ParameterSchemas,
+    element_names: &[String],
+) {
+    let schema = parameter_schemas.parameters_for(flow);
+
+    if flow.parameters.is_empty() && schema.is_empty() {
+        if ui.button("Add Parameter").clicked() {
+            flow.parameters.push(Parameter::default());
+        }
+        return;
+    }
+
+    parameter_grid_egui(ui, "Parameters List", &mut flow.parameters, element_names);
+
+    let errors = validate_parameters(
+        &flow.parameters,
+        &schema,
+        &element_names.iter().cloned().collect(),
+    );
+    for error in &errors {
+        ui.colored_label(egui::Color32::RED, error);
+    }
+
+    if errors.iter().any(|error| error.ends_with("is missing"))
+        && ui.button("Add Missing Schema Parameters").clicked()
+    {
+        parameter_schemas.add_missing_parameters(flow);
+    }
+}
+
+/// Editable grid of typed parameters. The value widget depends on the type of the parameter.
+fn parameter_grid_egui(
+    ui: &mut Ui,
+    id: &str,
+    parameters: &mut Vec<Parameter>,
+    element_names: &[String],
+) {
+    egui::Grid::new(id).striped(true).show(ui, |ui| {
+        if ui.button("Add").clicked() {
+            parameters.push(Parameter::default());
+        }
+        let min_size = egui::Vec2::new(100.0, 20.0);
+        ui.label("Name");
+        ui.label("Type");
+        ui.label("Value");
+        ui.end_row();
+        for idx in 0..parameters.len() {
+            if ui.button("Delete").clicked() {
+                parameters.remove(idx);
                 return;
             }
-            if ui.button("Add").clicked() {
-                flow.parameters.push(Parameter::default());
+            let parameter = &mut parameters[idx];
+            ui.add(
+                egui::TextEdit::singleline(&mut parameter.name)
+                    .hint_text("Name...")
+                    .min_size(min_size),
+            );
+
+            let mut kind = parameter.value.kind();
+            ComboBox::from_id_source((id, idx, "Type"))
+                .selected_text(kind.to_string())
+                .show_ui(ui, |ui| {
+                    for option in ParameterKind::ALL {
+                        ui.selectable_value(&mut kind, option, option.to_string());
+                    }
+                });
+            if kind != parameter.value.kind() {
+                parameter.value = parameter.value.convert_to(kind);
             }
-            let min_size = egui::Vec2::new(100.0, 20.0);
-            ui.label("Name");
-            ui.label("Value");
+
+            ui.horizontal(|ui| {
+                parameter_value_egui(ui, (id, idx), &mut parameter.value, element_names, true);
+            });
             ui.end_row();
-            for idx in 0..flow.parameters.len() {
-                if ui.button("Delete").clicked() {
-                    flow.parameters.remove(idx);
-                    return;
-                }
-                ui.add(
-                    egui::TextEdit::singleline(&mut flow.parameters[idx].name)
-                        .hint_text("Name...")
-                        .min_size(min_size),
-                );
-                ui.add(
-                    egui::TextEdit::singleline(&mut flow.parameters[idx].value)
-                        .hint_text("Value..")
-                        .min_size(min_size),
+        }
+    });
+}
+
+/// `edit_options` controls if the options of enumerations can be edited or only selected.
+fn parameter_value_egui(
+    ui: &mut Ui,
+    id: impl std::hash::Hash,
+    value: &mut ParameterValue,
+    element_names: &[String],
+    edit_options: bool,
+) {
+    match value {
+        ParameterValue::Text { value } => {
+            ui.add(egui::TextEdit::singleline(value).hint_text("Value.."));
+        }
+        ParameterValue::Number { value, unit } => {
+            let mut value_string = value.to_string();
+            ui.add(egui::TextEdit::singleline(&mut value_string).desired_width(60.0));
+            only_valid_positive_decimal(&mut value_string, value);
+            ui.add(
+                egui::TextEdit::singleline(unit)
+                    .hint_text("Unit...")
+                    .desired_width(40.0),
+            );
+        }
+        ParameterValue::Bool { value } => {
+            ui.add(Checkbox::without_text(value));
+        }
+        ParameterValue::Enum { value, options } => {
+            ComboBox::from_id_source((&id, "Enum"))
+                .selected_text(value.as_str())
+                .show_ui(ui, |ui| {
+                    for option in options.iter() {
+                        ui.selectable_value(value, option.clone(), option);
+                    }
+                });
+            let mut options_string = options.join(", ");
+            if edit_options
+                && ui
+                    .add(
+                        egui::TextEdit::singleline(&mut options_string)
+                            .hint_text("Option, Option...")
+                            .desired_width(100.0),
+                    )
+                    .changed()
+            {
+                *options = options_string
+                    .split(',')
+                    .map(|option| option.trim().to_string())
+                    .filter(|option| !option.is_empty())
+                    .collect();
+            }
+        }
+        ParameterValue::Date { value } => {
+            ui.add(
+                egui::TextEdit::singleline(value)
+                    .hint_text("YYYY-MM-DD")
+                    .desired_width(80.0),
+            );
+        }
+        ParameterValue::Reference { value } => {
+            ComboBox::from_id_source((&id, "Reference"))
+                .selected_text(value.as_str())
+                .show_ui(ui, |ui| {
+                    for name in element_names {
+                        ui.selectable_value(value, name.clone(), name);
+                    }
+                });
+        }
+    }
+}
+
+/// Generates the widgets for the attributes that the schema of the element type defines.
+fn attributes_egui(
+    ui: &mut Ui,
+    element_type: AttributeElementType,
+    attributes: &mut Vec<Parameter>,
+    attribute_schemas: &AttributeSchemas,
+    element_names: &[String],
+) {
+    let resolved = attribute_schemas.resolve(element_type, attributes);
+    if resolved.is_empty() {
+        return;
+    }
+
+    ui.separator();
+    vcj_label!(ui, "Attributes");
+
+    let mut edited = resolved.clone();
+    egui::Grid::new("Attributes").striped(true).show(ui, |ui| {
+        for (idx, attribute) in edited.iter_mut().enumerate() {
+            ui.label(attribute.name.trim());
+            ui.horizontal(|ui| {
+                parameter_value_egui(
+                    ui,
+                    ("Attributes", idx),
+                    &mut attribute.value,
+                    element_names,
+                    false,
                 );
-                ui.end_row();
+            });
+            ui.end_row();
+        }
+    });
+
+    let errors = validate_parameters(&edited, &[], &element_names.iter().cloned().collect());
+    for error in &errors {
+        ui.colored_label(egui::Color32::RED, error);
+    }
+
+    for (before, after) in resolved.iter().zip(edited) {
+        if *before == after {
+            continue;
+        }
+        match attributes
+            .iter_mut()
+            .find(|a| a.name.trim() == after.name.trim())
+        {
+            Some(existing) => *existing = after,
+            None => attributes.push(after),
+        }
+    }
+}
+
+fn transformation_rule_egui(ui: &mut Ui, system: &mut crate::components::System) {
+    let Some(rule) = &mut system.transformation_rule else {
+        if ui.button("Add Transformation Rule").clicked() {
+            system.transformation_rule = Some(TransformationRule::default());
+        }
+        return;
+    };
+
+    vcj_label!(ui, "Transformation Rule");
+    rule_terms_egui(ui, "Rule Inputs", &mut rule.inputs);
+    vc_label!(ui, "→");
+    rule_terms_egui(ui, "Rule Outputs", &mut rule.outputs);
+    vc_label!(ui, rule.to_string());
+
+    if ui.button("Remove Transformation Rule").clicked() {
+        system.transformation_rule = None;
+    }
+}
+
+fn rule_terms_egui(ui: &mut Ui, id: &str, terms: &mut Vec<RuleTerm>) {
+    egui::Grid::new(id).striped(true).show(ui, |ui| {
+        if ui.button("Add").clicked() {
+            terms.push(RuleTerm::default());
+        }
+        ui.label("Amount");
+        ui.label("Unit");
+        ui.label("Substance");
+        ui.end_row();
+        for idx in 0..terms.len() {
+            if ui.button("Delete").clicked() {
+                terms.remove(idx);
+                return;
             }
-        });
+            let term = &mut terms[idx];
+            let mut amount_string = term.amount.to_string();
+            ui.add(egui::TextEdit::singleline(&mut amount_string).desired_width(50.0));
+            only_valid_positive_decimal(&mut amount_string, &mut term.amount);
+            ui.add(
+                egui::TextEdit::singleline(&mut term.unit)
+                    .hint_text("Unit...")
+                    .desired_width(50.0),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut term.substance)
+                    .hint_text("Substance...")
+                    .desired_width(100.0),
+            );
+            ui.end_row();
+        }
+    });
 }
 
 pub fn only_valid_positive_decimal(s: &mut String, decimal: &mut Decimal) {
@@ -225,6 +681,8 @@ fn system_of_interest_egui(
     h_label!(ui, "Transformation");
     vcj_text_edit!(ui, &mut system.transformation, false);
 
+    transformation_rule_egui(ui, system);
+
     ui.separator();
     boundary_egui(ui, system);
 
@@ -285,6 +743,8 @@ fn subsystem_egui(
     h_label!(ui, "Transformation");
     vcj_text_edit!(ui, &mut system.transformation, false);
 
+    transformation_rule_egui(ui, system);
+
     ui.separator();
     boundary_egui(ui, system);
 
@@ -360,6 +820,98 @@ fn external_entity_egui(ui: &mut Ui, external_entity: &mut ExternalEntity) {
     vcj_text_edit!(ui, &mut external_entity.model, false);
 }
 
+/// Queries used to edit the position of subsystems and external entities and the angle of interfaces.
+#[derive(SystemParam)]
+pub struct Placement<'w, 's> {
+    transform_query: Query<'w, 's, &'static mut Transform, Without<Camera>>,
+    parent_query: Query<'w, 's, &'static Parent>,
+    interface_subsystem_query: Query<'w, 's, (), With<InterfaceSubsystem>>,
+    flow_query: Query<
+        'w,
+        's,
+        (
+            &'static FlowStartConnection,
+            &'static FlowEndConnection,
+            &'static FlowCurve,
+        ),
+        With<FlowCurve>,
+    >,
+    zoom: Res<'w, Zoom>,
+}
+
+/// Shows the position at zoom level 1 or the angle on the boundary of the parent system.
+/// `parent_radius` is the radius of the parent system, if there is one.
+fn placement_egui(
+    ui: &mut Ui,
+    entity: Entity,
+    system_element: &SystemElement,
+    parent_radius: Option<f32>,
+    placement: &mut Placement,
+) {
+    let zoom = **placement.zoom;
+    let Ok(mut transform) = placement.transform_query.get_mut(entity) else {
+        return;
+    };
+
+    match system_element {
+        SystemElement::Interface => {
+            let Some(radius) = parent_radius else {
+                return;
+            };
+            let mut angle = transform.translation.truncate().to_angle().to_degrees();
+            h_wrap!(ui, |ui| {
+                h_label!(ui, "Angle");
+                if ui
+                    .add(DragValue::new(&mut angle).speed(1.0).suffix("°"))
+                    .changed()
+                {
+                    let angle = angle.to_radians();
+                    let position = Vec2::from_angle(angle) * radius * zoom;
+                    transform.rotation = Quat::from_rotation_z(angle);
+                    transform.translation = position.extend(transform.translation.z);
+                }
+            });
+        }
+        SystemElement::System | SystemElement::ExternalEntity => {
+            let is_subsystem = *system_element == SystemElement::System;
+            if is_subsystem
+                && (placement.interface_subsystem_query.contains(entity)
+                    || !placement.parent_query.contains(entity))
+            {
+                return;
+            }
+            let mut position = transform.translation.truncate() / zoom;
+            h_wrap!(ui, |ui| {
+                h_label!(ui, "Position");
+                let x = ui.add(DragValue::new(&mut position.x).speed(1.0).prefix("x: "));
+                let y = ui.add(DragValue::new(&mut position.y).speed(1.0).prefix("y: "));
+                if x.changed() || y.changed() {
+                    if let (true, Some(radius)) = (is_subsystem, parent_radius) {
+                        position = position.clamp_length_max(radius);
+                    }
+                    transform.translation = (position * zoom).extend(transform.translation.z);
+                    if !is_subsystem {
+                        let (_, rotation) = external_entity_rotation(
+                            entity,
+                            transform.translation.truncate(),
+                            &placement.flow_query,
+                        );
+                        transform.rotation = rotation;
+                    }
+                }
+            });
+        }
+        SystemElement::Interaction => {}
+    }
+}
+
+/// Analysis results that are shown for the selected element.
+#[derive(SystemParam)]
+pub struct ElementAnalyses<'w> {
+    monte_carlo: Res<'w, MonteCarloAnalysis>,
+    protocol_problems: Res<'w, ProtocolProblems>,
+}
+
 /// Gets all the data associated with selectable system elements.
 /// Based on the System Element, it pipes relevant component data to different functions that control the UI for that System Element Type.
 pub fn egui_selected_context(
@@ -372,11 +924,19 @@ pub fn egui_selected_context(
         &mut ElementDescription,
     )>,
     mut interface_query: Query<&mut Interface>,
-    mut flow_query: Query<&mut Flow>,
+    mut flow_query: Query<(&mut Flow, Option<&mut FlowRoute>)>,
     mut system_environment_query: Query<&mut SystemEnvironment>,
     mut system_query: Query<&mut crate::components::System>,
     mut external_entity_query: Query<&mut ExternalEntity>,
     subsystem_query: Query<&crate::components::Subsystem>,
+    analyses: ElementAnalyses,
+    parameter_schemas: Res<ParameterSchemas>,
+    attribute_schemas: Res<AttributeSchemas>,
+    formula_errors: Res<FormulaErrors>,
+    protocols: Res<Protocols>,
+    substance_catalog: Res<SubstanceCatalog>,
+    mut placement: Placement,
+    mut description_links: DescriptionLinks,
 ) {
     let mut count = 0;
     for (_, selection, _, _, _) in &mut selectable_query {
@@ -395,13 +955,21 @@ pub fn egui_selected_context(
         info_hm.insert(entity, (name.to_string(), description.text.clone()));
     }
 
+    let mut element_names = info_hm
+        .values()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    element_names.sort();
+    element_names.dedup();
+
+    let link_index = LinkIndex::new(&info_hm);
+
     // Finds the currently selected System Element, defines the side panel layout, &
     // pipes the component data to an element type specific function that further defines the UI.
     for (entity, selection, system_element, mut name, mut description) in &mut selectable_query {
         if !selection.is_selected {
             continue;
         }
-        egui_contexts.ctx_mut().set_visuals(Visuals::light());
         egui_contexts.ctx_mut().style_mut(|style| {
             style.spacing.window_margin = Margin {
                 left: 10.0,
@@ -426,16 +994,29 @@ pub fn egui_selected_context(
                                 ui.text_edit_singleline(name);
                             });
                         });
-                        h_label!(ui, "Description");
-                        vcj_text_edit!(ui, &mut description.text, true);
+                        description_egui(
+                            ui,
+                            entity,
+                            &mut description,
+                            &link_index,
+                            &mut description_links,
+                        );
 
                         match system_element {
-                            SystemElement::Interface => interface_egui(
-                                ui,
-                                &mut interface_query
+                            SystemElement::Interface => {
+                                let mut interface = interface_query
                                     .get_mut(entity)
-                                    .expect("Interface not found"),
-                            ),
+                                    .expect("Interface not found");
+
+                                interface_egui(ui, &mut interface, &protocols);
+                                attributes_egui(
+                                    ui,
+                                    AttributeElementType::Interface,
+                                    &mut interface.attributes,
+                                    &attribute_schemas,
+                                    &element_names,
+                                );
+                            }
                             SystemElement::System => {
                                 let mut system =
                                     system_query.get_mut(entity).expect("System not found");
@@ -452,28 +1033,713 @@ pub fn egui_selected_context(
 
                                     subsystem_egui(ui, &mut system, parent_info);
                                 }
+
+                                attributes_egui(
+                                    ui,
+                                    AttributeElementType::System,
+                                    &mut system.attributes,
+                                    &attribute_schemas,
+                                    &element_names,
+                                );
                             }
-                            SystemElement::Interaction => interaction_egui(
-                                ui,
-                                &mut flow_query.get_mut(entity).expect("Interaction not found"),
-                            ),
-                            SystemElement::ExternalEntity => external_entity_egui(
-                                ui,
-                                &mut external_entity_query
+                            SystemElement::Interaction => {
+                                let (mut flow, route) =
+                                    flow_query.get_mut(entity).expect("Interaction not found");
+
+                                interaction_egui(
+                                    ui,
+                                    &mut flow,
+                                    formula_errors.get(&entity),
+                                    &parameter_schemas,
+                                    &substance_catalog,
+                                    &element_names,
+                                );
+                                if let Some(mut route) = route {
+                                    let mut routed = route.routed;
+                                    if ui.checkbox(&mut routed, "Route around obstacles").changed()
+                                    {
+                                        route.routed = routed;
+                                    }
+                                }
+                            }
+                            SystemElement::ExternalEntity => {
+                                let mut external_entity = external_entity_query
                                     .get_mut(entity)
-                                    .expect("External Entity not found"),
-                            ),
+                                    .expect("External Entity not found");
+
+                                external_entity_egui(ui, &mut external_entity);
+                                attributes_egui(
+                                    ui,
+                                    AttributeElementType::ExternalEntity,
+                                    &mut external_entity.attributes,
+                                    &attribute_schemas,
+                                    &element_names,
+                                );
+                            }
                         };
+
+                        let parent_radius = placement
+                            .parent_query
+                            .get(entity)
+                            .ok()
+                            .and_then(|parent| system_query.get(parent.get()).ok())
+                            .map(|system| system.radius);
+                        placement_egui(ui, entity, system_element, parent_radius, &mut placement);
+
+                        for problem in analyses.protocol_problems.for_element(entity) {
+                            ui.colored_label(egui::Color32::RED, &problem.message);
+                        }
+
+                        if let Some(result) = &analyses.monte_carlo.result {
+                            monte_carlo_element_egui(ui, result, entity);
+                        }
                     });
             });
     }
 }
 
+/// Resources that are changed from the View menu.
+#[derive(SystemParam)]
+pub struct ViewMenu<'w> {
+    themes: ResMut<'w, Themes>,
+    theme_editor: ResMut<'w, ThemeEditor>,
+    search_panel: ResMut<'w, SearchPanel>,
+    outline_panel: ResMut<'w, OutlinePanel>,
+    minimap: ResMut<'w, Minimap>,
+    bookmark_list: ResMut<'w, BookmarkList>,
+    fit_view_writer: EventWriter<'w, FitView>,
+    zoom_limits: ResMut<'w, ZoomLimits>,
+    snap_settings: ResMut<'w, SnapSettings>,
+    keybindings: Res<'w, Keybindings>,
+    keybindings_window: ResMut<'w, KeybindingsWindow>,
+    preferences_window: ResMut<'w, PreferencesWindow>,
+}
+
+/// The scenario window and the active scenario that edits currently change.
+#[derive(SystemParam)]
+pub struct ScenarioMenu<'w> {
+    editor: ResMut<'w, ScenarioEditor>,
+    scenarios: Res<'w, Scenarios>,
+}
+
+/// Defines the menu bar at the top of the window that gives access to the tool windows.
+pub fn egui_menu_bar(
+    mut egui_contexts: EguiContexts,
+    mut disruption_analysis: ResMut<DisruptionAnalysis>,
+    mut bottleneck_analysis: ResMut<BottleneckAnalysis>,
+    mut amount_propagation: ResMut<AmountPropagation>,
+    mut monte_carlo: ResMut<MonteCarloAnalysis>,
+    mut parameter_schema_editor: ResMut<ParameterSchemaEditor>,
+    mut attribute_schema_editor: ResMut<AttributeSchemaEditor>,
+    mut attribute_table: ResMut<AttributeTable>,
+    mut scenario_menu: ScenarioMenu,
+    mut protocol_editor: ResMut<ProtocolEditor>,
+    mut problems_list: ResMut<ProblemsList>,
+    mut substance_catalog_editor: ResMut<SubstanceCatalogEditor>,
+    mut view: ViewMenu,
+    mut auto_layout_writer: EventWriter<AutoLayout>,
+    mut create_annotation_writer: EventWriter<CreateAnnotation>,
+) {
+    egui::TopBottomPanel::top("Menu Bar").show(egui_contexts.ctx_mut(), |ui| {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button("Model", |ui| {
+                if ui.button("Parameter Schemas...").clicked() {
+                    parameter_schema_editor.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Attribute Schemas...").clicked() {
+                    attribute_schema_editor.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Substance Catalog...").clicked() {
+                    substance_catalog_editor.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Protocols...").clicked() {
+                    protocol_editor.open = true;
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui.button("Attribute Table...").clicked() {
+                    attribute_table.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Scenarios...").clicked() {
+                    scenario_menu.editor.open = true;
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui.button("Auto Layout Focused System").clicked() {
+                    auto_layout_writer.send(AutoLayout);
+                    ui.close_menu();
+                }
+            });
+            ui.menu_button("View", |ui| {
+                ui.checkbox(&mut view.outline_panel.open, "Outline");
+                ui.checkbox(&mut view.minimap.open, "Minimap");
+                ui.separator();
+                for (label, action, fit) in [
+                    ("Fit Model", KeyAction::FitModel, FitView::Model),
+                    ("Fit Selection", KeyAction::FitSelection, FitView::Selection),
+                    (
+                        "Fit Focused System",
+                        KeyAction::FitFocusedSystem,
+                        FitView::FocusedSystem,
+                    ),
+                ] {
+                    let shortcut = view.keybindings.get(action).label();
+                    if ui
+                        .add(egui::Button::new(label).shortcut_text(shortcut))
+                        .clicked()
+                    {
+                        view.fit_view_writer.send(fit);
+                        ui.close_menu();
+                    }
+                }
+                if ui.button("Camera Bookmarks...").clicked() {
+                    view.bookmark_list.open = true;
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui.button("Search...").clicked() {
+                    view.search_panel.open = true;
+                    ui.close_menu();
+                }
+                ui.menu_button("Theme", |ui| {
+                    let current = view.themes.current().name;
+                    for theme in view.themes.all() {
+                        if ui.radio(theme.name == current, &theme.name).clicked() {
+                            view.themes.active = theme.name;
+                            ui.close_menu();
+                        }
+                    }
+                });
+                if ui.button("Edit Themes...").clicked() {
+                    view.theme_editor.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Keyboard Shortcuts...").clicked() {
+                    view.keybindings_window.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Preferences...").clicked() {
+                    view.preferences_window.open = true;
+                    ui.close_menu();
+                }
+                ui.menu_button("Zoom Limits", |ui| {
+                    egui::Grid::new("Zoom Limits")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            let max = view.zoom_limits.max;
+                            ui.label("Minimum");
+                            ui.add(
+                                egui::DragValue::new(&mut view.zoom_limits.min)
+                                    .speed(0.01)
+                                    .clamp_range(0.01..=max),
+                            );
+                            ui.end_row();
+
+                            let min = view.zoom_limits.min;
+                            ui.label("Maximum");
+                            ui.add(
+                                egui::DragValue::new(&mut view.zoom_limits.max)
+                                    .speed(0.1)
+                                    .clamp_range(min..=100.0),
+                            );
+                            ui.end_row();
+                        });
+                });
+                ui.menu_button("Snapping", |ui| {
+                    ui.checkbox(&mut view.snap_settings.show_grid, "Show Grid");
+                    ui.checkbox(&mut view.snap_settings.grid_snap, "Snap to Grid");
+                    ui.horizontal(|ui| {
+                        ui.label("Grid Size");
+                        ui.add(
+                            egui::DragValue::new(&mut view.snap_settings.grid_size)
+                                .speed(1.0)
+                                .clamp_range(1.0..=500.0),
+                        );
+                    });
+                    ui.checkbox(&mut view.snap_settings.guides, "Alignment Guides");
+                    ui.separator();
+                    ui.checkbox(&mut view.snap_settings.angle_snap, "Snap Interface Angles");
+                    ui.horizontal(|ui| {
+                        ui.label("Angle Step (°)");
+                        ui.add(
+                            egui::DragValue::new(&mut view.snap_settings.angle_step)
+                                .speed(1.0)
+                                .clamp_range(1.0..=90.0),
+                        );
+                    });
+                });
+            });
+            ui.menu_button("Annotate", |ui| {
+                for kind in AnnotationKind::ALL {
+                    if ui
+                        .button(format!("Add {}", kind.label()))
+                        .on_hover_text("Attached to the selected element if there is one")
+                        .clicked()
+                    {
+                        create_annotation_writer.send(CreateAnnotation(kind));
+                        ui.close_menu();
+                    }
+                }
+            });
+            ui.menu_button("Analysis", |ui| {
+                if ui.button("Resilience...").clicked() {
+                    disruption_analysis.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Bottlenecks...").clicked() {
+                    bottleneck_analysis.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Propagate Amounts...").clicked() {
+                    amount_propagation.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Problems...").clicked() {
+                    problems_list.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Monte Carlo...").clicked() {
+                    monte_carlo.open = true;
+                    ui.close_menu();
+                }
+            });
+
+            if let Some(name) = scenario_menu.scenarios.active_name() {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let label = egui::RichText::new(format!("Scenario: {}", name))
+                        .color(ui.visuals().warn_fg_color);
+                    if ui
+                        .button(label)
+                        .on_hover_text("Edits change this scenario instead of the base model")
+                        .clicked()
+                    {
+                        scenario_menu.editor.open = true;
+                    }
+                });
+            }
+        });
+    });
+}
+
+/// Window to edit the parameters that flows of a substance are expected to have.
+pub fn parameter_schemas_egui(
+    mut egui_contexts: EguiContexts,
+    mut parameter_schema_editor: ResMut<ParameterSchemaEditor>,
+    mut parameter_schemas: ResMut<ParameterSchemas>,
+    name_query: Query<&Name, With<SystemElement>>,
+) {
+    if !parameter_schema_editor.open {
+        return;
+    }
+
+    let mut element_names = name_query
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    element_names.sort();
+    element_names.dedup();
+
+    egui::Window::new("Parameter Schemas")
+        .open(&mut parameter_schema_editor.open)
+        .default_width(420.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label("New flows of a substance are pre-populated with the parameters of its schemas. Leave the sub type empty to apply a schema to all flows of the substance type.");
+
+            if ui.button("Add Schema").clicked() {
+                parameter_schemas.push(ParameterSchema::default());
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let mut removed = None;
+
+                for (idx, schema) in parameter_schemas.iter_mut().enumerate() {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ComboBox::from_id_source(("Schema Substance Type", idx))
+                            .selected_text(format!("{:?}", schema.substance_type))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    &mut schema.substance_type,
+                                    SubstanceType::Energy,
+                                    "Energy",
+                                );
+                                ui.selectable_value(
+                                    &mut schema.substance_type,
+                                    SubstanceType::Material,
+                                    "Material",
+                                );
+                                ui.selectable_value(
+                                    &mut schema.substance_type,
+                                    SubstanceType::Message,
+                                    "Message",
+                                );
+                            });
+                        ui.add(
+                            egui::TextEdit::singleline(&mut schema.substance_sub_type)
+                                .hint_text("Sub type...")
+                                .desired_width(120.0),
+                        );
+                        if ui.button("Delete Schema").clicked() {
+                            removed = Some(idx);
+                        }
+                    });
+                    parameter_grid_egui(
+                        ui,
+                        &format!("Schema Parameters {}", idx),
+                        &mut schema.parameters,
+                        &element_names,
+                    );
+                }
+
+                if let Some(idx) = removed {
+                    parameter_schemas.remove(idx);
+                }
+            });
+        });
+}
+
+/// Edits the substances below `parent` and recurses into their children.
+fn substance_catalog_tree_egui(
+    ui: &mut Ui,
+    substance_catalog: &SubstanceCatalog,
+    substance_type: SubstanceType,
+    parent: Option<&str>,
+    depth: usize,
+    renaming: &mut Option<(usize, String)>,
+    edits: &mut Vec<SubstanceEdit>,
+) {
+    if depth > MAX_SUBSTANCE_DEPTH {
+        return;
+    }
+
+    for (idx, substance) in substance_catalog.children(substance_type, parent) {
+        ui.horizontal(|ui| {
+            ui.add_space(depth as f32 * 16.0);
+
+            let mut icon = substance.icon.clone();
+            if ui
+                .add(
+                    egui::TextEdit::singleline(&mut icon)
+                        .hint_text("Icon")
+                        .desired_width(30.0),
+                )
+                .changed()
+            {
+                edits.push(SubstanceEdit::Icon(idx, icon));
+            }
+
+            let mut name = match renaming {
+                Some((renamed_idx, name)) if *renamed_idx == idx => name.clone(),
+                _ => substance.name.clone(),
+            };
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut name)
+                    .hint_text("Name...")
+                    .desired_width(120.0),
+            );
+            if response.changed() {
+                *renaming = Some((idx, name.clone()));
+            }
+            // Losing the focus includes pressing enter.
+            if response.lost_focus()
+                && matches!(renaming, Some((renamed_idx, _)) if *renamed_idx == idx)
+            {
+                edits.push(SubstanceEdit::Rename(idx, name));
+            }
+
+            let mut color = substance.color;
+            let mut has_color = color.is_some();
+            if ui.checkbox(&mut has_color, "Color").changed() {
+                color = has_color.then(|| {
+                    let [r, g, b, _] = substance_type.flow_color().as_rgba_u8();
+                    [r, g, b]
+                });
+            }
+            if let Some(color) = &mut color {
+                ui.color_edit_button_srgb(color);
+            }
+            if color != substance.color {
+                edits.push(SubstanceEdit::Color(idx, color));
+            }
+
+            let mut default_unit = substance.default_unit.clone();
+            if ui
+                .add(
+                    egui::TextEdit::singleline(&mut default_unit)
+                        .hint_text("Unit...")
+                        .desired_width(50.0),
+                )
+                .changed()
+            {
+                edits.push(SubstanceEdit::DefaultUnit(idx, default_unit));
+            }
+
+            if ui.button("Add Child").clicked() {
+                edits.push(SubstanceEdit::AddChild(idx));
+            }
+            if ui.button("Delete").clicked() {
+                edits.push(SubstanceEdit::Remove(idx));
+            }
+        });
+
+        substance_catalog_tree_egui(
+            ui,
+            substance_catalog,
+            substance_type,
+            Some(&substance.name),
+            depth + 1,
+            renaming,
+            edits,
+        );
+    }
+}
+
+/// A change made in the substance catalog window. Edits are collected while the catalog is shown
+/// and applied afterwards so the catalog is only changed when the user actually edits it.
+enum SubstanceEdit {
+    Icon(usize, String),
+    Rename(usize, String),
+    Color(usize, Option<[u8; 3]>),
+    DefaultUnit(usize, String),
+    AddRoot(SubstanceType),
+    AddChild(usize),
+    Remove(usize),
+}
+
+/// Window to edit the hierarchical catalog of substances that flows pick their sub type from.
+pub fn substance_catalog_egui(
+    mut egui_contexts: EguiContexts,
+    mut substance_catalog_editor: ResMut<SubstanceCatalogEditor>,
+    mut substance_catalog: ResMut<SubstanceCatalog>,
+    mut flow_query: Query<&mut Flow>,
+) {
+    if !substance_catalog_editor.open {
+        return;
+    }
+
+    let editor = &mut *substance_catalog_editor;
+    let mut edits = vec![];
+
+    egui::Window::new("Substance Catalog")
+        .open(&mut editor.open)
+        .default_width(500.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label("Substances without a color use the color of their parent.");
+            if let Some(error) = &editor.rename_error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for substance_type in [
+                    SubstanceType::Energy,
+                    SubstanceType::Material,
+                    SubstanceType::Message,
+                ] {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.strong(format!("{:?}", substance_type));
+                        if ui.button("Add Substance").clicked() {
+                            edits.push(SubstanceEdit::AddRoot(substance_type));
+                        }
+                    });
+                    substance_catalog_tree_egui(
+                        ui,
+                        &substance_catalog,
+                        substance_type,
+                        None,
+                        1,
+                        &mut editor.renaming,
+                        &mut edits,
+                    );
+                }
+            });
+        });
+
+    for edit in edits {
+        match edit {
+            SubstanceEdit::Icon(idx, icon) => substance_catalog[idx].icon = icon,
+            SubstanceEdit::Color(idx, color) => substance_catalog[idx].color = color,
+            SubstanceEdit::DefaultUnit(idx, unit) => substance_catalog[idx].default_unit = unit,
+            SubstanceEdit::Rename(idx, new_name) => {
+                editor.renaming = None;
+
+                let new_name = new_name.trim().to_string();
+                let old_name = substance_catalog[idx].name.clone();
+                if new_name == old_name {
+                    editor.rename_error = None;
+                    continue;
+                }
+                if let Err(error) = substance_catalog.validate_name(idx, &new_name) {
+                    editor.rename_error = Some(error);
+                    continue;
+                }
+                editor.rename_error = None;
+
+                // Flows reference substances by name. Flows without a sub type don't reference any.
+                let substance_type = substance_catalog[idx].substance_type;
+                for mut flow in &mut flow_query {
+                    if flow.substance_type == substance_type
+                        && !old_name.trim().is_empty()
+                        && flow
+                            .substance_sub_type
+                            .trim()
+                            .eq_ignore_ascii_case(old_name.trim())
+                    {
+                        flow.substance_sub_type.clone_from(&new_name);
+                    }
+                }
+                substance_catalog.rename(idx, new_name);
+            }
+            SubstanceEdit::AddRoot(substance_type) => {
+                substance_catalog.push(SubstanceDefinition {
+                    name: "New Substance".to_string(),
+                    substance_type,
+                    ..default()
+                });
+            }
+            SubstanceEdit::AddChild(idx) => {
+                let parent = &substance_catalog[idx];
+                let child = SubstanceDefinition {
+                    name: format!("New {}", parent.name),
+                    substance_type: parent.substance_type,
+                    parent: Some(parent.name.clone()),
+                    default_unit: parent.default_unit.clone(),
+                    ..default()
+                };
+                substance_catalog.push(child);
+            }
+            SubstanceEdit::Remove(idx) => {
+                editor.renaming = None;
+                substance_catalog.remove(idx);
+            }
+        }
+    }
+}
+
+/// Window to edit the registry of protocols that interfaces can use.
+pub fn protocols_egui(
+    mut egui_contexts: EguiContexts,
+    mut protocol_editor: ResMut<ProtocolEditor>,
+    mut protocols: ResMut<Protocols>,
+) {
+    if !protocol_editor.open {
+        return;
+    }
+
+    egui::Window::new("Protocols")
+        .open(&mut protocol_editor.open)
+        .default_width(420.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label("Interfaces at both ends of an interaction need protocols with the same name and major version that carry the substance of the interaction.");
+
+            if ui.button("Add Protocol").clicked() {
+                protocols.push(Protocol::default());
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let mut removed = None;
+
+                for (idx, protocol) in protocols.iter_mut().enumerate() {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut protocol.name)
+                                .hint_text("Name...")
+                                .desired_width(120.0),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(&mut protocol.version)
+                                .hint_text("Version...")
+                                .desired_width(50.0),
+                        );
+                        ComboBox::from_id_source(("Protocol Substance Type", idx))
+                            .selected_text(format!("{:?}", protocol.substance_type))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    &mut protocol.substance_type,
+                                    SubstanceType::Energy,
+                                    "Energy",
+                                );
+                                ui.selectable_value(
+                                    &mut protocol.substance_type,
+                                    SubstanceType::Material,
+                                    "Material",
+                                );
+                                ui.selectable_value(
+                                    &mut protocol.substance_type,
+                                    SubstanceType::Message,
+                                    "Message",
+                                );
+                            });
+                        if ui.button("Delete").clicked() {
+                            removed = Some(idx);
+                        }
+                    });
+                    ui.add(
+                        egui::TextEdit::multiline(&mut protocol.specification)
+                            .hint_text("Message schema or physical specification...")
+                            .desired_rows(2),
+                    );
+                }
+
+                if let Some(idx) = removed {
+                    protocols.remove(idx);
+                }
+            });
+        });
+}
+
+/// Window to edit the attributes that systems, interfaces & external entities have.
+pub fn attribute_schemas_egui(
+    mut egui_contexts: EguiContexts,
+    mut attribute_schema_editor: ResMut<AttributeSchemaEditor>,
+    mut attribute_schemas: ResMut<AttributeSchemas>,
+    name_query: Query<&Name, With<SystemElement>>,
+) {
+    if !attribute_schema_editor.open {
+        return;
+    }
+
+    let mut element_names = name_query
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    element_names.sort();
+    element_names.dedup();
+
+    egui::Window::new("Attribute Schemas")
+        .open(&mut attribute_schema_editor.open)
+        .default_width(420.0)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label("Every element of a type has the attributes of its schema. The values are the defaults of elements that haven't set the attribute yet.");
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for element_type in AttributeElementType::ALL {
+                    egui::CollapsingHeader::new(element_type.to_string())
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            parameter_grid_egui(
+                                ui,
+                                &format!("{} Attribute Schema", element_type),
+                                attribute_schemas.schema_mut(element_type),
+                                &element_names,
+                            );
+                        });
+                }
+            });
+        });
+}
+
 /// When the user is interacting with EGUI, prevent the user input from effecting the diagram.
 pub fn absorb_egui_inputs(
     mut contexts: EguiContexts,
     mut mouse: ResMut<ButtonInput<MouseButton>>,
     mut mouse_wheel: ResMut<Events<MouseWheel>>,
+    mut magnify: ResMut<Events<TouchpadMagnify>>,
     mut keyboard: ResMut<ButtonInput<KeyCode>>,
 ) {
     let ctx = contexts.ctx_mut();
@@ -493,6 +1759,7 @@ pub fn absorb_egui_inputs(
 
         mouse.reset_all();
         mouse_wheel.clear();
+        magnify.clear();
         keyboard.reset_all();
 
         // for key in pressed.into_iter().flatten() {
@@ -2,6 +2,7 @@
 use crate::bundles::spawn_main_system;
 use crate::constants::*;
 use crate::data_model::Complexity;
+use crate::plugins::preferences::Preferences;
 use crate::resources::*;
 use bevy::prelude::*;
 use bevy::render::deterministic::DeterministicRenderingConfig;
@@ -24,6 +25,7 @@ pub fn window_setup(mut primary_window_query: Query<&mut Window, With<PrimaryWin
 pub fn setup(
     mut commands: Commands,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
     mut meshes: ResMut<Assets<Mesh>>,
     tess: ResMut<StrokeTessellator>,
     geometries: ResMut<FixedSystemElementGeometriesByNestingLevel>,
@@ -42,6 +44,7 @@ pub fn setup(
         Complexity::default(),
         Default::default(),
         **zoom,
+        preferences.subsystem_scaling_factor,
         "System",
         "",
         &mut meshes,
@@ -83,6 +86,7 @@ pub fn init_complete_system(
     mut stroke_tess: ResMut<StrokeTessellator>,
     mut fixed_system_element_geometries: ResMut<FixedSystemElementGeometriesByNestingLevel>,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
 ) {
     use crate::bundles::*;
     use crate::components::*;
@@ -98,6 +102,8 @@ pub fn init_complete_system(
         &mut stroke_tess,
         &mut fixed_system_element_geometries,
         **zoom,
+        preferences.subsystem_scaling_factor,
+        preferences.flow_length,
         0.0,
         MAIN_SYSTEM_RADIUS,
         Default::default(),
@@ -123,6 +129,8 @@ pub fn init_complete_system(
         &mut stroke_tess,
         &mut fixed_system_element_geometries,
         **zoom,
+        preferences.subsystem_scaling_factor,
+        preferences.flow_length,
         -std::f32::consts::FRAC_PI_4,
         MAIN_SYSTEM_RADIUS,
         Default::default(),
@@ -148,6 +156,8 @@ pub fn init_complete_system(
         &mut stroke_tess,
         &mut fixed_system_element_geometries,
         **zoom,
+        preferences.subsystem_scaling_factor,
+        preferences.flow_length,
         std::f32::consts::PI,
         MAIN_SYSTEM_RADIUS,
         Default::default(),
@@ -173,6 +183,8 @@ pub fn init_complete_system(
         &mut stroke_tess,
         &mut fixed_system_element_geometries,
         **zoom,
+        preferences.subsystem_scaling_factor,
+        preferences.flow_length,
         std::f32::consts::FRAC_PI_4 * 3.0,
         MAIN_SYSTEM_RADIUS,
         Default::default(),
@@ -1,8 +1,7 @@
 use crate::components::*;
-use crate::constants::{
-    SUBSYSTEM_FULL_SIZE_INTERFACE_COUNT, SUBSYSTEM_MIN_SCALING_FACTOR, SUBSYSTEM_SCALING_FACTOR,
-};
+use crate::constants::SUBSYSTEM_MIN_SCALING_FACTOR;
 use crate::events::RemoveEvent;
+use crate::plugins::preferences::Preferences;
 use crate::resources::Zoom;
 use crate::utils::{all_flow_end_connected_systems, all_flow_start_connected_systems};
 use bevy::prelude::*;
@@ -97,20 +96,27 @@ pub fn update_subsystem_radius_from_interface_count(
         Option<&Children>,
         Option<&InterfaceSubsystem>,
         &Parent,
+        &NestingLevel,
     )>,
     interface_query: Query<&Interface>,
     mut system_query: Query<&mut crate::components::System>,
     mut transform_query: Query<(&mut Transform, &mut InitialPosition)>,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
     mut remove_event_reader: EventReader<RemoveEvent>,
 ) {
-    if changed_query.is_empty() && remove_event_reader.is_empty() {
+    let preferences_changed = preferences.is_changed() && !preferences.is_added();
+    if changed_query.is_empty() && remove_event_reader.is_empty() && !preferences_changed {
         return;
     }
 
     remove_event_reader.clear();
 
-    for (subsystem_entity, subsystem, children, interface_subsystem, parent) in &subsystem_query {
+    // Parents first so that their subsystems are sized from their updated radius.
+    let mut subsystems = subsystem_query.iter().collect::<Vec<_>>();
+    subsystems.sort_by_key(|(.., nesting_level)| ***nesting_level);
+
+    for (subsystem_entity, subsystem, children, interface_subsystem, parent, _) in subsystems {
         let mut interface_count = 0;
 
         if interface_subsystem.is_some() {
@@ -123,11 +129,12 @@ pub fn update_subsystem_radius_from_interface_count(
             }
         }
 
+        let max_scaling_factor = preferences.subsystem_scaling_factor;
         let mut scaling_factor = SUBSYSTEM_MIN_SCALING_FACTOR
-            + interface_count as f32 * (SUBSYSTEM_SCALING_FACTOR - SUBSYSTEM_MIN_SCALING_FACTOR)
-                / SUBSYSTEM_FULL_SIZE_INTERFACE_COUNT;
+            + interface_count as f32 * (max_scaling_factor - SUBSYSTEM_MIN_SCALING_FACTOR)
+                / preferences.subsystem_full_size_interface_count;
 
-        scaling_factor = scaling_factor.min(SUBSYSTEM_SCALING_FACTOR);
+        scaling_factor = scaling_factor.min(max_scaling_factor);
 
         let parent_radius = system_query
             .get(subsystem.parent_system)
@@ -3,28 +3,38 @@ use crate::components::{
     Connection, CreateButton, Flow, HasFlowOtherEndButton, InterfaceSubsystem, TargetTypeConnection,
 };
 use crate::plugins::lyon_selection::HighlightBundles;
+use crate::plugins::theme::Themes;
+use crate::resources::SubstanceCatalog;
 use crate::{Interface, Subsystem};
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
 
-/// Update the color of a flow and it's connected external entities based on the flow substance type.
+/// Update the color of a flow and it's connected external entities based on the flow substance
+/// type, the color of its substance in the catalog and the active theme.
 pub fn update_color_from_substance_type<C>(
-    mut query: Query<
-        (
-            &Flow,
-            &mut HighlightBundles<Stroke, Stroke>,
-            &Children,
-            Option<&C>,
-        ),
-        Or<(Added<Flow>, Changed<Flow>)>,
-    >,
+    mut query: Query<(
+        Ref<Flow>,
+        &mut HighlightBundles<Stroke, Stroke>,
+        &Children,
+        Option<&C>,
+    )>,
     mut external_entity_query: Query<&mut HighlightBundles<Stroke, Stroke>, Without<Flow>>,
     mut arrow_query: Query<&mut Fill>,
+    substance_catalog: Res<SubstanceCatalog>,
+    themes: Res<Themes>,
 ) where
     C: Connection + TargetTypeConnection + Component,
 {
+    let theme = themes.current();
+
     for (flow, mut highlight, children, external_entity_connection) in &mut query {
-        let color = flow.substance_type.flow_color();
+        if !flow.is_changed() && !substance_catalog.is_changed() && !themes.is_changed() {
+            continue;
+        }
+
+        let color = substance_catalog
+            .color(flow.substance_type, &flow.substance_sub_type)
+            .unwrap_or_else(|| theme.flow_color(flow.substance_type));
         highlight.idle.color = color;
         highlight.selected.color = color;
 
@@ -62,35 +72,50 @@ pub fn update_button_substance_type_from_flow(
 
 /// Update the color of an interface based the flow substance type.
 pub fn update_interface_color_from_flow<C>(
-    mut query: Query<(&Flow, &C), Or<(Added<Flow>, Changed<Flow>)>>,
+    query: Query<(Ref<Flow>, &C)>,
     mut interface_query: Query<&mut Fill, (Without<Flow>, With<Interface>)>,
+    substance_catalog: Res<SubstanceCatalog>,
+    themes: Res<Themes>,
 ) where
     C: Connection + Component,
 {
-    for (flow, interface_connection) in &mut query {
+    let theme = themes.current();
+
+    for (flow, interface_connection) in &query {
+        if !flow.is_changed() && !substance_catalog.is_changed() && !themes.is_changed() {
+            continue;
+        }
+
         if let Ok(mut interface_fill) = interface_query.get_mut(interface_connection.target()) {
-            interface_fill.color = flow.substance_type.interface_color();
+            interface_fill.color =
+                match substance_catalog.color(flow.substance_type, &flow.substance_sub_type) {
+                    Some(color) => theme.interface_shade(color),
+                    None => theme.interface_color(flow.substance_type),
+                };
         }
     }
 }
 
 /// Update the color of an interface subsystem based on the substance type of the parent interface.
 pub fn update_interface_subsystem_color(
-    mut interface_subsystem_query: Query<
-        (Entity, &mut Fill, &InterfaceSubsystem),
-        Changed<InterfaceSubsystem>,
-    >,
+    mut interface_subsystem_query: Query<(Entity, &mut Fill, Ref<InterfaceSubsystem>)>,
     subsystem_query: Query<&Subsystem>,
+    themes: Res<Themes>,
 ) {
+    let theme = themes.current();
+
     'outer: for (system_entity, mut subsystem_fill, interface_subsystem) in
         &mut interface_subsystem_query
     {
+        if !interface_subsystem.is_changed() && !themes.is_changed() {
+            continue;
+        }
         for subsystem in &subsystem_query {
             if subsystem.parent_system == system_entity {
                 continue 'outer;
             }
         }
-        subsystem_fill.color = interface_subsystem.substance_type.interface_color();
+        subsystem_fill.color = theme.interface_color(interface_subsystem.substance_type);
     }
 }
 
@@ -1,6 +1,8 @@
 use crate::components::*;
 use crate::constants::EXTERNAL_ENTITY_WIDTH_HALF;
 use crate::events::*;
+use crate::plugins::layout::SnapSettings;
+use crate::plugins::preferences::Preferences;
 use crate::resources::Zoom;
 use crate::utils::{
     compute_end_and_direction_from_subsystem, compute_end_and_direction_from_system_child,
@@ -60,49 +62,39 @@ pub fn drag_external_entity(
         transform.translation.x = event.position.x;
         transform.translation.y = event.position.y;
 
-        let mut system = Entity::PLACEHOLDER;
-        let mut other_end = Vec2::ZERO;
-        let mut other_end_direction = Vec2::ZERO;
-        let mut tangent_len = 0.0;
-
-        for (start_connection, end_connection, flow_curve) in &flow_query {
-            if start_connection.target == event.target {
-                debug_assert!(end_connection.target_type == EndTargetType::System);
-                system = end_connection.target;
-                other_end = flow_curve.end;
-                other_end_direction = flow_curve.end_direction;
-                tangent_len = flow_curve.compute_tangent_length();
-                break;
-            } else if end_connection.target == event.target {
-                debug_assert!(start_connection.target_type == StartTargetType::System);
-                system = start_connection.target;
-                other_end = flow_curve.start;
-                other_end_direction = flow_curve.start_direction;
-                tangent_len = flow_curve.compute_tangent_length();
-                break;
-            }
-        }
+        let (system, rotation) =
+            external_entity_rotation(event.target, event.position, &flow_query);
+        transform.rotation = rotation;
 
-        transform.rotation = compute_external_entity_rotation(
+        transform.translation = clamp_to_parent_system(
+            system,
             event.position,
-            other_end,
-            other_end_direction,
-            tangent_len,
-        );
-
-        if let Ok(subsystem) = subsystem_query.get(system) {
-            let parent_system = system_query
-                .get(subsystem.parent_system)
-                .expect("Parent system has to exist");
-            transform.translation = transform
-                .translation
-                .truncate()
-                .clamp_length_max(parent_system.radius * **zoom)
-                .extend(transform.translation.z);
-        }
+            &subsystem_query,
+            &system_query,
+            **zoom,
+        )
+        .extend(transform.translation.z);
     }
 }
 
+/// Keeps a position inside the parent system of `system` if that's a subsystem. The external
+/// entities of a subsystem stay inside its parent like the subsystem itself.
+pub fn clamp_to_parent_system(
+    system: Entity,
+    position: Vec2,
+    subsystem_query: &Query<&Subsystem>,
+    system_query: &Query<&crate::components::System>,
+    zoom: f32,
+) -> Vec2 {
+    let Ok(subsystem) = subsystem_query.get(system) else {
+        return position;
+    };
+    let parent_system = system_query
+        .get(subsystem.parent_system)
+        .expect("Parent system has to exist");
+    position.clamp_length_max(parent_system.radius * zoom)
+}
+
 pub fn update_flow_from_external_entity(
     external_entity_query: Query<
         (Entity, &Transform, &NestingLevel),
@@ -110,10 +102,11 @@ pub fn update_flow_from_external_entity(
     >,
     mut flow_query: Query<(&mut FlowCurve, &FlowStartConnection, &FlowEndConnection)>,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
 ) {
     for (target, transform, nesting_level) in &external_entity_query {
         for (mut flow_curve, flow_start_connection, flow_end_connection) in &mut flow_query {
-            let scale = NestingLevel::compute_scale(**nesting_level, **zoom);
+            let scale = preferences.nesting_scale(**nesting_level, **zoom);
 
             if flow_start_connection.target == target {
                 let right = transform.right().truncate();
@@ -143,9 +136,10 @@ pub fn update_flow_from_interface(
         Option<&FlowEndInterfaceConnection>,
     )>,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
 ) {
     for (target, nesting_level) in &interface_query {
-        let scale = NestingLevel::compute_scale(**nesting_level, **zoom);
+        let scale = preferences.nesting_scale(**nesting_level, **zoom);
 
         for (mut flow_curve, flow_parent, flow_start_connection, flow_end_connection) in
             &mut flow_query
@@ -266,6 +260,7 @@ pub fn drag_interface(
         &FlowEndConnection,
     )>,
     zoom: Res<Zoom>,
+    snap_settings: Res<SnapSettings>,
 ) {
     for event in events.read() {
         if event.has_bubbled() {
@@ -289,10 +284,10 @@ pub fn drag_interface(
 
         let interface_pos = transform.translation.truncate();
 
-        let mut pos = interface_pos;
-        pos *= system.radius * **zoom / pos.length();
+        let angle = snap_settings.snap_angle(interface_pos.to_angle());
+        let pos = Vec2::from_angle(angle) * system.radius * **zoom;
 
-        transform.rotation = Quat::from_rotation_z(pos.to_angle());
+        transform.rotation = Quat::from_rotation_z(angle);
 
         transform.translation = pos.extend(transform.translation.z);
 
@@ -356,6 +351,41 @@ pub fn update_initial_position_from_transform(
     }
 }
 
+/// Finds the system that an external entity is connected to and the rotation that makes the
+/// external entity at `position` face along its flow.
+pub fn external_entity_rotation(
+    external_entity: Entity,
+    position: Vec2,
+    flow_query: &Query<(&FlowStartConnection, &FlowEndConnection, &FlowCurve), With<FlowCurve>>,
+) -> (Entity, Quat) {
+    let mut system = Entity::PLACEHOLDER;
+    let mut other_end = Vec2::ZERO;
+    let mut other_end_direction = Vec2::ZERO;
+    let mut tangent_len = 0.0;
+
+    for (start_connection, end_connection, flow_curve) in flow_query {
+        if start_connection.target == external_entity {
+            debug_assert!(end_connection.target_type == EndTargetType::System);
+            system = end_connection.target;
+            other_end = flow_curve.end;
+            other_end_direction = flow_curve.end_direction;
+            tangent_len = flow_curve.compute_tangent_length();
+            break;
+        } else if end_connection.target == external_entity {
+            debug_assert!(start_connection.target_type == StartTargetType::System);
+            system = start_connection.target;
+            other_end = flow_curve.start;
+            other_end_direction = flow_curve.start_direction;
+            tangent_len = flow_curve.compute_tangent_length();
+            break;
+        }
+    }
+
+    let rotation =
+        compute_external_entity_rotation(position, other_end, other_end_direction, tangent_len);
+    (system, rotation)
+}
+
 fn compute_external_entity_rotation(
     pos: Vec2,
     other_end: Vec2,
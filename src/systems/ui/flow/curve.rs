@@ -1,5 +1,7 @@
 use crate::components::{FlowCurve, NestingLevel};
 use crate::constants::{FLOW_ARROW_HEAD_LENGTH, FLOW_CLICK_TOLERANCE, FLOW_CLICK_WIDTH};
+use crate::plugins::layout::FlowRoute;
+use crate::plugins::preferences::Preferences;
 use crate::resources::{StrokeTessellator, Zoom};
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology};
@@ -20,24 +22,28 @@ pub fn draw_flow_curve(
             &mut Aabb,
             &Children,
             &NestingLevel,
+            Option<&FlowRoute>,
         ),
-        Changed<FlowCurve>,
+        Or<(Changed<FlowCurve>, Changed<FlowRoute>)>,
     >,
     mut transform_query: Query<&mut Transform, With<Path>>,
     mut stroke_tess: ResMut<StrokeTessellator>,
     mut meshes: ResMut<Assets<Mesh>>,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
 ) {
-    for (flow_curve, path, simplified_mesh, aabb, children, nesting_level) in &mut query {
+    for (flow_curve, path, simplified_mesh, aabb, children, nesting_level, route) in &mut query {
         update_flow_curve(
             &mut transform_query,
             flow_curve,
+            route.map_or(&[], |r| &r.waypoints),
             path,
             simplified_mesh,
             aabb,
             children,
             **nesting_level,
             **zoom,
+            preferences.subsystem_scaling_factor,
             &mut stroke_tess,
             &mut meshes,
         );
@@ -47,24 +53,26 @@ pub fn draw_flow_curve(
 pub fn update_flow_curve(
     transform_query: &mut Query<&mut Transform, With<Path>>,
     flow_curve: &FlowCurve,
+    waypoints: &[Vec2],
     mut path: Mut<Path>,
     mut simplified_mesh: Mut<SimplifiedMesh>,
     mut aabb: Mut<Aabb>,
     children: &Children,
     nesting_level: u16,
     zoom: f32,
+    scaling_factor: f32,
     stroke_tess: &mut ResMut<StrokeTessellator>,
     meshes: &mut ResMut<Assets<Mesh>>,
 ) {
-    let scale = NestingLevel::compute_scale(nesting_level, zoom);
+    let scale = NestingLevel::compute_scale(nesting_level, zoom, scaling_factor);
 
-    let curve_path = create_path_from_flow_curve(flow_curve, scale);
+    let curve_path = create_path_from_flow_curve(flow_curve, waypoints, scale);
 
     let simplified_curve = flow_curve.skip_start();
-    let simplified_curve_path = create_path_from_flow_curve(&simplified_curve, scale);
+    let simplified_curve_path = create_path_from_flow_curve(&simplified_curve, waypoints, scale);
 
     simplified_mesh.mesh = tessellate_simplified_mesh(&simplified_curve_path, meshes, stroke_tess);
-    *aabb = create_aabb_from_flow_curve(&simplified_curve);
+    *aabb = create_aabb_from_flow_curve(&simplified_curve, waypoints);
 
     *path = curve_path;
 
@@ -125,7 +133,10 @@ pub fn tessellate_simplified_mesh(
     meshes.add(mesh)
 }
 
-pub fn create_path_from_flow_curve(flow_curve: &FlowCurve, scale: f32) -> Path {
+/// Builds the path of a flow. Without waypoints this is a single cubic Bezier. With waypoints
+/// the path is a smooth chain of cubic Beziers through them whose tangent at each waypoint
+/// points from the previous to the next point.
+pub fn create_path_from_flow_curve(flow_curve: &FlowCurve, waypoints: &[Vec2], scale: f32) -> Path {
     let mut curve_path_builder = PathBuilder::new();
 
     let start = flow_curve.start;
@@ -136,26 +147,57 @@ pub fn create_path_from_flow_curve(flow_curve: &FlowCurve, scale: f32) -> Path {
     let end_direction = flow_curve.end_direction;
     let end = end + end_direction * (FLOW_ARROW_HEAD_LENGTH - 2.0) * scale;
 
-    let tangent_len = flow_curve.compute_tangent_length();
+    if waypoints.is_empty() {
+        let tangent_len = flow_curve.compute_tangent_length();
 
-    curve_path_builder.cubic_bezier_to(
-        start + flow_curve.start_direction * tangent_len,
-        end + flow_curve.end_direction * tangent_len,
-        end,
-    );
+        curve_path_builder.cubic_bezier_to(
+            start + flow_curve.start_direction * tangent_len,
+            end + flow_curve.end_direction * tangent_len,
+            end,
+        );
+    } else {
+        let points = std::iter::once(start)
+            .chain(waypoints.iter().copied())
+            .chain(std::iter::once(end))
+            .collect::<Vec<_>>();
+        let last = points.len() - 1;
+        let tangent = |i: usize| {
+            if i == 0 {
+                flow_curve.start_direction
+            } else if i == last {
+                -flow_curve.end_direction
+            } else {
+                (points[i + 1] - points[i - 1]).normalize_or_zero()
+            }
+        };
+
+        for i in 0..last {
+            let tangent_len =
+                FlowCurve::compute_tangent_length_from_points(points[i], points[i + 1]);
+            curve_path_builder.cubic_bezier_to(
+                points[i] + tangent(i) * tangent_len,
+                points[i + 1] - tangent(i + 1) * tangent_len,
+                points[i + 1],
+            );
+        }
+    }
 
     curve_path_builder.build()
 }
 
-pub fn create_aabb_from_flow_curve(flow_curve: &FlowCurve) -> Aabb {
+pub fn create_aabb_from_flow_curve(flow_curve: &FlowCurve, waypoints: &[Vec2]) -> Aabb {
     let tangent_length = flow_curve.compute_tangent_length();
 
-    let mut aabb = Aabb::enclosing([
-        (flow_curve.start).extend(0.0),
-        (flow_curve.start + flow_curve.start_direction * tangent_length).extend(0.0),
-        (flow_curve.end + flow_curve.end_direction * tangent_length).extend(0.0),
-        (flow_curve.end).extend(0.0),
-    ])
+    let mut aabb = Aabb::enclosing(
+        [
+            (flow_curve.start).extend(0.0),
+            (flow_curve.start + flow_curve.start_direction * tangent_length).extend(0.0),
+            (flow_curve.end + flow_curve.end_direction * tangent_length).extend(0.0),
+            (flow_curve.end).extend(0.0),
+        ]
+        .into_iter()
+        .chain(waypoints.iter().map(|w| w.extend(0.0))),
+    )
     .expect("Iterator is not empty so there has to be an Aabb");
 
     aabb.half_extents.x += FLOW_CLICK_WIDTH;
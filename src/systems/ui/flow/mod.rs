@@ -1,5 +1,7 @@
 mod curve;
+mod parameters;
 mod terminal_selecting;
 
 pub use curve::*;
+pub use parameters::*;
 pub use terminal_selecting::*;
@@ -0,0 +1,16 @@
+use crate::components::Flow;
+use crate::resources::ParameterSchemas;
+use bevy::prelude::*;
+
+/// Pre-populates the parameters of newly created flows from the parameter schemas.
+/// Flows that already have parameters (e.g. when loaded from a file) are left untouched.
+pub fn apply_parameter_schemas_to_new_flows(
+    mut flow_query: Query<&mut Flow, Added<Flow>>,
+    parameter_schemas: Res<ParameterSchemas>,
+) {
+    for mut flow in &mut flow_query {
+        if flow.parameters.is_empty() {
+            flow.parameters = parameter_schemas.parameters_for(&flow);
+        }
+    }
+}
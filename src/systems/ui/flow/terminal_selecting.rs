@@ -1,6 +1,7 @@
 use crate::bundles::spawn_external_entity;
 use crate::components::*;
 use crate::plugins::mouse_interaction::{MouseWorldPosition, PickTarget};
+use crate::plugins::preferences::Preferences;
 use crate::resources::{
     FixedSystemElementGeometriesByNestingLevel, FocusedSystem, StrokeTessellator, Zoom,
 };
@@ -75,6 +76,7 @@ pub fn select_flow_terminal(
     focused_system: Res<FocusedSystem>,
     mut fixed_system_element_geometries: ResMut<FixedSystemElementGeometriesByNestingLevel>,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut tess: ResMut<StrokeTessellator>,
 ) {
@@ -152,6 +154,7 @@ pub fn select_flow_terminal(
                         ),
                         &mut fixed_system_element_geometries,
                         **zoom,
+                        preferences.subsystem_scaling_factor,
                         true,
                         &mut meshes,
                         &mut tess,
@@ -175,6 +178,7 @@ pub fn select_flow_terminal(
                         ),
                         &mut fixed_system_element_geometries,
                         **zoom,
+                        preferences.subsystem_scaling_factor,
                         true,
                         &mut meshes,
                         &mut tess,
@@ -1,6 +1,8 @@
 use crate::components::*;
 use crate::constants::{INTERFACE_HEIGHT_HALF, INTERFACE_WIDTH_HALF};
 use crate::plugins::label::{CopyPosition, NameLabel};
+use crate::plugins::layout::FlowRoute;
+use crate::plugins::preferences::Preferences;
 use crate::resources::Zoom;
 use bevy::math::vec3;
 use bevy::prelude::*;
@@ -14,9 +16,10 @@ pub fn update_label_offset_from_interface(
         (Changed<GlobalTransform>, With<Interface>),
     >,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
 ) {
     for (mut copy_position, global_transform, nesting_level) in &mut query {
-        let scale = NestingLevel::compute_scale(**nesting_level, **zoom);
+        let scale = preferences.nesting_scale(**nesting_level, **zoom);
 
         let right = global_transform.right();
 
@@ -29,16 +32,21 @@ pub fn update_label_offset_from_interface(
 
 pub fn update_label_from_interaction(
     interaction_query: Query<
-        (&Path, &NameLabel, &GlobalTransform),
+        (&Path, &NameLabel, &GlobalTransform, Option<&FlowRoute>),
         (
             With<Flow>,
-            Or<(Changed<Path>, Changed<GlobalTransform>, Added<NameLabel>)>,
+            Or<(
+                Changed<Path>,
+                Changed<GlobalTransform>,
+                Changed<FlowRoute>,
+                Added<NameLabel>,
+            )>,
         ),
     >,
     parent_query: Query<&Parent>,
     mut transform_query: Query<&mut Transform>,
 ) {
-    for (path, name_label, global_transform) in &interaction_query {
+    for (path, name_label, global_transform, route) in &interaction_query {
         let sprite_entity = parent_query
             .get(name_label.label)
             .expect("Label should have a Parent")
@@ -72,6 +80,7 @@ pub fn update_label_from_interaction(
             interval: 1.0,
         };
 
-        walk_along_path(&path.0, path_len * 0.5, 0.1, &mut pattern);
+        let label_position = route.map_or(0.5, |route| route.label_position);
+        walk_along_path(&path.0, path_len * label_position, 0.1, &mut pattern);
     }
 }
@@ -7,6 +7,7 @@ mod selected_helper;
 mod zoom;
 
 use crate::plugins::mouse_interaction::{do_deselect_all, PickSelection};
+use crate::plugins::preferences::Preferences;
 pub use add_remove_buttons::*;
 pub use color::*;
 pub use drag::*;
@@ -20,11 +21,13 @@ use crate::bundles::{
     spawn_inflow, spawn_interface, spawn_interface_subsystem, spawn_outflow, spawn_subsystem,
 };
 use crate::components::*;
+use crate::events::CreateButtonClick;
 use crate::resources::{
     FixedSystemElementGeometriesByNestingLevel, FocusedSystem, StrokeTessellator, Zoom,
 };
 use crate::states::AppState;
 use crate::utils::combined_transform_of_entity_until_ancestor;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_mod_picking::prelude::*;
 use rust_decimal_macros::dec;
@@ -75,9 +78,25 @@ pub fn remove_unfocused_system_buttons(
     }
 }
 
+/// The zoom and the preferences that elements created from buttons are sized with.
+#[derive(SystemParam)]
+pub struct CreationScale<'w> {
+    zoom: Res<'w, Zoom>,
+    preferences: Res<'w, Preferences>,
+}
+
+/// Forwards clicks on create buttons to their handlers.
+pub fn on_create_button_pointer_click(
+    mut event: ListenerMut<Pointer<Click>>,
+    mut click_writer: EventWriter<CreateButtonClick>,
+) {
+    event.stop_propagation();
+    click_writer.send(CreateButtonClick(event.target));
+}
+
 pub fn on_subsystem_button_click(
     mut commands: Commands,
-    mut event: ListenerMut<Pointer<Click>>,
+    mut click_reader: EventReader<CreateButtonClick>,
     transform_query: Query<&Transform>,
     only_button_query: Query<(&CreateButton, Option<&Parent>)>,
     external_entity_query: Query<(Entity, &PickSelection, &Parent), With<ExternalEntity>>,
@@ -88,95 +107,109 @@ pub fn on_subsystem_button_click(
     mut meshes: ResMut<Assets<Mesh>>,
     mut stroke_tess: ResMut<StrokeTessellator>,
     mut fixed_system_element_geometries: ResMut<FixedSystemElementGeometriesByNestingLevel>,
-    zoom: Res<Zoom>,
+    scale: CreationScale,
 ) {
-    event.stop_propagation();
+    for CreateButtonClick(button_entity) in click_reader.read() {
+        let Ok((button, _)) = only_button_query.get(*button_entity) else {
+            continue;
+        };
+        if button.ty != CreateButtonType::Subsystem {
+            continue;
+        }
 
-    let mut inflows = vec![];
-    let mut outflows = vec![];
+        let mut inflows = vec![];
+        let mut outflows = vec![];
 
-    let mut parent_system = Entity::PLACEHOLDER;
+        let mut parent_system = Entity::PLACEHOLDER;
 
-    for (external_entity, selection, parent) in &external_entity_query {
-        if selection.is_selected {
-            parent_system = parent.get();
+        for (external_entity, selection, parent) in &external_entity_query {
+            if selection.is_selected {
+                parent_system = parent.get();
 
-            for (flow_entity, start_connection, end_connection) in &flow_connection_query {
-                if start_connection.target == external_entity {
-                    outflows.push(flow_entity);
-                } else if end_connection.target == external_entity {
-                    inflows.push(flow_entity);
+                for (flow_entity, start_connection, end_connection) in &flow_connection_query {
+                    if start_connection.target == external_entity {
+                        outflows.push(flow_entity);
+                    } else if end_connection.target == external_entity {
+                        inflows.push(flow_entity);
+                    }
                 }
-            }
 
-            commands.entity(external_entity).despawn_recursive();
-            commands
-                .entity(parent_system)
-                .remove_children(&[external_entity]);
+                commands.entity(external_entity).despawn_recursive();
+                commands
+                    .entity(parent_system)
+                    .remove_children(&[external_entity]);
+            }
         }
-    }
 
-    let transform = transform_query
-        .get(event.target)
-        .expect("After on click this has to exist");
-
-    spawn_subsystem(
-        &mut commands,
-        parent_system,
-        &system_query,
-        &nesting_level_query,
-        &flow_query,
-        &inflows,
-        &outflows,
-        &mut fixed_system_element_geometries,
-        &mut meshes,
-        &mut stroke_tess,
-        **zoom,
-        "Subsystem",
-        "",
-        transform.translation.truncate(),
-    );
-
-    despawn_create_button(&mut commands, event.target, &only_button_query);
+        let transform = transform_query
+            .get(*button_entity)
+            .expect("After on click this has to exist");
+
+        spawn_subsystem(
+            &mut commands,
+            parent_system,
+            &system_query,
+            &nesting_level_query,
+            &flow_query,
+            &inflows,
+            &outflows,
+            &mut fixed_system_element_geometries,
+            &mut meshes,
+            &mut stroke_tess,
+            **scale.zoom,
+            scale.preferences.subsystem_scaling_factor,
+            "Subsystem",
+            "",
+            transform.translation.truncate(),
+        );
+
+        despawn_create_button(&mut commands, *button_entity, &only_button_query);
+    }
 }
 
 pub fn on_flow_terminal_button_click(
     mut commands: Commands,
-    mut event: ListenerMut<Pointer<Click>>,
+    mut click_reader: EventReader<CreateButtonClick>,
     only_button_query: Query<(&CreateButton, Option<&Parent>)>,
     mut pick_selection_query: Query<&mut PickSelection>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
-    event.stop_propagation();
-
-    do_deselect_all(&mut pick_selection_query);
+    for CreateButtonClick(button_entity) in click_reader.read() {
+        let Ok((button, _)) = only_button_query.get(*button_entity) else {
+            continue;
+        };
+        if !matches!(
+            button.ty,
+            CreateButtonType::FlowTerminalStart | CreateButtonType::FlowTerminalEnd
+        ) {
+            continue;
+        }
 
-    let (button, _) = only_button_query
-        .get(event.target)
-        .expect("After on click this has to exist");
+        do_deselect_all(&mut pick_selection_query);
 
-    match button.ty {
-        CreateButtonType::FlowTerminalStart => {
-            commands
-                .entity(button.connection_source)
-                .insert(FlowTerminalSelecting::Start);
-            next_state.set(AppState::FlowTerminalSelection);
-        }
-        CreateButtonType::FlowTerminalEnd => {
-            commands
-                .entity(button.connection_source)
-                .insert(FlowTerminalSelecting::End);
-            next_state.set(AppState::FlowTerminalSelection);
+        match button.ty {
+            CreateButtonType::FlowTerminalStart => {
+                commands
+                    .entity(button.connection_source)
+                    .insert(FlowTerminalSelecting::Start);
+                next_state.set(AppState::FlowTerminalSelection);
+            }
+            CreateButtonType::FlowTerminalEnd => {
+                commands
+                    .entity(button.connection_source)
+                    .insert(FlowTerminalSelecting::End);
+                next_state.set(AppState::FlowTerminalSelection);
+            }
+            _ => unreachable!("The other types are handled in other systems"),
         }
-        _ => unreachable!("The other types are handled in other event listeners"),
-    }
 
-    despawn_create_button(&mut commands, event.target, &only_button_query);
+        despawn_create_button(&mut commands, *button_entity, &only_button_query);
+    }
 }
 
 pub fn on_external_entity_create_button_click(
     mut commands: Commands,
-    mut event: ListenerMut<Pointer<Click>>,
+    mut click_reader: EventReader<CreateButtonClick>,
     button_query: Query<(&CreateButton, &Transform)>,
     only_button_query: Query<(&CreateButton, Option<&Parent>)>,
     mut pick_selection_query: Query<&mut PickSelection>,
@@ -186,66 +219,71 @@ pub fn on_external_entity_create_button_click(
     mut meshes: ResMut<Assets<Mesh>>,
     mut stroke_tess: ResMut<StrokeTessellator>,
     mut fixed_system_element_geometries: ResMut<FixedSystemElementGeometriesByNestingLevel>,
-    zoom: Res<Zoom>,
+    scale: CreationScale,
 ) {
-    event.stop_propagation();
-
-    do_deselect_all(&mut pick_selection_query);
-
-    let (button, transform) = button_query
-        .get(event.target)
-        .expect("After on click this has to exist");
+    for CreateButtonClick(button_entity) in click_reader.read() {
+        let Ok((button, transform)) = button_query.get(*button_entity) else {
+            continue;
+        };
+        if !matches!(button.ty, CreateButtonType::Source | CreateButtonType::Sink) {
+            continue;
+        }
 
-    match button.ty {
-        CreateButtonType::Source => spawn_external_entity(
-            &mut commands,
-            &subsystem_query,
-            &nesting_query,
-            **focused_system,
-            InterfaceType::Import,
-            button
-                .substance_type
-                .expect("Source button must have a substance type"),
-            button.connection_source,
-            transform,
-            &mut fixed_system_element_geometries,
-            **zoom,
-            true,
-            &mut meshes,
-            &mut stroke_tess,
-            "Source",
-            "",
-            true,
-        ),
-        CreateButtonType::Sink => spawn_external_entity(
-            &mut commands,
-            &subsystem_query,
-            &nesting_query,
-            **focused_system,
-            InterfaceType::Export,
-            button
-                .substance_type
-                .expect("Sink button must have a substance type"),
-            button.connection_source,
-            transform,
-            &mut fixed_system_element_geometries,
-            **zoom,
-            true,
-            &mut meshes,
-            &mut stroke_tess,
-            "Sink",
-            "",
-            true,
-        ),
-        _ => unreachable!("The other types are handled in other event listeners"),
-    };
+        do_deselect_all(&mut pick_selection_query);
+
+        match button.ty {
+            CreateButtonType::Source => spawn_external_entity(
+                &mut commands,
+                &subsystem_query,
+                &nesting_query,
+                **focused_system,
+                InterfaceType::Import,
+                button
+                    .substance_type
+                    .expect("Source button must have a substance type"),
+                button.connection_source,
+                transform,
+                &mut fixed_system_element_geometries,
+                **scale.zoom,
+                scale.preferences.subsystem_scaling_factor,
+                true,
+                &mut meshes,
+                &mut stroke_tess,
+                "Source",
+                "",
+                true,
+            ),
+            CreateButtonType::Sink => spawn_external_entity(
+                &mut commands,
+                &subsystem_query,
+                &nesting_query,
+                **focused_system,
+                InterfaceType::Export,
+                button
+                    .substance_type
+                    .expect("Sink button must have a substance type"),
+                button.connection_source,
+                transform,
+                &mut fixed_system_element_geometries,
+                **scale.zoom,
+                scale.preferences.subsystem_scaling_factor,
+                true,
+                &mut meshes,
+                &mut stroke_tess,
+                "Sink",
+                "",
+                true,
+            ),
+            _ => unreachable!("The other types are handled in other systems"),
+        };
 
-    despawn_create_button(&mut commands, event.target, &only_button_query);
+        despawn_create_button(&mut commands, *button_entity, &only_button_query);
+    }
 }
 
 pub fn on_create_button_click(
     mut commands: Commands,
-    mut event: ListenerMut<Pointer<Click>>,
+    mut click_reader: EventReader<CreateButtonClick>,
     button_query: Query<(&CreateButton, &Transform)>,
     only_button_query: Query<(&CreateButton, Option<&Parent>)>,
     flow_interface_query: Query<(
@@ -264,127 +302,144 @@ pub fn on_create_button_click(
     mut meshes: ResMut<Assets<Mesh>>,
     mut stroke_tess: ResMut<StrokeTessellator>,
     mut fixed_system_element_geometries: ResMut<FixedSystemElementGeometriesByNestingLevel>,
-    zoom: Res<Zoom>,
+    scale: CreationScale,
 ) {
-    event.stop_propagation();
-
-    do_deselect_all(&mut pick_selection_query);
-
-    let (button, transform) = button_query
-        .get(event.target)
-        .expect("After on click this has to exist");
-
-    let nesting_level = NestingLevel::current(**focused_system, &nesting_query);
+    for CreateButtonClick(button_entity) in click_reader.read() {
+        let Ok((button, transform)) = button_query.get(*button_entity) else {
+            continue;
+        };
+        if !matches!(
+            button.ty,
+            CreateButtonType::ImportInterface
+                | CreateButtonType::ExportInterface
+                | CreateButtonType::Inflow
+                | CreateButtonType::Outflow
+                | CreateButtonType::InterfaceSubsystem { .. }
+        ) {
+            continue;
+        }
 
-    match button.ty {
-        CreateButtonType::ImportInterface => spawn_interface(
-            &mut commands,
-            InterfaceType::Import,
-            button
-                .substance_type
-                .expect("Interface button must have a substance type"),
-            button.connection_source,
-            transform,
-            nesting_level,
-            **focused_system,
-            &mut fixed_system_element_geometries,
-            **zoom,
-            true,
-            &mut meshes,
-            &mut stroke_tess,
-            "Interface",
-            "",
-        ),
-        CreateButtonType::ExportInterface => spawn_interface(
-            &mut commands,
-            InterfaceType::Export,
-            button
-                .substance_type
-                .expect("Interface button must have a substance type"),
-            button.connection_source,
-            transform,
-            nesting_level,
-            **focused_system,
-            &mut fixed_system_element_geometries,
-            **zoom,
-            true,
-            &mut meshes,
-            &mut stroke_tess,
-            "Interface",
-            "",
-        ),
-        CreateButtonType::Inflow => spawn_inflow(
-            &mut commands,
-            &subsystem_query,
-            &nesting_query,
-            &system_query,
-            button.connection_source,
-            &combined_transform_of_entity_until_ancestor(
-                event.target,
-                subsystem_query
-                    .get(button.connection_source)
-                    .ok()
-                    .map(|s| s.parent_system),
-                &transform_query,
-                &parent_query,
+        do_deselect_all(&mut pick_selection_query);
+
+        let nesting_level = NestingLevel::current(**focused_system, &nesting_query);
+
+        match button.ty {
+            CreateButtonType::ImportInterface => spawn_interface(
+                &mut commands,
+                InterfaceType::Import,
+                button
+                    .substance_type
+                    .expect("Interface button must have a substance type"),
+                button.connection_source,
+                transform,
+                nesting_level,
+                **focused_system,
+                &mut fixed_system_element_geometries,
+                **scale.zoom,
+                scale.preferences.subsystem_scaling_factor,
+                true,
+                &mut meshes,
+                &mut stroke_tess,
+                "Interface",
+                "",
             ),
-            &mut stroke_tess,
-            &mut meshes,
-            **zoom,
-            true,
-            Default::default(),
-            InteractionUsability::Resource,
-            dec!(1),
-            "",
-            "Flow",
-            "",
-        ),
-        CreateButtonType::Outflow => spawn_outflow(
-            &mut commands,
-            &subsystem_query,
-            &nesting_query,
-            &system_query,
-            button.connection_source,
-            &combined_transform_of_entity_until_ancestor(
-                event.target,
-                subsystem_query
-                    .get(button.connection_source)
-                    .ok()
-                    .map(|s| s.parent_system),
-                &transform_query,
-                &parent_query,
+            CreateButtonType::ExportInterface => spawn_interface(
+                &mut commands,
+                InterfaceType::Export,
+                button
+                    .substance_type
+                    .expect("Interface button must have a substance type"),
+                button.connection_source,
+                transform,
+                nesting_level,
+                **focused_system,
+                &mut fixed_system_element_geometries,
+                **scale.zoom,
+                scale.preferences.subsystem_scaling_factor,
+                true,
+                &mut meshes,
+                &mut stroke_tess,
+                "Interface",
+                "",
+            ),
+            CreateButtonType::Inflow => spawn_inflow(
+                &mut commands,
+                &subsystem_query,
+                &nesting_query,
+                &system_query,
+                button.connection_source,
+                &combined_transform_of_entity_until_ancestor(
+                    *button_entity,
+                    subsystem_query
+                        .get(button.connection_source)
+                        .ok()
+                        .map(|s| s.parent_system),
+                    &transform_query,
+                    &parent_query,
+                ),
+                &mut stroke_tess,
+                &mut meshes,
+                **scale.zoom,
+                scale.preferences.subsystem_scaling_factor,
+                scale.preferences.flow_length,
+                true,
+                Default::default(),
+                InteractionUsability::Resource,
+                dec!(1),
+                "",
+                "Flow",
+                "",
+            ),
+            CreateButtonType::Outflow => spawn_outflow(
+                &mut commands,
+                &subsystem_query,
+                &nesting_query,
+                &system_query,
+                button.connection_source,
+                &combined_transform_of_entity_until_ancestor(
+                    *button_entity,
+                    subsystem_query
+                        .get(button.connection_source)
+                        .ok()
+                        .map(|s| s.parent_system),
+                    &transform_query,
+                    &parent_query,
+                ),
+                &mut stroke_tess,
+                &mut meshes,
+                **scale.zoom,
+                scale.preferences.subsystem_scaling_factor,
+                scale.preferences.flow_length,
+                true,
+                Default::default(),
+                InteractionUsability::Product,
+                dec!(1),
+                "",
+                "Flow",
+                "",
+            ),
+            CreateButtonType::InterfaceSubsystem {
+                is_child_of_interface,
+                interface_type,
+            } => spawn_interface_subsystem(
+                &mut commands,
+                is_child_of_interface,
+                interface_type,
+                button.connection_source,
+                &flow_interface_query,
+                &system_query,
+                &nesting_query,
+                &focused_system,
+                &mut meshes,
+                **scale.zoom,
+                scale.preferences.subsystem_scaling_factor,
+                "Subsystem",
+                "",
             ),
-            &mut stroke_tess,
-            &mut meshes,
-            **zoom,
-            true,
-            Default::default(),
-            InteractionUsability::Product,
-            dec!(1),
-            "",
-            "Flow",
-            "",
-        ),
-        CreateButtonType::InterfaceSubsystem {
-            is_child_of_interface,
-            interface_type,
-        } => spawn_interface_subsystem(
-            &mut commands,
-            is_child_of_interface,
-            interface_type,
-            button.connection_source,
-            &flow_interface_query,
-            &system_query,
-            &nesting_query,
-            &focused_system,
-            &mut meshes,
-            **zoom,
-            "Subsystem",
-            "",
-        ),
 
-        _ => unreachable!("The other types are handled in other event listeners"),
-    };
+            _ => unreachable!("The other types are handled in other systems"),
+        };
 
-    despawn_create_button(&mut commands, event.target, &only_button_query);
+        despawn_create_button(&mut commands, *button_entity, &only_button_query);
+    }
 }
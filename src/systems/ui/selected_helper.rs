@@ -1,6 +1,9 @@
 use crate::components::*;
 use crate::constants::*;
+use crate::plugins::layout::FlowRoute;
 use crate::plugins::mouse_interaction::{PickParent, PickSelection};
+use crate::plugins::preferences::Preferences;
+use crate::plugins::theme::{srgb, Themes};
 use crate::resources::*;
 use crate::systems::create_path_from_flow_curve;
 use bevy::prelude::*;
@@ -16,6 +19,7 @@ pub fn spawn_selected_system(
         ),
     >,
     zoom: Res<Zoom>,
+    themes: Res<Themes>,
 ) {
     for (selected_entity, selection, system) in &system_query {
         if selection.is_selected {
@@ -33,7 +37,10 @@ pub fn spawn_selected_system(
                         }),
                         ..default()
                     },
-                    Stroke::new(Color::WHITE, SYSTEM_SELECTED_INNER_LINE_WIDTH),
+                    Stroke::new(
+                        srgb(themes.current().selection),
+                        SYSTEM_SELECTED_INNER_LINE_WIDTH,
+                    ),
                 ))
                 .id();
 
@@ -56,6 +63,7 @@ pub fn spawn_selected_interface(
         ),
     >,
     fixed_system_element_geometries: Res<FixedSystemElementGeometriesByNestingLevel>,
+    themes: Res<Themes>,
 ) {
     for (selected_entity, selection, nesting_level) in &interface_query {
         if selection.is_selected {
@@ -71,7 +79,10 @@ pub fn spawn_selected_interface(
                         .expect("Geometries added in spawn_interface")
                         .interface
                         .clone(),
-                    Stroke::new(Color::WHITE, INTERFACE_SELECTED_INNER_LINE_WIDTH),
+                    Stroke::new(
+                        srgb(themes.current().selection),
+                        INTERFACE_SELECTED_INNER_LINE_WIDTH,
+                    ),
                 ))
                 .id();
 
@@ -86,19 +97,28 @@ pub fn spawn_selected_interface(
 pub fn spawn_selected_flow(
     mut commands: Commands,
     curve_query: Query<
-        (Entity, &FlowCurve, &PickSelection, &NestingLevel),
+        (
+            Entity,
+            &FlowCurve,
+            &PickSelection,
+            &NestingLevel,
+            Option<&FlowRoute>,
+        ),
         (
             Changed<PickSelection>,
             Without<SelectedHighlightHelperAdded>,
         ),
     >,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
+    themes: Res<Themes>,
 ) {
-    for (selected_entity, flow_curve, selection, nesting_level) in &curve_query {
+    for (selected_entity, flow_curve, selection, nesting_level, route) in &curve_query {
         if selection.is_selected {
             let curve_path = create_path_from_flow_curve(
                 flow_curve,
-                NestingLevel::compute_scale(**nesting_level, **zoom),
+                route.map_or(&[], |r| &r.waypoints),
+                preferences.nesting_scale(**nesting_level, **zoom),
             );
 
             let helper_entity = commands
@@ -111,7 +131,10 @@ pub fn spawn_selected_flow(
                         },
                         ..default()
                     },
-                    Stroke::new(Color::WHITE, FLOW_SELECTED_INNER_LINE_WIDTH),
+                    Stroke::new(
+                        srgb(themes.current().selection),
+                        FLOW_SELECTED_INNER_LINE_WIDTH,
+                    ),
                 ))
                 .id();
 
@@ -134,6 +157,7 @@ pub fn spawn_selected_external_entity(
         ),
     >,
     fixed_system_element_geometries: Res<FixedSystemElementGeometriesByNestingLevel>,
+    themes: Res<Themes>,
 ) {
     for (selected_entity, selection, nesting_level) in &external_entity_query {
         if selection.is_selected {
@@ -149,7 +173,10 @@ pub fn spawn_selected_external_entity(
                         .external_entity
                         .clone(),
                     PickParent,
-                    Stroke::new(Color::WHITE, EXTERNAL_ENTITY_SELECTED_INNER_LINE_WIDTH),
+                    Stroke::new(
+                        srgb(themes.current().selection),
+                        EXTERNAL_ENTITY_SELECTED_INNER_LINE_WIDTH,
+                    ),
                 ))
                 .id();
 
@@ -163,19 +190,26 @@ pub fn spawn_selected_external_entity(
 
 pub fn update_selected_flow_curve(
     flow_curve_query: Query<
-        (&FlowCurve, &SelectedHighlightHelperAdded, &NestingLevel),
-        Changed<FlowCurve>,
+        (
+            &FlowCurve,
+            &SelectedHighlightHelperAdded,
+            &NestingLevel,
+            Option<&FlowRoute>,
+        ),
+        Or<(Changed<FlowCurve>, Changed<FlowRoute>)>,
     >,
     mut selected_query: Query<&mut Path>,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
 ) {
-    for (flow_curve, helper, nesting_level) in &flow_curve_query {
+    for (flow_curve, helper, nesting_level, route) in &flow_curve_query {
         let mut path = selected_query
             .get_mut(helper.helper_entity)
             .expect("Helper entity should exist");
         let curve_path = create_path_from_flow_curve(
             flow_curve,
-            NestingLevel::compute_scale(**nesting_level, **zoom),
+            route.map_or(&[], |r| &r.waypoints),
+            preferences.nesting_scale(**nesting_level, **zoom),
         );
 
         *path = curve_path;
@@ -5,21 +5,22 @@ use crate::bundles::{
     aabb_from_radius, get_system_geometry_from_radius, FixedSystemElementGeometry,
 };
 use crate::components::*;
-use crate::constants::{
-    BUTTON_WIDTH_HALF, EXTERNAL_ENTITY_LINE_WIDTH, LABEL_SCALE_VISIBILITY_THRESHOLD,
-    SCALE_VISIBILITY_THRESHOLD,
-};
+use crate::constants::{BUTTON_WIDTH_HALF, WHEEL_LINE_HEIGHT};
 use crate::plugins::label::LabelContainer;
 use crate::plugins::lyon_selection::HighlightBundles;
+use crate::plugins::preferences::Preferences;
 use crate::resources::{
     build_external_entity_aabb_half_extents, build_external_entity_path,
     build_interface_aabb_half_extends, build_interface_path, build_interface_simplified_mesh,
-    FixedSystemElementGeometriesByNestingLevel, StrokeTessellator, Zoom,
+    FixedSystemElementGeometriesByNestingLevel, StrokeTessellator, Zoom, ZoomLimits,
 };
 use crate::systems::tessellate_simplified_mesh;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::input::touchpad::TouchpadMagnify;
 use bevy::math::vec3;
 use bevy::prelude::*;
 use bevy::render::primitives::Aabb;
+use bevy::window::PrimaryWindow;
 use bevy_mod_picking::backends::raycast::bevy_mod_raycast::prelude::*;
 use bevy_prototype_lyon::prelude::*;
 
@@ -121,32 +122,110 @@ pub fn apply_zoom_to_incomplete_flows(
 /// Adjusts the 'Zoom' level based on keyboard input.
 ///
 /// Press the minus (-) key to zoom in, or press the equals (=) key to zoom out.
-pub fn control_zoom_from_keyboard(input: Res<ButtonInput<KeyCode>>, mut zoom: ResMut<Zoom>) {
+pub fn control_zoom_from_keyboard(
+    input: Res<ButtonInput<KeyCode>>,
+    mut zoom: ResMut<Zoom>,
+    zoom_limits: Res<ZoomLimits>,
+) {
     if input.just_pressed(KeyCode::Minus) {
-        zoom.mul(1.2);
+        zoom.mul_within(1.2, &zoom_limits);
     }
 
     if input.just_pressed(KeyCode::Equal) {
-        zoom.mul(0.8);
+        zoom.mul_within(0.8, &zoom_limits);
     }
 }
 
-// TODO
-// pub fn control_zoom_from_mouse_wheel(
-//     mut scroll_events: EventReader<MouseWheel>,
-//     mut zoom: ResMut<Zoom>,
-// ) {
-//     for event in scroll_events.read() {
-//         match event.unit {
-//             MouseScrollUnit::Line => {
-//                 zoom.mul(1.0 + event.y * 0.01);
-//             }
-//             MouseScrollUnit::Pixel => {
-//                 zoom.mul(1.0 + event.y * 0.001);
-//             }
-//         }
-//     }
-// }
+/// Zooms by `fac` while keeping the world point under the cursor in place.
+///
+/// The camera position is given for the current zoom. It's scaled along with the new zoom by
+/// `apply_zoom_to_camera_position`, so it's moved here such that the point under the cursor ends
+/// up at the same window position after scaling.
+fn zoom_around_cursor(
+    fac: f32,
+    zoom: &mut Zoom,
+    zoom_limits: &ZoomLimits,
+    camera_transform: &mut Transform,
+    window: &Window,
+) {
+    let fac = zoom.mul_within(fac, zoom_limits);
+    if (fac - 1.0).abs() <= f32::EPSILON {
+        return;
+    }
+
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let cursor_offset = Vec2::new(
+        cursor.x - window.width() * 0.5,
+        window.height() * 0.5 - cursor.y,
+    );
+
+    let camera = camera_transform.translation.truncate();
+    let anchor = camera + cursor_offset;
+    let new_camera = anchor - (anchor - camera) / fac;
+    camera_transform.translation = new_camera.extend(camera_transform.translation.z);
+}
+
+/// Zooms with the mouse wheel or two finger scrolling around the cursor.
+pub fn control_zoom_from_mouse_wheel(
+    mut scroll_events: EventReader<MouseWheel>,
+    mut zoom: ResMut<Zoom>,
+    zoom_limits: Res<ZoomLimits>,
+    preferences: Res<Preferences>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let pixels = scroll_events
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * WHEEL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum::<f32>();
+
+    if pixels == 0.0 {
+        return;
+    }
+    let (Ok(mut camera_transform), Ok(window)) =
+        (camera_query.get_single_mut(), window_query.get_single())
+    else {
+        return;
+    };
+
+    zoom_around_cursor(
+        (pixels * preferences.wheel_zoom_speed).exp(),
+        &mut zoom,
+        &zoom_limits,
+        &mut camera_transform,
+        window,
+    );
+}
+
+/// Zooms with the trackpad pinch gesture around the cursor.
+pub fn control_zoom_from_pinch(
+    mut magnify_events: EventReader<TouchpadMagnify>,
+    mut zoom: ResMut<Zoom>,
+    zoom_limits: Res<ZoomLimits>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let fac = magnify_events
+        .read()
+        .map(|event| (1.0 + event.0).max(0.1))
+        .product::<f32>();
+
+    if fac == 1.0 {
+        return;
+    }
+    let (Ok(mut camera_transform), Ok(window)) =
+        (camera_query.get_single_mut(), window_query.get_single())
+    else {
+        return;
+    };
+
+    zoom_around_cursor(fac, &mut zoom, &zoom_limits, &mut camera_transform, window);
+}
 
 //noinspection ALL
 pub fn apply_zoom_to_system_geometries(
@@ -163,11 +242,12 @@ pub fn apply_zoom_to_system_geometries(
     mut fixed_system_element_geometries: ResMut<FixedSystemElementGeometriesByNestingLevel>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut tess: ResMut<StrokeTessellator>,
+    preferences: Res<Preferences>,
 ) {
     let zoom = **zoom;
 
     for (nesting_level, geometries) in &mut **fixed_system_element_geometries {
-        let scale = NestingLevel::compute_scale(*nesting_level, zoom);
+        let scale = preferences.nesting_scale(*nesting_level, zoom);
 
         let external_entity_path = build_external_entity_path(scale);
         geometries.external_entity.simplified.mesh =
@@ -239,30 +319,102 @@ pub fn apply_zoom_to_strokes(
         Without<HighlightBundles<Stroke, Stroke>>,
     >,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
 ) {
     for (nesting_level, mut highlight, mut visibility) in &mut highlight_query {
-        let scale = NestingLevel::compute_scale(**nesting_level, **zoom);
-        highlight.idle.options.line_width = scale * EXTERNAL_ENTITY_LINE_WIDTH;
-        // TODO : this assumes only one line width which is the case right now
-        // highlight.selected.options.line_width = (scale * EXTERNAL_ENTITY_SELECTED_LINE_WIDTH);
-
-        apply_visibility(&mut visibility, scale, SCALE_VISIBILITY_THRESHOLD);
+        apply_zoom_to_highlight_stroke(
+            nesting_level,
+            &mut highlight,
+            &mut visibility,
+            **zoom,
+            &preferences,
+        );
     }
 
     for (nesting_level, mut stroke, mut visibility) in &mut stroke_query {
-        let scale = NestingLevel::compute_scale(**nesting_level, **zoom);
-        let line_width = scale * EXTERNAL_ENTITY_LINE_WIDTH;
-        stroke.options.line_width = line_width;
+        apply_zoom_to_stroke(
+            nesting_level,
+            &mut stroke,
+            &mut visibility,
+            **zoom,
+            &preferences,
+        );
+    }
+}
 
-        apply_visibility(&mut visibility, scale, SCALE_VISIBILITY_THRESHOLD);
+/// Adjusts the line width and `Visibility` state of newly added strokes so that they use the
+/// line width of the preferences right away.
+pub fn apply_zoom_to_added_strokes(
+    mut highlight_query: Query<
+        (
+            &NestingLevel,
+            &mut HighlightBundles<Stroke, Stroke>,
+            &mut Visibility,
+        ),
+        Added<HighlightBundles<Stroke, Stroke>>,
+    >,
+    mut stroke_query: Query<
+        (&NestingLevel, &mut Stroke, &mut Visibility),
+        (Added<Stroke>, Without<HighlightBundles<Stroke, Stroke>>),
+    >,
+    zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
+) {
+    for (nesting_level, mut highlight, mut visibility) in &mut highlight_query {
+        apply_zoom_to_highlight_stroke(
+            nesting_level,
+            &mut highlight,
+            &mut visibility,
+            **zoom,
+            &preferences,
+        );
+    }
+
+    for (nesting_level, mut stroke, mut visibility) in &mut stroke_query {
+        apply_zoom_to_stroke(
+            nesting_level,
+            &mut stroke,
+            &mut visibility,
+            **zoom,
+            &preferences,
+        );
     }
 }
 
+fn apply_zoom_to_highlight_stroke(
+    nesting_level: &NestingLevel,
+    highlight: &mut HighlightBundles<Stroke, Stroke>,
+    visibility: &mut Mut<Visibility>,
+    zoom: f32,
+    preferences: &Preferences,
+) {
+    let scale = preferences.nesting_scale(**nesting_level, zoom);
+    highlight.idle.options.line_width = scale * preferences.line_width;
+    // TODO : this assumes only one line width which is the case right now
+    // highlight.selected.options.line_width = (scale * EXTERNAL_ENTITY_SELECTED_LINE_WIDTH);
+
+    apply_visibility(visibility, scale, preferences.scale_visibility_threshold);
+}
+
+fn apply_zoom_to_stroke(
+    nesting_level: &NestingLevel,
+    stroke: &mut Stroke,
+    visibility: &mut Mut<Visibility>,
+    zoom: f32,
+    preferences: &Preferences,
+) {
+    let scale = preferences.nesting_scale(**nesting_level, zoom);
+    stroke.options.line_width = scale * preferences.line_width;
+
+    apply_visibility(visibility, scale, preferences.scale_visibility_threshold);
+}
+
 /// Adjusts the scale and `Visibility` state of entities with a `ApplyZoomToScale` component
 /// based on the current `Zoom` level.
 pub fn apply_zoom_to_scale(
     mut query: Query<(&mut Transform, &mut Visibility, &NestingLevel), With<ApplyZoomToScale>>,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
 ) {
     for (mut transform, mut visibility, nesting_level) in &mut query {
         apply_scale_and_visibility(
@@ -270,7 +422,8 @@ pub fn apply_zoom_to_scale(
             &mut transform,
             &mut visibility,
             **zoom,
-            SCALE_VISIBILITY_THRESHOLD,
+            &preferences,
+            preferences.scale_visibility_threshold,
         );
     }
 }
@@ -280,6 +433,7 @@ pub fn apply_zoom_to_scale(
 pub fn apply_zoom_to_label(
     mut query: Query<(&mut Transform, &mut Visibility, &NestingLevel), With<LabelContainer>>,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
 ) {
     for (mut transform, mut visibility, nesting_level) in &mut query {
         apply_scale_and_visibility(
@@ -287,7 +441,8 @@ pub fn apply_zoom_to_label(
             &mut transform,
             &mut visibility,
             **zoom,
-            LABEL_SCALE_VISIBILITY_THRESHOLD,
+            &preferences,
+            preferences.label_scale_visibility_threshold,
         );
     }
 }
@@ -299,9 +454,10 @@ fn apply_scale_and_visibility(
     transform: &mut Mut<Transform>,
     visibility: &mut Mut<Visibility>,
     zoom: f32,
+    preferences: &Preferences,
     threshold: f32,
 ) {
-    let scale = NestingLevel::compute_scale(**nesting_level, zoom);
+    let scale = preferences.nesting_scale(**nesting_level, zoom);
     transform.scale = vec3(scale, scale, 1.0);
 
     apply_visibility(visibility, scale, threshold);
@@ -320,6 +476,7 @@ fn apply_visibility(visibility: &mut Mut<Visibility>, scale: f32, threshold: f32
 pub fn apply_zoom_to_added_label(
     mut query: Query<(&mut Transform, &mut Visibility, &NestingLevel), Added<LabelContainer>>,
     zoom: Res<Zoom>,
+    preferences: Res<Preferences>,
 ) {
     for (mut transform, mut visibility, nesting_level) in &mut query {
         apply_scale_and_visibility(
@@ -327,7 +484,8 @@ pub fn apply_zoom_to_added_label(
             &mut transform,
             &mut visibility,
             **zoom,
-            LABEL_SCALE_VISIBILITY_THRESHOLD,
+            &preferences,
+            preferences.label_scale_visibility_threshold,
         );
     }
 }
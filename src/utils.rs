@@ -149,3 +149,71 @@ pub fn transform_from_point2d_and_direction(point2d: Vec2, direction: Vec2) -> T
     Transform::from_translation(point2d.extend(0.0))
         .with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x)))
 }
+
+/// Path of a file in the user's configuration directory of the app. `None` if the home
+/// directory can't be determined.
+pub fn user_config_path(file_name: &str) -> Option<std::path::PathBuf> {
+    let config_dir = if cfg!(target_os = "windows") {
+        std::path::PathBuf::from(std::env::var_os("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        std::path::PathBuf::from(std::env::var_os("HOME")?).join("Library/Application Support")
+    } else {
+        match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) => std::path::PathBuf::from(dir),
+            None => std::path::PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        }
+    };
+
+    Some(config_dir.join("deep-systems-analysis").join(file_name))
+}
+
+/// Names of the configuration files that exist but couldn't be read. They aren't overwritten so
+/// the user can fix them.
+static UNREADABLE_USER_CONFIGS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+/// Reads a user configuration file. Returns the default if the file doesn't exist or is invalid.
+/// Invalid files are logged and protected from being overwritten by [`save_user_config`].
+pub fn load_user_config<T: serde::de::DeserializeOwned + Default>(file_name: &str) -> T {
+    let Some(path) = user_config_path(file_name) else {
+        return T::default();
+    };
+    let Ok(bytes) = std::fs::read(&path) else {
+        return T::default();
+    };
+
+    match serde_json::from_slice(&bytes) {
+        Ok(config) => config,
+        Err(error) => {
+            error!(
+                "Failed to read {}, using the defaults until it's fixed: {}",
+                path.display(),
+                error
+            );
+            if let Ok(mut unreadable) = UNREADABLE_USER_CONFIGS.lock() {
+                unreadable.push(file_name.to_string());
+            }
+            T::default()
+        }
+    }
+}
+
+/// Writes a user configuration file and creates the configuration directory if needed.
+/// Files that failed to load aren't overwritten.
+pub fn save_user_config<T: serde::Serialize>(file_name: &str, config: &T) -> Result<(), String> {
+    let path = user_config_path(file_name).ok_or("No home directory found")?;
+    let is_unreadable = UNREADABLE_USER_CONFIGS
+        .lock()
+        .is_ok_and(|unreadable| unreadable.iter().any(|name| name == file_name));
+    if is_unreadable {
+        return Err(format!(
+            "{} couldn't be read when the app started and isn't overwritten",
+            path.display()
+        ));
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}